@@ -1,4 +1,4 @@
-//use std::path::PathBuf;
+use std::path::PathBuf;
 
 /// Lantern server
 #[derive(Debug, argh::FromArgs)]
@@ -10,6 +10,10 @@ pub struct CliOptions {
     /// logging level (0 = Info, 1 = Debug, 2 = Trace) [env LANTERN_VERBOSE]
     #[argh(option, short = 'v')]
     pub verbose: Option<u8>,
+
+    /// path to TOML config file, reloaded on SIGHUP [env LANTERN_CONFIG]
+    #[argh(option, short = 'c')]
+    pub config: Option<PathBuf>,
 }
 
 impl CliOptions {
@@ -27,6 +31,10 @@ impl CliOptions {
             }
         }
 
+        if args.config.is_none() {
+            args.config = std::env::var_os("LANTERN_CONFIG").map(PathBuf::from);
+        }
+
         Ok(args)
     }
 }
@@ -81,3 +81,31 @@ pub struct Config {
     pub local: LocalConfig,
     pub shared: SharedConfig,
 }
+
+impl LocalConfig {
+    /// Carries over sections that are only read at startup from the `current` config,
+    /// as changing them would require a restart.
+    pub fn retain_startup_sections(&mut self, current: &LocalConfig) {
+        self.general = current.general.clone();
+        self.paths = current.paths.clone();
+        self.keys = current.keys.clone();
+        self.rpc = current.rpc.clone();
+    }
+
+    /// Checks that any changed values can be applied to the running server.
+    pub fn validate_reload(&self, current: &LocalConfig) -> Result<(), &'static str> {
+        if self.web.cert_path != current.web.cert_path && !self.web.cert_path.exists() {
+            return Err("TLS certificate path does not exist");
+        }
+
+        if self.web.key_path != current.web.key_path && !self.web.key_path.exists() {
+            return Err("TLS key path does not exist");
+        }
+
+        if self.web.web_path != current.web.web_path && !self.web.web_path.is_dir() {
+            return Err("Web path is not a directory");
+        }
+
+        Ok(())
+    }
+}
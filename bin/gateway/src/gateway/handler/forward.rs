@@ -0,0 +1,71 @@
+use futures::{future, stream, Stream, StreamExt};
+
+use ::rpc::{
+    event::{ClientCommand, ClientCommandReply},
+    request::RpcRequest,
+    stream::RpcRecvReader,
+    DeserializeExt,
+};
+use sdk::api::error::ApiError;
+
+use crate::prelude::*;
+
+use super::item::Item;
+
+/// Forwards a client command to the node that owns its state, resolved by the `RpcManager`.
+///
+/// Replies are yielded as [`Item::Reply`] so they can be pushed into the event loop
+/// of the originating connection.
+pub fn forward_command(
+    state: GatewayServerState,
+    user_id: UserId,
+    conn_id: ConnectionId,
+    cmd: ClientCommand,
+) -> impl Stream<Item = Item> + Send + 'static {
+    let req = RpcRequest::ForwardedClientCommand { user_id, conn_id, cmd };
+
+    stream::once(async move { state.rpc.send(&req).await })
+        .filter_map(|res| {
+            future::ready(match res {
+                Ok(recv) => Some(RpcRecvReader::new(recv)),
+                Err(e) => {
+                    log::warn!("Error forwarding client command: {e}");
+                    None
+                }
+            })
+        })
+        .flat_map(|recv| {
+            stream::unfold(recv, |mut recv| async move {
+                let reply = match recv.recv::<Result<ClientCommandReply, ApiError>>().await {
+                    Ok(Some(reply)) => reply.deserialize_full(),
+                    Ok(None) => return None,
+                    Err(e) => {
+                        log::warn!("Error receiving forwarded command reply: {e}");
+                        return None;
+                    }
+                };
+
+                match reply {
+                    Ok(Ok(reply)) => Some((Item::Reply(reply), recv)),
+                    Ok(Err(e)) => {
+                        log::debug!("Forwarded client command failed: {e:?}");
+                        None
+                    }
+                    Err(_) => {
+                        log::warn!("Error decoding forwarded command reply");
+                        None
+                    }
+                }
+            })
+        })
+}
+
+/// Forwards a client command without waiting for any replies, such as when the connection has closed.
+pub fn forward_command_detached(
+    state: GatewayServerState,
+    user_id: UserId,
+    conn_id: ConnectionId,
+    cmd: ClientCommand,
+) {
+    tokio::spawn(forward_command(state, user_id, conn_id, cmd).for_each(|_| future::ready(())));
+}
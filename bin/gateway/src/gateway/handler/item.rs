@@ -1,6 +1,7 @@
 use tokio::sync::broadcast::error::RecvError;
 use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
 
+use rpc::event::ClientCommandReply;
 use sdk::models::gateway::message::ClientMsg;
 
 use super::Event;
@@ -8,6 +9,8 @@ use super::Event;
 pub enum Item {
     Event(Result<Event, EventError>),
     Msg(Result<ClientMsg, MessageIncomingError>),
+    /// Reply to a client command forwarded to another node
+    Reply(ClientCommandReply),
     Ping,
     MissedHeartbeat,
 }
@@ -43,4 +43,17 @@ impl ListenerTable {
             stream.map(|event| Item::Event(event.map_err(Into::into))).boxed()
         }));
     }
+
+    /// Abort and forget the listener for the given party or room, if any.
+    ///
+    /// Aborted streams are removed from the `SelectAll` automatically.
+    pub fn unregister(&mut self, id: Snowflake) -> bool {
+        match self.table.remove(&id) {
+            Some(handle) => {
+                handle.abort();
+                true
+            }
+            None => false,
+        }
+    }
 }
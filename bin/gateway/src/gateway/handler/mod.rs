@@ -21,6 +21,8 @@ use sdk::{
     },
 };
 
+use rpc::event::{ClientCommand, ClientCommandReply};
+
 use crate::{gateway::event::InternalEvent, prelude::*};
 
 use super::{
@@ -28,6 +30,7 @@ use super::{
     event::{self as events, Event, EventInner, ExternalEvent},
 };
 
+pub mod forward;
 pub mod item;
 pub mod listener_table;
 pub mod role_cache;
@@ -74,7 +77,12 @@ pub enum Loop<T> {
     Break,
 }
 
-pub async fn client_connection(ws: WebSocket, query: GatewayQueryParams, _addr: IpAddr, state: GatewayServerState) {
+pub async fn client_connection(
+    ws: WebSocket,
+    query: GatewayQueryParams,
+    _addr: IpAddr,
+    state: GatewayServerState,
+) {
     let (ws_tx, ws_rx) = ws.split();
 
     let (conn, conn_rx) = state.new_gateway_connection().await;
@@ -187,9 +195,7 @@ pub async fn client_connection(ws: WebSocket, query: GatewayQueryParams, _addr:
         tokio::spawn(async move {
             tokio::time::sleep(state.config().shared.presence_timeout).await;
 
-            //if let Err(e) = clear_presence(state, user_id, conn_id).await {
-            //    log::error!("Error clearing connection presence: {e}");
-            //}
+            forward::forward_command_detached(state, user_id, conn_id, ClientCommand::Disconnected);
         });
     }
 
@@ -198,7 +204,11 @@ pub async fn client_connection(ws: WebSocket, query: GatewayQueryParams, _addr:
 }
 
 impl ConnectionState {
-    pub async fn handle_event(&mut self, mut event: Event, events: &mut SelectAll<BoxStream<'_, Item>>) -> Loop<Result<Event, MessageOutgoingError>> {
+    pub async fn handle_event(
+        &mut self,
+        mut event: Event,
+        events: &mut SelectAll<BoxStream<'_, Item>>,
+    ) -> Loop<Result<Event, MessageOutgoingError>> {
         let e = match *event {
             EventInner::External(ref e) => e,
             EventInner::Internal(ref event) => {
@@ -268,14 +278,20 @@ impl ConnectionState {
 
                 if let Some(room_id) = e.room_id {
                     // skip event if user can't view room
-                    if !matches!(self.get_perm(user_id, room_id).await, Some(perms) if perms.contains(Permissions::VIEW_ROOM)) {
+                    if !matches!(self.get_perm(user_id, room_id).await, Some(perms) if perms.contains(Permissions::VIEW_ROOM))
+                    {
                         return Loop::Continue;
                     }
                 }
 
                 match e.msg {
                     ServerMsg::PartyCreate(ref payload) => {
-                        let subs = self.state.gateway.sub_and_activate_connection(user_id, self.conn.clone(), [payload.id], []).boxed().await;
+                        let subs = self
+                            .state
+                            .gateway
+                            .sub_and_activate_connection(user_id, self.conn.clone(), [payload.id], [])
+                            .boxed()
+                            .await;
 
                         self.listener_table.register_subs(events, subs);
                     }
@@ -286,10 +302,15 @@ impl ConnectionState {
                     }
                     ServerMsg::PartyDelete(ref payload) => {
                         // by cancelling a stream, it will be removed from the SelectStream automatically
-                        self.listener_table.get(&payload.id).map(|event_stream| event_stream.abort());
+                        self.listener_table.unregister(payload.id);
                     }
                     ServerMsg::RoomDelete(ref payload) => {
-                        self.listener_table.get(&payload.id).map(|event_stream| event_stream.abort());
+                        self.listener_table.unregister(payload.id);
+                    }
+                    // the current user was kicked from or left the party, so stop listening to it.
+                    // The event itself is still forwarded below.
+                    ServerMsg::MemberRemove(ref payload) if payload.inner.member.user.id == user_id => {
+                        self.listener_table.unregister(payload.inner.party_id);
                     }
                     _ => {}
                 }
@@ -313,8 +334,7 @@ impl ConnectionState {
                 true
             }
             // member events for the current user
-            ServerMsg::MemberUpdate(MemberUpdatePayload { ref inner }) |
-            ServerMsg::MemberRemove(MemberRemovePayload { ref inner }) if inner.member.user.id == user_id => {
+            ServerMsg::MemberUpdate(MemberUpdatePayload { ref inner }) if inner.member.user.id == user_id => {
                 // remove old roles and add new
                 self.roles.remove_party(inner.party_id);
 
@@ -322,6 +342,10 @@ impl ConnectionState {
 
                 true
             }
+            ServerMsg::MemberRemove(MemberRemovePayload { ref inner }) if inner.member.user.id == user_id => {
+                self.roles.remove_party(inner.party_id);
+                true
+            }
             ServerMsg::PartyDelete(ref p) => {
                 self.roles.remove_party(p.id);
                 true
@@ -340,7 +364,11 @@ impl ConnectionState {
         }
     }
 
-    pub async fn handle_msg(&mut self, msg: ClientMsg, events: &mut SelectAll<BoxStream<'_, Item>>) -> Loop<Result<Event, MessageOutgoingError>> {
+    pub async fn handle_msg(
+        &mut self,
+        msg: ClientMsg,
+        events: &mut SelectAll<BoxStream<'_, Item>>,
+    ) -> Loop<Result<Event, MessageOutgoingError>> {
         match msg {
             // Respond to heartbeats immediately.
             ClientMsg::Heartbeat(_) => Loop::Yield(Ok(events::HEARTBEAT_ACK.clone())),
@@ -355,30 +383,68 @@ impl ConnectionState {
                 log::error!("Attempted to resume connection");
                 Loop::Break
             }
-            ClientMsg::SetPresence(payload) => {
-                match self.user_id {
-                    None => {
-                        log::warn!("Attempted to set presence before identification");
-                        return Loop::Break;
-                    }
-                    Some(_user_id) => {
-                        //tokio::spawn(set_presence(state.clone(), user_id, conn.id, payload.inner.presence));
-                    }
-                }
+            ClientMsg::Unsubscribe(payload) => {
+                self.listener_table.unregister(payload.party_id);
+                Loop::Continue // no reply
+            }
+            // commands requiring server-side state are forwarded to the node that owns it,
+            // with any replies fed back into this event loop
+            ClientMsg::SetPresence(_) | ClientMsg::Subscribe(_) => {
+                let Some(user_id) = self.user_id else {
+                    log::warn!("Attempted to forward command before identification");
+                    return Loop::Break;
+                };
+
+                events.push(
+                    forward::forward_command(
+                        self.state.clone(),
+                        user_id,
+                        self.conn.id,
+                        ClientCommand::Regular(msg),
+                    )
+                    .boxed(),
+                );
 
-                Loop::Continue // no reply, so continue event loop
+                Loop::Continue // replies arrive later, if any
             }
-            ClientMsg::Subscribe(_) | ClientMsg::Unsubscribe(_) => {
-                log::error!("Unimplemented sub/unsub");
-                Loop::Continue // no reply
+        }
+    }
+
+    pub async fn handle_reply(
+        &mut self,
+        reply: ClientCommandReply,
+        events: &mut SelectAll<BoxStream<'_, Item>>,
+    ) -> Loop<Result<Event, MessageOutgoingError>> {
+        match reply {
+            ClientCommandReply::Msg(msg) => Loop::Yield(Ok(Event::new(msg, None))),
+            ClientCommandReply::Subscribed(party_id) => {
+                let Some(user_id) = self.user_id else {
+                    return Loop::Continue;
+                };
+
+                let subs = self
+                    .state
+                    .gateway
+                    .sub_and_activate_connection(user_id, self.conn.clone(), [party_id], [])
+                    .boxed()
+                    .await;
+
+                self.listener_table.register_subs(events, subs);
+
+                Loop::Continue
             }
         }
     }
 
-    pub async fn handle_item(&mut self, event: Item, events: &mut SelectAll<BoxStream<'_, Item>>) -> Loop<Result<Event, MessageOutgoingError>> {
+    pub async fn handle_item(
+        &mut self,
+        event: Item,
+        events: &mut SelectAll<BoxStream<'_, Item>>,
+    ) -> Loop<Result<Event, MessageOutgoingError>> {
         match event {
             Item::Event(Ok(event)) => self.handle_event(event, events).await,
             Item::Msg(Ok(msg)) => self.handle_msg(msg, events).await,
+            Item::Reply(reply) => self.handle_reply(reply, events).await,
 
             Item::MissedHeartbeat => Loop::Yield(Err(MessageOutgoingError::SocketClosed)),
 
@@ -145,6 +145,19 @@ impl Gateway {
         //crate::metrics::API_METRICS.load().add_event();
     }
 
+    /// Sends an event to every identified connection on this gateway
+    pub async fn broadcast_global_event(&self, event: Event) {
+        self.users
+            .scan_async(|_, users| {
+                for conn in users.values() {
+                    if let Err(e) = conn.tx.try_send(event.clone()) {
+                        log::warn!("Could not send global message to user connection: {}", e);
+                    }
+                }
+            })
+            .await;
+    }
+
     pub fn broadcast_event(&self, event: Event, party_id: PartyId) {
         match *event {
             EventInner::Internal(_) => log::debug!("broadcasting internal event"),
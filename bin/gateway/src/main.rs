@@ -8,6 +8,7 @@ pub mod allocator;
 pub mod cli;
 pub mod config;
 pub mod gateway;
+pub mod net;
 pub mod nexus;
 pub mod rpc;
 pub mod state;
@@ -44,8 +45,7 @@ async fn main() -> anyhow::Result<()> {
 
     log::debug!("Arguments: {:?}", args);
 
-    let mut local = config::LocalConfig::default();
-    ::config::Configuration::configure(&mut local);
+    let local: config::LocalConfig = ::config::load_file(args.config.as_deref())?;
 
     // setup full logger
     log::info!("Setting up log-file rotation in {}", local.paths.log_dir.display());
@@ -71,6 +71,7 @@ async fn main() -> anyhow::Result<()> {
     log::info!("Starting tasks...");
     let runner = tasks::TaskRunner::default();
     tasks::add_tasks(&state, &runner);
+    tasks::add_config_reload_task(&state, &runner, args.config.clone());
 
     log::trace!("Setting up shutdown signal for Ctrl+C");
     let shutdown = runner.signal();
@@ -79,6 +80,19 @@ async fn main() -> anyhow::Result<()> {
         shutdown.stop();
     });
 
+    #[cfg(unix)]
+    {
+        log::trace!("Setting up config reload signal for SIGHUP");
+        let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())?;
+        let state = state.clone();
+        tokio::spawn(async move {
+            while hangup.recv().await.is_some() {
+                log::info!("Received SIGHUP, reloading config");
+                state.config.trigger_reload();
+            }
+        });
+    }
+
     log::info!("Gateway server started");
     runner.wait().await?;
 
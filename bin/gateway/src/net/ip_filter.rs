@@ -2,7 +2,8 @@ use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 
 use arc_swap::ArcSwap;
-use iplist::IpSet;
+use iplist::{IpNet, IpSet};
+use rpc::request::IpBanList;
 
 pub trait AddrFilter {
     #[inline]
@@ -45,6 +46,17 @@ impl IpFilter {
         });
     }
 
+    /// Replaces the filter with the hardcoded bans plus the given list from the nexus
+    pub fn apply(&self, bans: &IpBanList) {
+        let mut ips = super::hardcoded_ip_bans::HARDCODED_IP_BANS.clone();
+        ips.extend_from_slice(&bans.addresses);
+
+        let nets: Vec<IpNet> =
+            bans.networks.iter().filter_map(|&(addr, prefix)| IpNet::new(addr, prefix).ok()).collect();
+
+        self.store(IpSet::with_networks(&ips, &nets));
+    }
+
     pub fn refresh(&self, ips: &[IpAddr]) {
         self.filter.rcu(|set| {
             let mut set = IpSet::clone(&set);
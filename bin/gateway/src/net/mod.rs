@@ -1,7 +1,2 @@
-pub mod addr_stream;
-pub mod filtered_addr_incoming;
 pub mod hardcoded_ip_bans;
 pub mod ip_filter;
-pub mod tls;
-
-// https://github.com/tmccombs/tls-listener
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use sdk::models::{aliases::*, Timestamp, UserFlags};
 
 use schema::auth::{RawAuthToken, UserToken};
@@ -40,10 +42,16 @@ impl AuthCache {
                 flags: partial.flags,
             }),
             RawAuthToken::Bot(token) if token.verify(&state.config().local.keys.bt_key) => {
-                self.bots.peek_with(&token.id, |_, partial| Authorization::Bot {
-                    bot_id: token.id,
-                    issued: partial.issued,
-                })
+                let issued = Timestamp::UNIX_EPOCH + Duration::from_secs(token.issued);
+
+                // tokens issued before or after the cached one are checked against the database
+                self.bots
+                    .peek_with(&token.id, |_, partial| partial.issued)
+                    .filter(|&cached| cached == issued)
+                    .map(|issued| Authorization::Bot {
+                        bot_id: token.id,
+                        issued,
+                    })
             }
             _ => return Err(Error::Unauthorized),
         })
@@ -53,10 +61,9 @@ impl AuthCache {
         _ = tokio::join! {
             self.invalid.insert_async(token),
             async {
-                #[allow(clippy::single_match)] // until fixed by adding bot removal
                 match token {
                     RawAuthToken::Bearer(token) => _ = self.users.remove_async(&token).await,
-                    _ => {} // TODO: Implement bot removal
+                    RawAuthToken::Bot(token) => _ = self.bots.remove_async(&token.id).await,
                 }
             },
         };
@@ -94,7 +101,19 @@ impl AuthCache {
         self.users.retain_async(|_, part| part.expires < now).await;
     }
 
-    // pub async fn clear_user(&self, user_id: UserId) {
-    //     self.map.retain_async(|_, part| part.user_id != user_id).await;
-    // }
+    /// Drops cached authorizations for the given user, or bot if a bot user.
+    pub async fn clear_user(&self, user_id: UserId) {
+        _ = tokio::join! {
+            self.users.retain_async(|_, part| part.user_id != user_id),
+            self.bots.remove_async(&user_id),
+        };
+    }
+
+    /// Drops all cached user and bot authorizations, forcing them to be re-checked against the database.
+    pub async fn clear(&self) {
+        _ = tokio::join! {
+            self.users.retain_async(|_, _| false),
+            self.bots.retain_async(|_, _| false),
+        };
+    }
 }
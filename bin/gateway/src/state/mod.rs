@@ -5,6 +5,7 @@ use crate::{
     config::Config,
     gateway::Gateway,
     //web::{file_cache::MainFileCache, rate_limit::RateLimitTable},
+    net::ip_filter::IpFilter,
     web::file_cache::StaticFileCache,
 };
 
@@ -14,6 +15,7 @@ use tokio::sync::{Notify, Semaphore};
 use schema::sf::SnowflakeGenerator;
 
 pub mod auth_cache;
+pub mod violations;
 
 pub struct InnerServerState {
     pub sf: SnowflakeGenerator,
@@ -23,6 +25,8 @@ pub struct InnerServerState {
     pub emoji: common::emoji::EmojiMap,
     pub rpc: ::rpc::client::RpcManager,
     pub gateway: Gateway,
+    pub ip_filter: IpFilter,
+    pub violations: violations::ViolationTable,
 }
 
 #[derive(Clone)]
@@ -55,6 +59,8 @@ impl GatewayServerState {
             emoji: common::emoji::EmojiMap::default(),
             rpc: ::rpc::client::RpcManager::new(nexus),
             gateway: Gateway::default(),
+            ip_filter: IpFilter::default(),
+            violations: violations::ViolationTable::default(),
         }))
     }
 }
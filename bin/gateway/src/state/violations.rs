@@ -0,0 +1,27 @@
+use std::net::IpAddr;
+
+use rpc::request::RateLimitViolation;
+
+/// Rate-limit violations counted since the last report to the nexus
+#[derive(Default)]
+pub struct ViolationTable {
+    counts: scc::HashMap<IpAddr, u32, sdk::FxRandomState2>,
+}
+
+impl ViolationTable {
+    pub fn add(&self, addr: IpAddr, count: u32) {
+        *self.counts.entry(addr).or_insert(0).get_mut() += count;
+    }
+
+    /// Removes and returns all counted violations
+    pub fn take(&self) -> Vec<RateLimitViolation> {
+        let mut violations = Vec::with_capacity(self.counts.len());
+
+        self.counts.retain(|&addr, &mut count| {
+            violations.push(RateLimitViolation { addr, count });
+            false
+        });
+
+        violations
+    }
+}
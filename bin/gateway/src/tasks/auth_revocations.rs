@@ -0,0 +1,43 @@
+use ::rpc::{client::RpcClientError, request::RpcRequest, stream::RpcRecvReader, DeserializeExt};
+use sdk::api::error::ApiError;
+
+use super::*;
+
+/// Drops cached authorizations for users whose sessions were revoked on the nexus,
+/// such as when banned or forcibly logged out.
+///
+/// Any revocations missed while disconnected are covered by clearing the entire cache.
+pub fn add_auth_revocations_task(state: &GatewayServerState, runner: &TaskRunner) {
+    runner.add(RetryAsyncFnTask::new(state.clone(), |mut alive, state| async move {
+        let mut stream = RpcRecvReader::new(state.rpc.nexus().send(&RpcRequest::WatchRevokedUsers).await?);
+
+        state.auth_cache.clear().await;
+
+        loop {
+            let user_id = tokio::select! {
+                biased;
+                _ = alive.changed() => break,
+                user_id = stream.recv::<Result<UserId, ApiError>>() => user_id?,
+            };
+
+            let Some(user_id) = user_id else { break };
+
+            match user_id.deserialize_simple() {
+                Ok(Ok(user_id)) => {
+                    log::debug!("Revoking cached authorization for {user_id}");
+
+                    state.auth_cache.clear_user(user_id).await;
+                }
+                Ok(Err(e)) => {
+                    log::warn!("Revocation stream interrupted: {e:?}");
+
+                    state.auth_cache.clear().await;
+                    break;
+                }
+                Err(_) => return Err(Error::RpcClientError(RpcClientError::EncodingError)),
+            }
+        }
+
+        Ok::<(), Error>(())
+    }));
+}
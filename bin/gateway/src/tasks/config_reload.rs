@@ -0,0 +1,83 @@
+use std::{path::PathBuf, sync::Arc};
+
+use ::rpc::{client::RpcClientError, request::RpcRequest, stream::RpcRecvReader, DeserializeExt};
+use sdk::api::error::ApiError;
+
+use crate::config::{LocalConfig, SharedConfig};
+
+use super::*;
+
+/// Applies shared config changes pushed by the nexus, and reloads the local config file
+/// when a reload is triggered, such as by SIGHUP.
+///
+/// Invalid local configs are logged and discarded, leaving the current config in place.
+pub fn add_config_reload_task(state: &GatewayServerState, runner: &TaskRunner, path: Option<PathBuf>) {
+    runner.add(RetryAsyncFnTask::new(state.clone(), |mut alive, state| async move {
+        let mut stream = RpcRecvReader::new(state.rpc.nexus().send(&RpcRequest::WatchSharedConfig).await?);
+
+        loop {
+            let shared = tokio::select! {
+                biased;
+                _ = alive.changed() => break,
+                shared = stream.recv::<Result<SharedConfig, ApiError>>() => shared?,
+            };
+
+            let Some(shared) = shared else { break };
+
+            match shared.deserialize_full() {
+                Ok(Ok(shared)) => {
+                    let current = state.config_full();
+
+                    // the nexus sends the current config first, which is likely unchanged
+                    if shared.etag() != current.shared.etag() {
+                        log::info!("Applying shared config from nexus");
+
+                        state.config.set(Arc::new(Config { shared, local: current.local.clone() }));
+                    }
+                }
+                Ok(Err(e)) => log::error!("Error fetching shared config: {e:?}"),
+                Err(_) => return Err(Error::RpcClientError(RpcClientError::EncodingError)),
+            }
+        }
+
+        Ok::<(), Error>(())
+    }));
+
+    let state = state.clone();
+
+    runner.add(AsyncFnTask::new(|mut alive| async move {
+        loop {
+            tokio::select! {
+                biased;
+                _ = alive.changed() => break,
+                _ = state.config.config_reload.notified() => {}
+            }
+
+            let Some(ref path) = path else {
+                log::warn!("No config file to reload");
+                continue;
+            };
+
+            let current = state.config_full();
+
+            let mut local: LocalConfig = match ::config::load_file(Some(path)) {
+                Ok(local) => local,
+                Err(e) => {
+                    log::error!("Error reloading config, keeping current config: {e}");
+                    continue;
+                }
+            };
+
+            local.retain_startup_sections(&current.local);
+
+            if let Err(e) = local.validate_reload(&current.local) {
+                log::error!("Invalid config, keeping current config: {e}");
+                continue;
+            }
+
+            state.config.set(Arc::new(Config { shared: current.shared.clone(), local }));
+
+            log::info!("Config reloaded");
+        }
+    }));
+}
@@ -0,0 +1,42 @@
+use futures::StreamExt;
+use sdk::models::gateway::message::ServerMsg;
+
+use crate::gateway::Event;
+
+use super::*;
+
+/// Notifies connected clients whenever the public server config changes,
+/// so they can refresh any limits used for input validation.
+pub fn add_config_updates_task(state: &GatewayServerState, runner: &TaskRunner) {
+    let state = state.clone();
+
+    runner.add(AsyncFnTask::new(|mut alive| async move {
+        let mut configs = std::pin::pin!(state.clone().config_full_stream());
+
+        let mut last_etag = None;
+
+        loop {
+            let config = tokio::select! {
+                biased;
+                _ = alive.changed() => break,
+                config = configs.next() => match config {
+                    Some(config) => config,
+                    None => break,
+                },
+            };
+
+            let etag = config.shared.etag();
+
+            // the first config is the one clients already received
+            if last_etag.replace(etag.clone()).is_none_or(|last| last == etag) {
+                continue;
+            }
+
+            log::info!("Server config changed, notifying clients");
+
+            let event = Event::new(ServerMsg::new_server_config_update(config.shared.public()), None);
+
+            state.gateway.broadcast_global_event(event).await;
+        }
+    }));
+}
@@ -0,0 +1,45 @@
+use ::rpc::{
+    client::RpcClientError,
+    request::{IpBanList, RpcRequest},
+    stream::RpcRecvReader,
+    DeserializeExt,
+};
+use sdk::api::error::ApiError;
+
+use super::*;
+
+/// Keeps the gateway IP filter in sync with the bans stored by the nexus.
+///
+/// The nexus pushes a fresh list whenever the bans change or expire, and periodically otherwise.
+pub fn add_ip_bans_task(state: &GatewayServerState, runner: &TaskRunner) {
+    runner.add(RetryAsyncFnTask::new(state.clone(), |mut alive, state| async move {
+        let mut stream = RpcRecvReader::new(state.rpc.nexus().send(&RpcRequest::WatchIpBans).await?);
+
+        loop {
+            let bans = tokio::select! {
+                biased;
+                _ = alive.changed() => break,
+                bans = stream.recv::<Result<IpBanList, ApiError>>() => bans?,
+            };
+
+            let Some(bans) = bans else { break };
+
+            match bans.deserialize_full() {
+                Ok(Ok(bans)) => {
+                    log::debug!(
+                        "Applying {} IP bans and {} network bans",
+                        bans.addresses.len(),
+                        bans.networks.len()
+                    );
+
+                    state.ip_filter.apply(&bans);
+                }
+                // keep the previous filter, a new list will arrive later
+                Ok(Err(e)) => log::error!("Error fetching IP bans: {e:?}"),
+                Err(_) => return Err(Error::RpcClientError(RpcClientError::EncodingError)),
+            }
+        }
+
+        Ok::<(), Error>(())
+    }));
+}
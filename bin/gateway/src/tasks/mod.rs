@@ -1,4 +1,4 @@
-pub use task_runner::{AsyncFnTask, IntervalFnTask, RetryTask, TaskRunner};
+pub use task_runner::{AsyncFnTask, IntervalFnTask, RetryAsyncFnTask, RetryTask, TaskRunner};
 
 use std::{sync::atomic::Ordering, time::Duration};
 
@@ -6,10 +6,23 @@ type Alive = tokio::sync::watch::Receiver<bool>;
 
 use crate::prelude::*;
 
+pub use config_reload::add_config_reload_task;
+
 pub fn add_tasks(state: &GatewayServerState, runner: &TaskRunner) {
+    auth_revocations::add_auth_revocations_task(state, runner);
+    config_updates::add_config_updates_task(state, runner);
     http_server::add_http_server_task(state, runner);
     https_server::add_https_server_task(state, runner);
+    ip_bans::add_ip_bans_task(state, runner);
+    party_migrations::add_party_migrations_task(state, runner);
+    rate_limit_report::add_rate_limit_report_task(state, runner);
 }
 
+pub mod auth_revocations;
+pub mod config_reload;
+pub mod config_updates;
 pub mod http_server;
 pub mod https_server;
+pub mod ip_bans;
+pub mod party_migrations;
+pub mod rate_limit_report;
@@ -0,0 +1,54 @@
+use ::rpc::{
+    client::RpcClientError,
+    request::{PartyMigration, RpcRequest},
+    stream::RpcRecvReader,
+    DeserializeExt,
+};
+use sdk::api::error::ApiError;
+
+use super::*;
+
+/// Applies party migrations between faction nodes to the `RpcManager`, holding requests for a party
+/// while it moves and then resolving its new location.
+///
+/// Any migrations missed while disconnected are covered by forgetting all party locations.
+pub fn add_party_migrations_task(state: &GatewayServerState, runner: &TaskRunner) {
+    runner.add(RetryAsyncFnTask::new(state.clone(), |mut alive, state| async move {
+        let mut stream = RpcRecvReader::new(state.rpc.nexus().send(&RpcRequest::WatchPartyMigrations).await?);
+
+        state.rpc.reset_parties().await;
+
+        loop {
+            let migration = tokio::select! {
+                biased;
+                _ = alive.changed() => break,
+                migration = stream.recv::<Result<PartyMigration, ApiError>>() => migration?,
+            };
+
+            let Some(migration) = migration else { break };
+
+            match migration.deserialize_simple() {
+                Ok(Ok(PartyMigration::Freeze(party_id))) => {
+                    log::debug!("Holding requests for migrating party {party_id}");
+
+                    state.rpc.freeze_party(party_id).await;
+                }
+                Ok(Ok(PartyMigration::Moved(party_id))) => {
+                    log::debug!("Party {party_id} migrated, releasing held requests");
+
+                    state.rpc.remove_party(party_id).await;
+                    state.rpc.thaw_party(party_id).await;
+                }
+                Ok(Err(e)) => {
+                    log::warn!("Party migration stream interrupted: {e:?}");
+
+                    state.rpc.reset_parties().await;
+                    break;
+                }
+                Err(_) => return Err(Error::RpcClientError(RpcClientError::EncodingError)),
+            }
+        }
+
+        Ok::<(), Error>(())
+    }));
+}
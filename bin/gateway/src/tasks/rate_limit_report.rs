@@ -0,0 +1,42 @@
+use ::rpc::{client::RpcClientError, request::RpcRequest, stream::RpcRecvReader, DeserializeExt};
+use sdk::api::error::ApiError;
+
+use super::*;
+
+/// Periodically reports counted rate-limit violations to the nexus, which persists
+/// them and escalates repeat offenders to temporary IP bans.
+pub fn add_rate_limit_report_task(state: &GatewayServerState, runner: &TaskRunner) {
+    runner.add(RetryTask::new(IntervalFnTask::new(
+        state.clone(),
+        Duration::from_secs(15),
+        |state, _| async move {
+            let violations = state.violations.take();
+
+            if violations.is_empty() {
+                return;
+            }
+
+            let task = async {
+                let cmd = RpcRequest::ReportRateLimitViolations(violations.clone());
+                let mut stream = RpcRecvReader::new(state.rpc.nexus().send(&cmd).await?);
+
+                match stream.recv::<Result<(), ApiError>>().await? {
+                    Some(res) => match res.deserialize_simple() {
+                        Ok(res) => res.map_err(Error::ApiError),
+                        Err(_) => Err(Error::RpcClientError(RpcClientError::EncodingError)),
+                    },
+                    None => Err(Error::RpcClientError(RpcClientError::EncodingError)),
+                }
+            };
+
+            if let Err(e) = task.await {
+                log::error!("Error reporting rate-limit violations: {e}");
+
+                // keep them for the next attempt
+                for violation in violations {
+                    state.violations.add(violation.addr, violation.count);
+                }
+            }
+        },
+    )))
+}
@@ -64,6 +64,8 @@ impl ApiV1Service {
 
         let (mut parts, body) = req.into_parts();
 
+        let if_none_match = parts.headers.get(http::header::IF_NONE_MATCH).cloned();
+
         // get the start time for request. This is all guaranteed to be present.
         let StartTime(start) = *parts.extensions.get::<ftl::layers::resp_timing::StartTime>().unwrap();
 
@@ -79,6 +81,8 @@ impl ApiV1Service {
 
         // perform request within the global rate-limiter
         if let Err(e) = global_rate_limiter.req(start).await {
+            state.violations.add(key.0.into(), 1);
+
             return Ok(e.into_response());
         }
 
@@ -145,7 +149,11 @@ impl ApiV1Service {
         // call the api router to get the procedure
         let proc = match self.api.call_opt(Request::from_parts(parts, body)).await {
             // Rate-limit error is the only one allowed through directly as a response
-            Err(RateLimitError::RateLimit(rate_limit_error)) => return Ok(rate_limit_error.into_response()),
+            Err(RateLimitError::RateLimit(rate_limit_error)) => {
+                state.violations.add(key.0.into(), 1);
+
+                return Ok(rate_limit_error.into_response());
+            }
             // if the key is rejected, it failed to parse from the request
             // NOTE: Due to the above manual key extraction, this should be impossible
             Err(RateLimitError::KeyRejection(_)) => return Err(Error::BadRequest),
@@ -164,8 +172,15 @@ impl ApiV1Service {
 
         let try_proc = async move {
             // NOTE: This goes here because of proc?, it's just easier with the error handling below
+            let proc = proc?;
+
+            // answered directly from the gateway's copy of the shared config
+            if let Procedure::GetServerConfig(_) = proc {
+                return Ok(server_config_response(state, if_none_match.as_ref()));
+            }
+
             let cmd = RpcRequest::ApiProcedure {
-                proc: proc?,
+                proc,
                 addr: rl.key().0.into(), // hijack the rate-limiter key to get the IP address
                 auth: auth.map(Box::new),
             };
@@ -292,6 +307,9 @@ impl ApiV1Service {
             cmds::GetUser,
             cmds::UpdateUserPrefs,
             cmds::UserLogout,
+            cmds::RequestDataExport,
+            cmds::DeleteAccount,
+            cmds::CancelAccountDeletion,
 
             cmds::CreateFile,
             cmds::GetFilesystemStatus,
@@ -319,6 +337,17 @@ impl ApiV1Service {
             cmds::CreatePinFolder,
             cmds::CreateRoom,
             cmds::SearchParty,
+            cmds::CreateEmote,
+            cmds::PatchEmote,
+            cmds::DeleteEmote,
+            cmds::BanMember,
+            cmds::UnbanMember,
+            cmds::GetPartyBans,
+            cmds::TimeoutMember,
+            cmds::KickMember,
+            cmds::GetAuditLog,
+            cmds::AddBotToParty,
+            cmds::GetPartyCommands,
 
             cmds::CreateMessage,
             cmds::EditMessage,
@@ -338,6 +367,35 @@ impl ApiV1Service {
             cmds::PatchRoom,
             cmds::DeleteRoom,
             cmds::GetRoom,
+            cmds::TimeoutRoomMember,
+            cmds::CreateWebhook,
+            cmds::GetWebhooks,
+            cmds::PatchWebhook,
+            cmds::DeleteWebhook,
+            cmds::RespondToInteraction,
+            cmds::GetMessageRevisions,
+
+            cmds::CreateIpBan,
+            cmds::DeleteIpBan,
+            cmds::GetIpBans,
+            cmds::BanUser,
+            cmds::UnbanUser,
+            cmds::DeleteUser,
+            cmds::LogoutUser,
+            cmds::LookupUser,
+            cmds::MigrateParty,
+
+            cmds::CreateApp,
+            cmds::GetApps,
+            cmds::PatchApp,
+            cmds::DeleteApp,
+            cmds::RegenerateBotToken,
+            cmds::SetAppSubscription,
+            cmds::GetAppSubscription,
+            cmds::DeleteAppSubscription,
+            cmds::CreateAppCommand,
+            cmds::GetAppCommands,
+            cmds::DeleteAppCommand,
         }
 
         let rl = rl.build();
@@ -348,3 +406,28 @@ impl ApiV1Service {
         }
     }
 }
+
+/// Responds with the public server config, or `304 Not Modified` if the client's copy is still current.
+fn server_config_response(state: &GatewayServerState, if_none_match: Option<&http::HeaderValue>) -> Response {
+    use ftl::body::deferred::Deferred;
+    use http::{header, HeaderValue, StatusCode};
+
+    let config = state.config();
+    let etag = config.shared.etag();
+
+    let mut resp = match if_none_match {
+        Some(tag) if tag.as_bytes() == etag.as_bytes() => StatusCode::NOT_MODIFIED.into_response(),
+        _ => Deferred::new(config.shared.public()).into_response(),
+    };
+
+    let headers = resp.headers_mut();
+
+    if let Ok(etag) = HeaderValue::from_str(&etag) {
+        headers.insert(header::ETAG, etag);
+    }
+
+    // clients must revalidate, but can do so cheaply with the ETag
+    headers.insert(header::CACHE_CONTROL, const { HeaderValue::from_static("public, no-cache") });
+
+    resp
+}
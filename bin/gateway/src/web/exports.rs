@@ -0,0 +1,62 @@
+use futures::{StreamExt, TryStreamExt};
+use http::{
+    header::{CACHE_CONTROL, CONTENT_DISPOSITION, CONTENT_TYPE},
+    HeaderValue,
+};
+use http_body_util::StreamBody;
+use hyper::body::Frame;
+
+use ftl::{body::Body, extract::State, IntoResponse, RequestParts, Response};
+
+use crate::prelude::*;
+
+/// Downloads a completed data export at `/exports/{export_id}/{token}`, using the link emailed to its user.
+pub async fn download_data_export(
+    State(state): State<GatewayServerState>,
+    parts: RequestParts,
+) -> Result<Response, Error> {
+    let Some((export_id, token)) = parts.uri.path().strip_prefix("/exports/").and_then(|p| p.split_once('/'))
+    else {
+        return Err(Error::NotFound);
+    };
+
+    let Ok(export_id) = export_id.parse::<Snowflake>() else {
+        return Err(Error::NotFound);
+    };
+
+    let Ok(token) = util::base64::decode_u128(token) else {
+        return Err(Error::NotFound);
+    };
+
+    let mut stream = Box::pin(state.rpc.download_data_export(export_id, token).await.map_err(|e| {
+        log::error!("Error requesting data export: {e:?}");
+        Error::InternalErrorStatic("RPC Error")
+    })?);
+
+    // the first item carries any error, such as for an expired token, before the response is started
+    let first = match stream.next().await {
+        Some(first) => first??,
+        None => Vec::new(),
+    };
+
+    let chunks = futures::stream::iter([Ok(first)]).chain(stream.map(|chunk| match chunk {
+        Ok(Ok(chunk)) => Ok(chunk),
+        Ok(Err(api_error)) => Err(std::io::Error::new(std::io::ErrorKind::Other, api_error)),
+        Err(err) => Err(err),
+    }));
+
+    let body = Body::wrap(StreamBody::new(chunks.map_ok(|chunk| Frame::data(bytes::Bytes::from(chunk)))));
+
+    let mut resp = body.into_response();
+
+    let headers = resp.headers_mut();
+
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/zip"));
+    headers.insert(CACHE_CONTROL, HeaderValue::from_static("private, no-store"));
+
+    if let Ok(value) = HeaderValue::from_str(&format!("attachment; filename=\"lantern-export-{export_id}.zip\"")) {
+        headers.insert(CONTENT_DISPOSITION, value);
+    }
+
+    Ok(resp)
+}
@@ -161,27 +161,7 @@ impl StaticFileCache {
 
             match m.pattern().as_u32() {
                 0 => {
-                    serde_json::to_writer(
-                        &mut new_file,
-                        &sdk::models::ServerConfig {
-                            hcaptcha_sitekey: c.shared.hcaptcha_sitekey,
-                            cdn: c.shared.cdn_domain.as_str().into(),
-                            min_age: c.shared.minimum_age,
-                            secure: c.shared.secure_web,
-                            camo: c.shared.camo_enable,
-                            limits: sdk::models::ServerLimits {
-                                max_upload_size: c.shared.max_upload_size,
-                                max_avatar_size: c.shared.max_avatar_size,
-                                max_banner_size: c.shared.max_banner_size,
-                                max_avatar_pixels: c.shared.max_avatar_pixels,
-                                max_banner_pixels: c.shared.max_banner_pixels,
-                                avatar_width: c.shared.avatar_width,
-                                banner_width: c.shared.banner_width,
-                                banner_height: c.shared.banner_height,
-                            },
-                        },
-                    )
-                    .unwrap();
+                    serde_json::to_writer(&mut new_file, &c.shared.public()).unwrap();
                 }
 
                 1 => new_file.extend_from_slice(String::as_bytes(&format!(
@@ -4,7 +4,7 @@ use http::{HeaderName, HeaderValue, Method, StatusCode};
 
 use ftl::{
     body::deferred::Deferred,
-    extract::{MatchedPath, State},
+    extract::{real_ip::RealIp, MatchedPath, State},
     fs::FileCacheExtra,
     layers::rate_limit::{Error as RateLimitError, RateLimitLayerBuilder, RateLimitService},
     router::{HandlerService, Router},
@@ -14,8 +14,10 @@ use ftl::{
 
 use crate::prelude::*;
 
+pub mod exports;
 pub mod file_cache;
 pub mod layers;
+pub mod webhooks;
 
 pub mod api {
     pub mod v1;
@@ -24,6 +26,7 @@ pub mod api {
 type InnerWebService = HandlerService<GatewayServerState, Response>;
 
 pub struct WebService {
+    pub state: GatewayServerState,
     pub web: Router<GatewayServerState, Response, RateLimitService<InnerWebService>>,
     pub api_v1: api::v1::ApiV1Service,
 }
@@ -34,9 +37,18 @@ impl Service<Request> for WebService {
 
     fn call(&self, req: Request) -> impl ServiceFuture<Self::Response, Self::Error> {
         async move {
+            use crate::net::ip_filter::AddrFilter;
+
+            if let Some(ip) = req.extensions().get::<RealIp>() {
+                if self.state.ip_filter.reject(ip) {
+                    return Ok(StatusCode::FORBIDDEN.into_response());
+                }
+            }
+
             let path = req.uri().path();
 
-            if is_bad_pattern(path) {
+            // webhook and export tokens are random, and could contain a bad pattern by chance
+            if !(path.starts_with("/webhooks/") || path.starts_with("/exports/")) && is_bad_pattern(path) {
                 return Ok(StatusCode::IM_A_TEAPOT.into_response());
             }
 
@@ -91,6 +103,8 @@ impl WebService {
                 common_web::decl_build_info!(BuildInfo);
                 future::ready(Deferred::new_static::<BuildInfo>())
             },
+            POST "/webhooks/{webhook_id}/{token}" (250; 20) => webhooks::execute_webhook,
+            GET "/exports/{export_id}/{token}" (1000; 5) => exports::download_data_export,
             GET|HEAD "/favicon.ico" => favicon,
             GET|HEAD "/static/{*path}" => static_files,
             GET|HEAD "/{*page}" => index_file,
@@ -102,6 +116,7 @@ impl WebService {
         Self {
             web: web.route_layer(rl.build()),
             api_v1: api::v1::ApiV1Service::new(state.clone()),
+            state,
         }
     }
 }
@@ -0,0 +1,53 @@
+use http::{header::CONTENT_TYPE, StatusCode};
+use http_body_util::{BodyExt, LengthLimitError, Limited};
+
+use ftl::{body::Body, extract::State, RequestParts};
+use sdk::api::commands::room::CreateMessageBody;
+
+use crate::prelude::*;
+
+/// Maximum size of a webhook payload, which cannot include attachments
+const MAX_BODY_SIZE: usize = 64 * 1024;
+
+/// Posts a message through an incoming webhook at `/webhooks/{webhook_id}/{token}`.
+///
+/// The message is given as JSON or CBOR, depending on the `Content-Type` header.
+pub async fn execute_webhook(
+    State(state): State<GatewayServerState>,
+    parts: RequestParts,
+    body: Body,
+) -> Result<StatusCode, Error> {
+    let Some((webhook_id, token)) = parts.uri.path().strip_prefix("/webhooks/").and_then(|p| p.split_once('/'))
+    else {
+        return Err(Error::NotFound);
+    };
+
+    let Ok(webhook_id) = webhook_id.parse::<Snowflake>() else {
+        return Err(Error::NotFound);
+    };
+
+    let token = util::base64::decode_u128(token)?;
+
+    let body = match Limited::new(body, MAX_BODY_SIZE).collect().await {
+        Ok(body) => body.to_bytes(),
+        Err(e) if e.is::<LengthLimitError>() => return Err(Error::RequestEntityTooLarge),
+        Err(_) => return Err(Error::BadRequest),
+    };
+
+    let content_type = match parts.headers.get(CONTENT_TYPE) {
+        Some(header) => header.to_str()?,
+        None => return Err(Error::UnsupportedMediaTypeGeneric),
+    };
+
+    // malformed payloads are the sender's fault, so don't report them as internal errors
+    let body: CreateMessageBody = match content_type.split(';').next().map(str::trim) {
+        Some("application/json") => serde_json::from_slice(&body).map_err(|_| Error::BadRequest)?,
+        Some("application/cbor") => ciborium::de::from_reader(&body[..]).map_err(|_| Error::BadRequest)?,
+        _ => return Err(Error::UnsupportedMediaTypeGeneric),
+    };
+
+    match state.rpc.execute_webhook(webhook_id, token, body).await? {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(Error::from(e)),
+    }
+}
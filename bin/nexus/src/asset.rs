@@ -1,4 +1,22 @@
-use sdk::api::commands::all::BannerAlign;
+//! Processing of uploaded images into user assets, such as avatars, banners and emotes.
+//!
+//! Images are decoded, cropped and resized by the separate `process` binary, so malformed uploads
+//! can't take down the server. Each encoded version is stored as its own file and listed under
+//! the asset in `UserAssetFiles`, along with the original upload if it was animated.
+
+use std::process::Stdio;
+
+use filesystem::store::{CipherOptions, OpenMode};
+use framed::tokio::{AsyncFramedReader, AsyncFramedWriter};
+use rand::Rng;
+use schema::flags::FileFlags;
+use sdk::{api::commands::all::BannerAlign, models::AssetFlags};
+use tokio::{
+    io::AsyncReadExt,
+    process::{Child, ChildStdin, ChildStdout},
+};
+
+use process::{Command, EncodingFormat, ProcessedResponse, Response};
 
 use crate::prelude::*;
 
@@ -6,13 +24,541 @@ use crate::prelude::*;
 pub enum AssetMode {
     Avatar,
     Banner(BannerAlign),
+    Emote,
 }
 
+/// Height emotes are scaled down to, their width following their aspect ratio
+pub const EMOTE_HEIGHT: u32 = 128;
+
+/// Widest aspect ratio allowed for emotes, anything wider is cropped
+pub const EMOTE_MAX_ASPECT: u32 = 4;
+
+/// Incremented whenever processing changes, so older assets can be found and reprocessed
+const ASSET_VERSION: i16 = 1;
+
+/// Formats every asset is encoded to, in order. JPEG must come last,
+/// as the processor premultiplies alpha in-place before encoding it.
+const ENCODINGS: &[(EncodingFormat, u8)] = &[
+    (EncodingFormat::Avif, 80),
+    (EncodingFormat::Png, 100),
+    (EncodingFormat::Jpeg, 90),
+];
+
+/// Processes `file_id` into a new asset if given, otherwise passes through `Null` or `Undefined` as-is.
 pub async fn maybe_add_asset(
     state: &ServerState,
     mode: AssetMode,
     user_id: UserId,
     file_id: Nullable<FileId>,
 ) -> Result<Nullable<FileId>, Error> {
-    Ok(Nullable::Null)
+    match file_id {
+        Nullable::Some(file_id) => add_asset(state, mode, user_id, file_id).await.map(Nullable::Some),
+        Nullable::Null => Ok(Nullable::Null),
+        Nullable::Undefined => Ok(Nullable::Undefined),
+    }
+}
+
+struct Limits {
+    width: u32,
+    height: u32,
+    max_pixels: u32,
+    max_size: i64,
+}
+
+/// Processes a completed upload owned by `user_id` into a new asset, returning the asset id.
+pub async fn add_asset(state: &ServerState, mode: AssetMode, user_id: UserId, file_id: FileId) -> Result<FileId, Error> {
+    #[rustfmt::skip]
+    let Some(row) = state.db.read.get().await?.query_opt2(schema::sql! {
+        SELECT
+            Files.Size      AS @_,
+            Files.Width     AS @_,
+            Files.Height    AS @_,
+            Files.Flags     AS @_,
+            Files.Nonce     AS @_,
+            Files.BlobId    AS @_
+        FROM Files
+        WHERE Files.Id = #{&file_id as Files::Id}
+          AND Files.UserId = #{&user_id as Files::UserId}
+    }).await? else {
+        return Err(Error::NotFound);
+    };
+
+    if !FileFlags::from_bits_truncate(row.files_flags()?).contains(FileFlags::COMPLETE) {
+        return Err(Error::UploadError);
+    }
+
+    let size: i64 = row.files_size()?;
+    let nonce: Option<i64> = row.files_nonce()?;
+    let stored_id: Snowflake = row.files_blob_id::<Option<Snowflake>>()?.unwrap_or(file_id);
+
+    let limits = {
+        let config = state.config();
+        let shared = &config.shared;
+
+        match mode {
+            AssetMode::Avatar => Limits {
+                width: shared.avatar_width,
+                height: shared.avatar_width,
+                max_pixels: shared.max_avatar_pixels,
+                max_size: shared.max_avatar_size as i64,
+            },
+            // TODO: Use the alignment when cropping
+            AssetMode::Banner(_) => Limits {
+                width: shared.banner_width,
+                height: shared.banner_height,
+                max_pixels: shared.max_banner_pixels,
+                max_size: shared.max_banner_size as i64,
+            },
+            AssetMode::Emote => {
+                let (width, height) = emote_bounds(row.files_width()?, row.files_height()?);
+
+                Limits {
+                    width,
+                    height,
+                    max_pixels: shared.max_avatar_pixels,
+                    max_size: shared.max_avatar_size as i64,
+                }
+            }
+        }
+    };
+
+    if size > limits.max_size {
+        return Err(Error::RequestEntityTooLarge);
+    }
+
+    let Some(nonce) = nonce else {
+        return Err(Error::UploadError);
+    };
+
+    // the upload may have been evicted after replication
+    if !crate::internal::files::ensure_local(state, stored_id).await? {
+        return Err(Error::NotFound);
+    }
+
+    let fs = state.fs();
+    let file_key = state.config().local.keys.file_key;
+
+    // file decryption is synchronous
+    let input = tokio::task::spawn_blocking(move || -> Result<Vec<u8>, Error> {
+        use std::io::Read;
+
+        let options = CipherOptions::new_from_i64_nonce(file_key, nonce);
+        let src = fs.open_crypt_read_sync(stored_id, &options)?;
+
+        let mut buf = Vec::with_capacity(size as usize);
+        src.take(size as u64).read_to_end(&mut buf)?;
+
+        Ok(buf)
+    })
+    .await??;
+
+    let animated = animated_format(&input);
+
+    let (processed, encoded) = {
+        let _permit = state.cpu_semaphore.acquire().await?;
+
+        run_process(state, &limits, &input).await?
+    };
+
+    drop(input);
+
+    let asset_id = state.sf.gen();
+
+    // write out encoded files before touching the database, removing them again if anything fails
+    let mut stored = Vec::with_capacity(encoded.len());
+
+    let res = async {
+        for (format, quality, data) in encoded {
+            let id = FileId::now();
+            let nonce: i64 = util::rng::crypto_thread_rng().gen();
+
+            stored.push((id, nonce, format, quality, data.len() as i64));
+
+            let mut file = state
+                .fs()
+                .open_crypt(id, OpenMode::Write, &CipherOptions::new_from_i64_nonce(file_key, nonce))
+                .await?;
+
+            file.write_buf(&data).await?;
+            file.flush().await?;
+        }
+
+        insert_asset(state, user_id, file_id, asset_id, &processed, animated, &stored).await
+    }
+    .await;
+
+    if let Err(e) = res {
+        for &(id, ..) in &stored {
+            if let Err(e) = state.fs().delete(id).await {
+                log::warn!("Error removing encoded asset file {id}: {e}");
+            }
+        }
+
+        return Err(e);
+    }
+
+    log::debug!("Processed file {file_id} into asset {asset_id} ({} versions)", stored.len());
+
+    Ok(asset_id)
+}
+
+async fn insert_asset(
+    state: &ServerState,
+    user_id: UserId,
+    file_id: FileId,
+    asset_id: FileId,
+    processed: &ProcessedResponse,
+    animated: Option<AssetFlags>,
+    stored: &[(FileId, i64, EncodingFormat, u8, i64)],
+) -> Result<(), Error> {
+    let width = processed.width as i32;
+    let height = processed.height as i32;
+    let has_alpha = processed.flags & process::HAS_ALPHA != 0;
+    let file_flags = FileFlags::COMPLETE.bits();
+
+    let mut db = state.db.write.get().await?;
+    let t = db.transaction().await?;
+
+    #[rustfmt::skip]
+    t.execute2(schema::sql! {
+        INSERT INTO UserAssets (Id, FileId, Version, Preview) VALUES (
+            #{&asset_id             as UserAssets::Id},
+            #{&file_id              as UserAssets::FileId},
+            #{&ASSET_VERSION        as UserAssets::Version},
+            #{&processed.preview    as UserAssets::Preview}
+        )
+    }).await?;
+
+    for &(id, nonce, format, quality, size) in stored {
+        let (mime, ext, mut flags) = match format {
+            EncodingFormat::Avif => ("image/avif", "avif", AssetFlags::FORMAT_AVIF),
+            EncodingFormat::Png => ("image/png", "png", AssetFlags::FORMAT_PNG),
+            EncodingFormat::Jpeg => ("image/jpeg", "jpeg", AssetFlags::FORMAT_JPEG),
+        };
+
+        // JPEG has no alpha channel, its premultiplied version is the fallback for clients that need one
+        if has_alpha && !matches!(format, EncodingFormat::Jpeg) {
+            flags |= AssetFlags::HAS_ALPHA;
+        }
+
+        let flags = (flags | AssetFlags::from_bits_truncate(quality.min(100) as i16)).bits();
+        let name = format!("{asset_id}.{ext}").into_bytes();
+
+        #[rustfmt::skip]
+        t.execute2(schema::sql! {
+            INSERT INTO Files (Id, UserId, Nonce, Size, Width, Height, Flags, Name, Mime) VALUES (
+                #{&id           as Files::Id},
+                #{&user_id      as Files::UserId},
+                #{&nonce        as Files::Nonce},
+                #{&size         as Files::Size},
+                #{&width        as Files::Width},
+                #{&height       as Files::Height},
+                #{&file_flags   as Files::Flags},
+                #{&name         as Files::Name},
+                #{&mime         as Files::Mime}
+            )
+        }).await?;
+
+        #[rustfmt::skip]
+        t.execute2(schema::sql! {
+            INSERT INTO UserAssetFiles (AssetId, FileId, Flags) VALUES (
+                #{&asset_id as UserAssetFiles::AssetId},
+                #{&id       as UserAssetFiles::FileId},
+                #{&flags    as UserAssetFiles::Flags}
+            )
+        }).await?;
+    }
+
+    // animations aren't re-encoded, so the original upload is served as the animated version
+    if let Some(format) = animated {
+        let flags = (format | AssetFlags::ANIMATED | AssetFlags::HAS_ALPHA | AssetFlags::from_bits_truncate(100)).bits();
+
+        #[rustfmt::skip]
+        t.execute2(schema::sql! {
+            INSERT INTO UserAssetFiles (AssetId, FileId, Flags) VALUES (
+                #{&asset_id as UserAssetFiles::AssetId},
+                #{&file_id  as UserAssetFiles::FileId},
+                #{&flags    as UserAssetFiles::Flags}
+            )
+        }).await?;
+    }
+
+    t.commit().await?;
+
+    Ok(())
+}
+
+type EncodedFile = (EncodingFormat, u8, Vec<u8>);
+
+/// Decodes the image in the `process` binary and encodes it in each format of [`ENCODINGS`].
+async fn run_process(
+    state: &ServerState,
+    limits: &Limits,
+    input: &[u8],
+) -> Result<(ProcessedResponse, Vec<EncodedFile>), Error> {
+    let bin = state.config().local.paths.bin_path.join("process");
+
+    let mut child = tokio::process::Command::new(&bin)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| {
+            log::error!("Unable to start image processor at {}: {e}", bin.display());
+            Error::IOError(e)
+        })?;
+
+    let res = talk_to_process(&mut child, limits, input).await;
+
+    // the processor exits on its own once stdin is closed, but don't leave it running on errors
+    if res.is_err() {
+        let _ = child.kill().await;
+    } else if let Err(e) = child.wait().await {
+        log::warn!("Error waiting on image processor: {e}");
+    }
+
+    res
+}
+
+async fn talk_to_process(
+    child: &mut Child,
+    limits: &Limits,
+    input: &[u8],
+) -> Result<(ProcessedResponse, Vec<EncodedFile>), Error> {
+    let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+        return Err(Error::InternalErrorStatic("Image processor pipes unavailable"));
+    };
+
+    let mut writer = AsyncFramedWriter::new(stdin);
+    let mut reader = AsyncFramedReader::new(stdout);
+
+    match read_response(&mut reader).await? {
+        Response::Ready => {}
+        _ => return Err(Error::InternalErrorStatic("Image processor failed to start")),
+    }
+
+    send(&mut writer, Command::Initialize {
+        width: limits.width,
+        height: limits.height,
+        max_pixels: limits.max_pixels,
+    })
+    .await?;
+
+    send(&mut writer, Command::ReadAndProcess {
+        length: input.len() as u64,
+    })
+    .await?;
+
+    writer.write_msg(input).await?;
+
+    let processed = match read_response(&mut reader).await? {
+        Response::Processed(processed) => processed,
+        _ => return Err(Error::InternalErrorStatic("Unexpected response from image processor")),
+    };
+
+    let mut encoded = Vec::with_capacity(ENCODINGS.len());
+
+    for &(format, quality) in ENCODINGS {
+        send(&mut writer, Command::Encode { format, quality }).await?;
+
+        match read_response(&mut reader).await? {
+            Response::Encoded => {}
+            _ => return Err(Error::InternalErrorStatic("Unexpected response from image processor")),
+        }
+
+        let Some(msg) = reader.next_msg().await? else {
+            return Err(Error::InternalErrorStatic("Image processor exited early"));
+        };
+
+        let mut data = Vec::with_capacity(msg.len() as usize);
+        msg.read_to_end(&mut data).await?;
+
+        encoded.push((format, quality, data));
+    }
+
+    send(&mut writer, Command::Exit).await?;
+
+    Ok((processed, encoded))
+}
+
+async fn send(writer: &mut AsyncFramedWriter<ChildStdin>, cmd: Command) -> Result<(), Error> {
+    writer.write_buffered_object(&cmd).await.map_err(|e| Error::InternalError(e.to_string()))
+}
+
+async fn read_response(reader: &mut AsyncFramedReader<ChildStdout>) -> Result<Response, Error> {
+    match reader.read_buffered_object().await {
+        Ok(Some(Response::Error(e))) => Err(match e {
+            process::Error::InvalidImageFormat
+            | process::Error::UnsupportedFormat
+            | process::Error::DecodingError(_) => Error::InvalidImageFormat,
+            process::Error::ImageTooLarge | process::Error::FileTooLarge => Error::RequestEntityTooLarge,
+            e => Error::InternalError(e.to_string()),
+        }),
+        Ok(Some(res)) => Ok(res),
+        Ok(None) => Err(Error::InternalErrorStatic("Image processor exited early")),
+        Err(e) => Err(Error::InternalError(e.to_string())),
+    }
+}
+
+/// Emotes keep their aspect ratio up to [`EMOTE_MAX_ASPECT`], based on the dimensions given at upload.
+fn emote_bounds(width: Option<i32>, height: Option<i32>) -> (u32, u32) {
+    let (Some(width), Some(height)) = (width, height) else {
+        return (EMOTE_HEIGHT, EMOTE_HEIGHT);
+    };
+
+    let (width, height) = (width.max(1) as u64, height.max(1) as u64);
+
+    let min_width = EMOTE_HEIGHT / EMOTE_MAX_ASPECT;
+    let max_width = EMOTE_HEIGHT * EMOTE_MAX_ASPECT;
+
+    let bound_width = (EMOTE_HEIGHT as u64 * width / height) as u32;
+
+    (bound_width.clamp(min_width, max_width), EMOTE_HEIGHT)
+}
+
+/// If the image has more than one frame, returns the format flag of the original file.
+///
+/// Only GIF and APNG are detected, as they're the animated formats clients can display directly.
+fn animated_format(data: &[u8]) -> Option<AssetFlags> {
+    if is_animated_gif(data).unwrap_or(false) {
+        return Some(AssetFlags::FORMAT_GIF);
+    }
+
+    if is_animated_png(data).unwrap_or(false) {
+        return Some(AssetFlags::FORMAT_PNG);
+    }
+
+    None
+}
+
+/// Walks the GIF blocks until a second image descriptor is found
+fn is_animated_gif(data: &[u8]) -> Option<bool> {
+    if !data.starts_with(b"GIF87a") && !data.starts_with(b"GIF89a") {
+        return Some(false);
+    }
+
+    fn color_table_len(flags: u8) -> usize {
+        match flags & 0x80 {
+            0 => 0,
+            _ => 3 * (1 << ((flags & 0x07) + 1)),
+        }
+    }
+
+    fn skip_sub_blocks(data: &[u8], mut pos: usize) -> Option<usize> {
+        loop {
+            let len = *data.get(pos)? as usize;
+            pos += 1 + len;
+
+            if len == 0 {
+                return Some(pos);
+            }
+        }
+    }
+
+    // header + logical screen descriptor
+    let mut pos = 13 + color_table_len(*data.get(10)?);
+    let mut frames = 0;
+
+    loop {
+        match *data.get(pos)? {
+            // extension: introducer, label, then data sub-blocks
+            0x21 => pos = skip_sub_blocks(data, pos + 2)?,
+            // image descriptor: 10 bytes, local color table, LZW code size, then data sub-blocks
+            0x2C => {
+                frames += 1;
+
+                if frames > 1 {
+                    return Some(true);
+                }
+
+                pos += 10 + color_table_len(*data.get(pos + 9)?) + 1;
+                pos = skip_sub_blocks(data, pos)?;
+            }
+            _ => return Some(false),
+        }
+    }
+}
+
+/// APNGs declare their animation in an `acTL` chunk before the first `IDAT`
+fn is_animated_png(data: &[u8]) -> Option<bool> {
+    if !data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some(false);
+    }
+
+    let mut pos = 8;
+
+    loop {
+        let len = u32::from_be_bytes(data.get(pos..pos + 4)?.try_into().ok()?) as usize;
+        let kind = data.get(pos + 4..pos + 8)?;
+
+        match kind {
+            b"acTL" => {
+                let num_frames = u32::from_be_bytes(data.get(pos + 8..pos + 12)?.try_into().ok()?);
+
+                return Some(num_frames > 1);
+            }
+            b"IDAT" | b"IEND" => return Some(false),
+            _ => pos += 12 + len,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 1x1 GIF with the given number of frames
+    fn gif(frames: usize) -> Vec<u8> {
+        let mut data = b"GIF89a\x01\x00\x01\x00\x80\x00\x00\x00\x00\x00\xff\xff\xff".to_vec();
+
+        for _ in 0..frames {
+            // graphic control extension
+            data.extend_from_slice(b"\x21\xf9\x04\x00\x0a\x00\x00\x00");
+            // image descriptor, LZW code size and image data
+            data.extend_from_slice(b"\x2c\x00\x00\x00\x00\x01\x00\x01\x00\x00\x02\x02\x44\x01\x00");
+        }
+
+        data.push(0x3B);
+        data
+    }
+
+    fn png_chunk(data: &mut Vec<u8>, kind: &[u8; 4], body: &[u8]) {
+        data.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        data.extend_from_slice(kind);
+        data.extend_from_slice(body);
+        data.extend_from_slice(&[0; 4]); // CRC isn't checked
+    }
+
+    #[test]
+    fn test_animated_gif() {
+        assert_eq!(animated_format(&gif(1)), None);
+        assert_eq!(animated_format(&gif(2)), Some(AssetFlags::FORMAT_GIF));
+
+        // truncated files are treated as still images
+        assert_eq!(animated_format(&gif(2)[..30]), None);
+    }
+
+    #[test]
+    fn test_animated_png() {
+        let mut still = b"\x89PNG\r\n\x1a\n".to_vec();
+        png_chunk(&mut still, b"IHDR", &[0; 13]);
+
+        let mut animated = still.clone();
+
+        png_chunk(&mut still, b"IDAT", &[0; 4]);
+        assert_eq!(animated_format(&still), None);
+
+        png_chunk(&mut animated, b"acTL", &[0, 0, 0, 2, 0, 0, 0, 0]);
+        png_chunk(&mut animated, b"IDAT", &[0; 4]);
+        assert_eq!(animated_format(&animated), Some(AssetFlags::FORMAT_PNG));
+    }
+
+    #[test]
+    fn test_emote_bounds() {
+        assert_eq!(emote_bounds(None, None), (EMOTE_HEIGHT, EMOTE_HEIGHT));
+        assert_eq!(emote_bounds(Some(64), Some(32)), (EMOTE_HEIGHT * 2, EMOTE_HEIGHT));
+        assert_eq!(emote_bounds(Some(1000), Some(10)), (EMOTE_HEIGHT * EMOTE_MAX_ASPECT, EMOTE_HEIGHT));
+        assert_eq!(emote_bounds(Some(10), Some(1000)), (EMOTE_HEIGHT / EMOTE_MAX_ASPECT, EMOTE_HEIGHT));
+    }
 }
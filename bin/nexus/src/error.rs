@@ -43,6 +43,21 @@ pub enum Error {
     #[error("Request Error: {0}")]
     RequestError(#[from] reqwest::Error),
 
+    #[error("Mailer Error: {0}")]
+    MailerError(#[from] email::mailer::MailerError),
+
+    #[error("Zip Error: {0}")]
+    ZipError(#[from] zip::result::ZipError),
+
+    #[error("Object Storage Error: {0}")]
+    ObjectStoreError(#[from] crate::services::object_store::ObjectStoreError),
+
+    #[error("Config Error: {0}")]
+    ConfigError(#[from] schema::config::ConfigError),
+
+    #[error("Config Error: {0}")]
+    ConfigLoadError(#[from] ::config::ConfigLoadError),
+
     // #[error("Encoding Error {0}")]
     // EventEncodingError(#[from] crate::backend::gateway::event::EventEncodingError),
 
@@ -177,6 +192,11 @@ impl Error {
             | Error::DbError(_)
             | Error::JoinError(_)
             | Error::SemaphoreError(_)
+            | Error::MailerError(_)
+            | Error::ZipError(_)
+            | Error::ObjectStoreError(_)
+            | Error::ConfigError(_)
+            | Error::ConfigLoadError(_)
             | Error::RequestError(_) => true,
 
             #[cfg(feature = "rust-argon2")]
@@ -257,6 +277,11 @@ impl From<Error> for sdk::api::error::ApiError {
             #[cfg(feature = "rustcrypto-argon2")]
             Error::RustCryptoArgon2PasswordHashError(_) => ApiErrorCode::HashError,
             Error::RequestError(_)          => ApiErrorCode::RequestError,
+            Error::MailerError(_)           => ApiErrorCode::InternalError,
+            Error::ZipError(_)              => ApiErrorCode::InternalError,
+            Error::ObjectStoreError(_)      => ApiErrorCode::InternalError,
+            Error::ConfigError(_)           => ApiErrorCode::InternalError,
+            Error::ConfigLoadError(_)       => ApiErrorCode::InternalError,
             Error::IOError(_)               => ApiErrorCode::IOError,
 
             | Error::Utf8ParseError(_)
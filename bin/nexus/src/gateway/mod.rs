@@ -19,6 +19,7 @@ use rkyv::{
 
 use quinn::{Connection, ConnectionError, RecvStream, SendStream, VarInt};
 
+pub mod subscriptions;
 pub mod task;
 
 #[derive(Clone)]
@@ -96,6 +97,9 @@ pub struct Gateway {
     /// Triggered by the database listener in the listener task
     pub notifier: Notify,
 
+    /// Intents of app subscriptions, to skip event delivery when no app could receive an event
+    pub subscriptions: subscriptions::SubscriptionSet,
+
     /// First element stores the actual last event, updated frequently
     ///
     /// Second element stores the last event 60 seconds ago as determined by the `event_cleanup` task.
@@ -119,6 +123,7 @@ impl Default for Gateway {
             rpcs: Default::default(),
             gateways: Default::default(),
             notifier: Notify::new(),
+            subscriptions: Default::default(),
             last_events: Default::default(),
         }
     }
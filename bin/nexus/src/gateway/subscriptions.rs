@@ -0,0 +1,254 @@
+//! HTTP delivery of gateway events to app subscriptions, for bots without a gateway connection.
+
+use std::time::Duration;
+
+use hashbrown::HashMap;
+use schema::flags::MemberFlags;
+use sdk::models::Permissions;
+use triomphe::Arc;
+
+use crate::{prelude::*, services::event_delivery::DeliveryError};
+
+/// Number of attempts before a delivery is given up on and written to the dead-letter log
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Delay before the first retry, doubling with each attempt after
+const BASE_BACKOFF: Duration = Duration::from_secs(2);
+
+/// Intents of each app subscription, by app ID.
+///
+/// Every node reloads these whenever the subscriptions table changes, as notified through the database.
+#[derive(Default)]
+pub struct SubscriptionSet {
+    intents: scc::HashIndex<Snowflake, i32, sdk::FxRandomState2>,
+}
+
+impl SubscriptionSet {
+    /// Loads all subscriptions from the database, replacing any already known
+    pub async fn load(&self, state: &ServerState) -> Result<(), Error> {
+        use scc::hash_index::Entry;
+
+        #[rustfmt::skip]
+        let rows = state.db.read.get().await?.query2(schema::sql! {
+            SELECT AppSubscriptions.AppId AS @_, AppSubscriptions.Intents AS @_ FROM AppSubscriptions
+        }).await?;
+
+        let mut subs: HashMap<Snowflake, i32, _> =
+            HashMap::with_capacity_and_hasher(rows.len(), sdk::FxRandomState2::default());
+
+        for row in rows {
+            subs.insert(row.app_subscriptions_app_id()?, row.app_subscriptions_intents()?);
+        }
+
+        // updated in place rather than cleared, so events in the meantime aren't skipped
+        self.intents.retain_async(|app_id, _| subs.contains_key(app_id)).await;
+
+        for (app_id, intents) in subs {
+            match self.intents.entry_async(app_id).await {
+                Entry::Occupied(entry) => entry.update(intents),
+                Entry::Vacant(entry) => {
+                    entry.insert_entry(intents);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether any app is subscribed to the given intent
+    fn any_matching(&self, intent: i32) -> bool {
+        self.intents.iter(&scc::ebr::Guard::new()).any(|(_, &intents)| intents & intent == intent)
+    }
+}
+
+struct Subscription {
+    app_id: Snowflake,
+    url: SmolStr,
+    secret: Vec<u8>,
+}
+
+/// Queues delivery of an event to every app subscribed to it, in the background.
+///
+/// Apps receive the same events their bot would over the gateway: the event must match the subscription's
+/// intents, be addressed to the bot or one of its parties, and the bot must be able to view the event's room.
+pub fn deliver(state: &ServerState, event: ServerEvent) {
+    let ServerEvent::Regular {
+        msg,
+        room_id,
+        user_ids,
+        party_ids,
+    } = event
+    else {
+        return;
+    };
+
+    // events without an intent are only meaningful to a gateway session, such as `Ready`
+    let Some(intent) = msg.matching_intent() else {
+        return;
+    };
+
+    let intent = intent.bits() as i32;
+
+    if !state.gateway.subscriptions.any_matching(intent) {
+        return;
+    }
+
+    let state = state.clone();
+
+    tokio::spawn(async move {
+        match is_delivering_node(&state, &party_ids).await {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(e) => return log::error!("Error finding node to deliver events: {e}"),
+        }
+
+        let subs = match find_subscriptions(&state, intent, room_id, &user_ids, &party_ids).await {
+            Ok(subs) if subs.is_empty() => return,
+            Ok(subs) => subs,
+            Err(e) => return log::error!("Error finding event subscriptions: {e}"),
+        };
+
+        let payload = match serde_json::to_value(&msg) {
+            Ok(payload) => Arc::new(payload),
+            Err(e) => return log::error!("Error encoding event for delivery: {e}"),
+        };
+
+        // JSON values always serialize
+        let body: Arc<[u8]> = serde_json::to_vec(&*payload).unwrap_or_default().into();
+
+        for sub in subs {
+            tokio::spawn(deliver_with_retries(state.clone(), sub, payload.clone(), body.clone()));
+        }
+    });
+}
+
+/// Every node processes every event, so only one may deliver it for apps to receive it once.
+///
+/// Party events are delivered by the node serving the lowest of their parties, and user events by the user nexus.
+async fn is_delivering_node(state: &ServerState, party_ids: &[Snowflake]) -> Result<bool, Error> {
+    let node = &state.config().local.node;
+
+    let Some(&party_id) = party_ids.iter().min() else {
+        return Ok(node.is_user_nexus());
+    };
+
+    let db = state.db.read.get().await?;
+
+    Ok(crate::internal::faction::serving_faction(&db, party_id).await? == node.faction_id())
+}
+
+async fn find_subscriptions(
+    state: &ServerState,
+    intent: i32,
+    room_id: Option<RoomId>,
+    user_ids: &[Snowflake],
+    party_ids: &[Snowflake],
+) -> Result<Vec<Subscription>, Error> {
+    let db = state.db.read.get().await?;
+
+    #[rustfmt::skip]
+    let rows = db.query2(schema::sql! {
+        SELECT
+            AppSubscriptions.AppId  AS @_,
+            AppSubscriptions.Url    AS @_,
+            AppSubscriptions.Secret AS @_,
+            Apps.BotId              AS @_
+        FROM AppSubscriptions INNER JOIN Apps ON Apps.Id = AppSubscriptions.AppId
+        WHERE AppSubscriptions.Intents & #{&intent as AppSubscriptions::Intents} = #{&intent as AppSubscriptions::Intents}
+          AND (
+            Apps.BotId = ANY(#{&user_ids as SNOWFLAKE_ARRAY})
+            OR EXISTS (
+                SELECT FROM PartyMembers
+                WHERE PartyMembers.UserId = Apps.BotId
+                  AND PartyMembers.PartyId = ANY(#{&party_ids as SNOWFLAKE_ARRAY})
+                  AND PartyMembers.Flags & const {MemberFlags::BANNED.bits()} = 0
+            )
+          )
+    }).await?;
+
+    let mut subs = Vec::with_capacity(rows.len());
+
+    for row in rows {
+        let bot_id: UserId = row.apps_bot_id()?;
+
+        // same room filter as gateway connections
+        if let Some(room_id) = room_id {
+            let perms =
+                crate::rpc::perm::get_cached_room_permissions_with_conn(state, &db, bot_id, room_id).await?;
+
+            if !perms.contains(Permissions::VIEW_ROOM) {
+                continue;
+            }
+        }
+
+        subs.push(Subscription {
+            app_id: row.app_subscriptions_app_id()?,
+            url: row.app_subscriptions_url()?,
+            secret: row.app_subscriptions_secret()?,
+        });
+    }
+
+    Ok(subs)
+}
+
+async fn deliver_with_retries(
+    state: ServerState,
+    sub: Subscription,
+    payload: Arc<serde_json::Value>,
+    body: Arc<[u8]>,
+) {
+    let mut backoff = BASE_BACKOFF;
+    let mut attempts = 0;
+
+    let err = loop {
+        attempts += 1;
+
+        match state.services.event_delivery.post(&sub.url, &sub.secret, &body).await {
+            Ok(()) => return,
+            Err(e) if attempts >= MAX_ATTEMPTS => break e,
+            Err(e) => {
+                log::debug!("Event delivery to app {} failed, attempt {attempts}: {e}", sub.app_id);
+
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+        }
+    };
+
+    log::warn!(
+        "Event delivery to app {} failed after {attempts} attempts: {err}",
+        sub.app_id
+    );
+
+    if let Err(e) = record_failure(&state, &sub, attempts, &err, &payload).await {
+        log::error!("Error recording failed event delivery: {e}");
+    }
+}
+
+/// Writes the undelivered event to the dead-letter log, kept for operators to inspect until it expires
+async fn record_failure(
+    state: &ServerState,
+    sub: &Subscription,
+    attempts: u32,
+    err: &DeliveryError,
+    payload: &serde_json::Value,
+) -> Result<(), Error> {
+    let id = state.sf.gen();
+    let attempts = attempts as i16;
+    let status = err.status().map(|s| s.as_u16() as i16);
+    let error = err.to_string();
+
+    #[rustfmt::skip]
+    state.db.write.get().await?.execute2(schema::sql! {
+        INSERT INTO FailedDeliveries (Id, AppId, Attempts, Status, Error, Payload) VALUES (
+            #{&id           as FailedDeliveries::Id},
+            #{&sub.app_id   as FailedDeliveries::AppId},
+            #{&attempts     as FailedDeliveries::Attempts},
+            #{&status       as FailedDeliveries::Status},
+            #{&error        as FailedDeliveries::Error},
+            #{payload       as FailedDeliveries::Payload}
+        )
+    }).await?;
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use schema::EventCode;
+
+use super::prelude::*;
+
+pub async fn emote_event(
+    state: &ServerState,
+    event: EventCode,
+    db: &db::Client,
+    emote_id: EmoteId,
+    party_id: Option<PartyId>,
+) -> Result<(), Error> {
+    let Some(party_id) = party_id else {
+        return Err(Error::InternalError(format!(
+            "Emote event without a party id!: {event:?} - {emote_id}"
+        )));
+    };
+
+    if event == EventCode::EmoteDeleted {
+        #[rustfmt::skip]
+        emit(state, ServerEvent::party(
+            party_id,
+            None,
+            ServerMsg::new_emote_delete(EmoteDeleteEvent { id: emote_id, party_id }),
+        )).await?;
+
+        return Ok(());
+    }
+
+    #[rustfmt::skip]
+    let row = db.query_one2(schema::sql! {
+        SELECT
+            Emotes.AssetId      AS @_,
+            Emotes.Name         AS @_,
+            Emotes.Alt          AS @_,
+            Emotes.Flags        AS @_,
+            Emotes.AspectRatio  AS @_
+        FROM Emotes
+        WHERE Emotes.Id = #{&emote_id as Emotes::Id}
+    }).await?;
+
+    let emote = CustomEmote {
+        id: emote_id,
+        party_id,
+        asset: row.emotes_asset_id()?,
+        name: row.emotes_name()?,
+        alt: row.emotes_alt()?,
+        flags: row.emotes_flags()?,
+        aspect_ratio: row.emotes_aspect_ratio()?,
+    };
+
+    let event = match event {
+        EventCode::EmoteCreated => ServerMsg::new_emote_create(emote),
+        EventCode::EmoteUpdated => ServerMsg::new_emote_update(emote),
+        _ => unreachable!(),
+    };
+
+    emit(state, ServerEvent::party(party_id, None, event)).await?;
+
+    Ok(())
+}
@@ -96,17 +96,17 @@ pub async fn member_event(
             };
 
             // Send user the party information
-            state.gateway.events.send(&ServerEvent::user(user_id, None, ServerMsg::new_party_create(party))).await?;
+            emit(state, ServerEvent::user(user_id, None, ServerMsg::new_party_create(party))).await?;
 
             ServerMsg::new_member_add(inner)
         }
         EventCode::MemberLeft | EventCode::MemberBan => {
             let inner: Arc<PartyMemberEvent> = Arc::new(inner);
 
-            state.gateway.events.send(&ServerEvent::user(user_id, None, ServerMsg::new_party_delete(party_id))).await?;
+            emit(state, ServerEvent::user(user_id, None, ServerMsg::new_party_delete(party_id))).await?;
 
             if event == EventCode::MemberBan {
-                state.gateway.events.send(&ServerEvent::party(
+                emit(state, ServerEvent::party(
                     party_id,
                     None,
                     ServerMsg::new_member_ban(inner.clone()),
@@ -119,7 +119,7 @@ pub async fn member_event(
         _ => unreachable!(),
     };
 
-    state.gateway.events.send(&ServerEvent::party(party_id, None, msg)).await?;
+    emit(state, ServerEvent::party(party_id, None, msg)).await?;
 
     Ok(())
 }
@@ -12,7 +12,7 @@ pub async fn message_delete(
     };
 
     #[rustfmt::skip]
-    state.gateway.events.send(&ServerEvent::party(
+    emit(state, ServerEvent::party(
         party_id,
         Some(room_id),
         ServerMsg::new_message_delete(MessageDeleteEvent { id, room_id, party_id }),
@@ -4,7 +4,7 @@ pub async fn message_update(state: &ServerState, db: &db::Client, id: MessageId)
     let msg = crate::internal::get_messages::get_one(state.clone(), db, id).await?;
 
     #[rustfmt::skip]
-    state.gateway.events.send(&ServerEvent::party(
+    emit(state, ServerEvent::party(
         msg.party_id,
         Some(msg.room_id),
         ServerMsg::new_message_update(msg),
@@ -9,8 +9,11 @@ pub mod prelude {
         gateway::{events::*, message::ServerMsg, Intent},
         *,
     };
+
+    pub use super::emit;
 }
 
+pub mod emote_event;
 pub mod member_event;
 pub mod message_create;
 pub mod message_delete;
@@ -29,6 +32,15 @@ pub struct RawEvent {
 
 use schema::EventCode;
 
+/// Sends an event to gateway connections, and to any apps subscribed to it over HTTP
+pub async fn emit(state: &ServerState, event: ServerEvent) -> Result<(), Error> {
+    state.gateway.events.send(&event).await?;
+
+    crate::gateway::subscriptions::deliver(state, event);
+
+    Ok(())
+}
+
 #[allow(unused_variables)]
 pub async fn process(
     state: &ServerState,
@@ -51,6 +63,9 @@ pub async fn process(
         EventCode::RoleCreated | EventCode::RoleUpdated | EventCode::RoleDeleted => {
             role_event::role_event(state, code, db, id, party_id).await
         }
+        EventCode::EmoteCreated | EventCode::EmoteUpdated | EventCode::EmoteDeleted => {
+            emote_event::emote_event(state, code, db, id, party_id).await
+        }
         EventCode::SelfUpdated => user_event::self_update(state, db, id, party_id).await,
         EventCode::UserUpdated => user_event::user_update(state, db, id).await,
         EventCode::ProfileUpdated => profile_event::profile_updated(state, db, id, party_id).await,
@@ -64,7 +64,7 @@ pub async fn presence_updated(
             };
 
             #[rustfmt::skip]
-            state.gateway.events.send(&ServerEvent::new_iter(
+            emit(state, ServerEvent::new_iter(
                 [], // TODO: Also send to friends
                 party_id,
                 None,
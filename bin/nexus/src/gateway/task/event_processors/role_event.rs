@@ -19,7 +19,7 @@ pub async fn role_event(
         };
 
         #[rustfmt::skip]
-        state.gateway.events.send(&ServerEvent::party(
+        emit(state, ServerEvent::party(
             party_id,
             None,
             ServerMsg::new_role_delete(RoleDeleteEvent { id: role_id, party_id }),
@@ -63,7 +63,7 @@ pub async fn role_event(
         _ => unreachable!(),
     };
 
-    state.gateway.events.send(&ServerEvent::party(party_id, None, event)).await?;
+    emit(state, ServerEvent::party(party_id, None, event)).await?;
 
     Ok(())
 }
@@ -60,7 +60,7 @@ pub async fn user_update(state: &ServerState, db: &db::Client, user_id: UserId)
 
     // shotgun the event to every relevant parties and users
     #[rustfmt::skip]
-    state.gateway.events.send(&ServerEvent::new(
+    emit(state, ServerEvent::new(
         friend_ids.into(),
         party_ids.into(),
         None,
@@ -88,7 +88,7 @@ pub async fn self_update(
     let user = crate::rpc::user::me::user_get_self::get_full_self(state, user_id).await?;
 
     #[rustfmt::skip]
-    state.gateway.events.send(&ServerEvent::user(user_id, None, ServerMsg::new_user_update(user))).await?;
+    emit(state, ServerEvent::user(user_id, None, ServerMsg::new_user_update(user))).await?;
 
     Ok(())
 }
@@ -114,7 +114,7 @@ async fn party_position_update(
         id: party_id,
     }));
 
-    state.gateway.events.send(&ServerEvent::user(user_id, None, event)).await?;
+    emit(state, ServerEvent::user(user_id, None, event)).await?;
 
     Ok(())
 }
@@ -10,6 +10,14 @@ pub fn add_gateway_listener(state: ServerState, runner: &TaskRunner) {
         let db = Object::take(state.db.read.get().await?);
 
         db.execute("LISTEN event_log", &[]).await?;
+        db.execute("LISTEN ip_bans", &[]).await?;
+        db.execute("LISTEN config", &[]).await?;
+        db.execute("LISTEN party_fences", &[]).await?;
+        db.execute("LISTEN app_subscriptions", &[]).await?;
+
+        // fences may have been requested, and subscriptions changed, while not listening
+        spawn_ack_party_fences(&state);
+        spawn_reload_subscriptions(&state);
 
         let conn = db.take_connection().await;
 
@@ -23,9 +31,19 @@ pub fn add_gateway_listener(state: ServerState, runner: &TaskRunner) {
             };
 
             match event {
-                Some(Ok(AsyncMessage::Notification(_))) => {
-                    state.gateway.notifier.notify_waiters();
-                }
+                Some(Ok(AsyncMessage::Notification(n))) => match n.channel() {
+                    "ip_bans" => state.ip_bans_changed.send_replace(()),
+                    "config" => state.shared_config_changed.send_replace(()),
+                    "party_fences" => {
+                        let faction_id = state.config().local.node.faction_id();
+
+                        if faction_id.is_some_and(|id| id.to_string() == n.payload()) {
+                            spawn_ack_party_fences(&state);
+                        }
+                    }
+                    "app_subscriptions" => spawn_reload_subscriptions(&state),
+                    _ => state.gateway.notifier.notify_waiters(),
+                },
                 Some(Ok(AsyncMessage::Notice(notice))) => {
                     log::info!("Database notice: {notice}");
                 }
@@ -41,3 +59,27 @@ pub fn add_gateway_listener(state: ServerState, runner: &TaskRunner) {
         Ok::<(), Error>(())
     }))
 }
+
+fn spawn_ack_party_fences(state: &ServerState) {
+    if !state.config().local.node.is_faction() {
+        return;
+    }
+
+    let state = state.clone();
+
+    tokio::spawn(async move {
+        if let Err(e) = crate::internal::faction::ack_party_fences(&state).await {
+            log::error!("Error acknowledging party fences: {e}");
+        }
+    });
+}
+
+fn spawn_reload_subscriptions(state: &ServerState) {
+    let state = state.clone();
+
+    tokio::spawn(async move {
+        if let Err(e) = state.gateway.subscriptions.load(&state).await {
+            log::error!("Error reloading app subscriptions: {e}");
+        }
+    });
+}
@@ -0,0 +1,81 @@
+use schema::audit::AuditAction;
+use sdk::models::*;
+use serde_json::{Map, Value};
+
+use crate::prelude::*;
+
+/// Maximum length of the reason given for an administrative action, in bytes
+pub const MAX_REASON_LENGTH: usize = 512;
+
+/// A pending entry in the party audit log
+pub struct AuditEntry<'a> {
+    pub party_id: PartyId,
+    pub user_id: UserId,
+    pub target_id: Option<Snowflake>,
+    pub action: AuditAction,
+    pub reason: Option<&'a str>,
+    pub changes: AuditChanges,
+}
+
+impl AuditEntry<'_> {
+    /// Insert the entry into the audit log. Should be done within the same
+    /// transaction as the action itself, so one is never recorded without the other.
+    pub async fn record<DB: db::AnyClient>(self, state: &ServerState, db: &DB) -> Result<(), Error> {
+        let id = state.sf.gen();
+        let action = self.action.to_i16();
+        let changes = self.changes.into_value();
+
+        #[rustfmt::skip]
+        db.execute2(schema::sql! {
+            INSERT INTO AuditLog (Id, PartyId, UserId, TargetId, Action, Reason, Changes) VALUES (
+                #{&id               as AuditLog::Id},
+                #{&self.party_id    as AuditLog::PartyId},
+                #{&self.user_id     as AuditLog::UserId},
+                #{&self.target_id   as AuditLog::TargetId},
+                #{&action           as AuditLog::Action},
+                #{&self.reason      as AuditLog::Reason},
+                #{&changes          as AuditLog::Changes}
+            )
+        }).await?;
+
+        Ok(())
+    }
+}
+
+/// Before/after values of fields changed by an action, stored as
+/// `{"field": {"old": ..., "new": ...}}`
+///
+/// Updates find old values by joining the table to itself, like `UPDATE Roles .. FROM Roles AS OldRoles`,
+/// as the joined row is read as it was before the update and can be returned alongside the new one.
+#[derive(Default)]
+pub struct AuditChanges(Map<String, Value>);
+
+impl AuditChanges {
+    /// Record a field only if its value actually changed
+    pub fn diff<T: serde::Serialize + PartialEq>(&mut self, field: &str, old: T, new: T) -> &mut Self {
+        if old != new {
+            self.set(field, Some(old), new);
+        }
+
+        self
+    }
+
+    /// Record a field unconditionally, with an unknown or absent previous value given as `None`
+    pub fn set<T: serde::Serialize>(&mut self, field: &str, old: Option<T>, new: T) -> &mut Self {
+        let mut change = Map::with_capacity(2);
+
+        change.insert("old".to_owned(), serde_json::to_value(old).unwrap_or(Value::Null));
+        change.insert("new".to_owned(), serde_json::to_value(new).unwrap_or(Value::Null));
+
+        self.0.insert(field.to_owned(), Value::Object(change));
+
+        self
+    }
+
+    fn into_value(self) -> Option<Value> {
+        match self.0.is_empty() {
+            true => None,
+            false => Some(Value::Object(self.0)),
+        }
+    }
+}
@@ -0,0 +1,428 @@
+//! Builds an archive of all personal data belonging to a user.
+//!
+//! The archive is a zip file containing JSON documents for each category of data,
+//! alongside the decrypted contents of every file the user has uploaded. It is written
+//! to the file store encrypted like any other file, and streamed to the gateway's
+//! `/exports/{export_id}/{token}` route with the token emailed to the user until it expires.
+
+use std::{
+    io::{ErrorKind, Write},
+    net::IpAddr,
+    time::SystemTime,
+};
+
+use email::{scenarios::DataExportReady, Email};
+use filesystem::store::{CipherOptions, OpenMode};
+use rand::Rng;
+use schema::flags::FileFlags;
+use sdk::models::MessageFlags;
+use serde_json::{json, Value};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::{prelude::*, services::email::send_email};
+
+struct ExportedFile {
+    id: FileId,
+    /// Where the contents are stored, which differs from `id` for deduplicated files
+    stored_id: Snowflake,
+    nonce: i64,
+    size: i64,
+    name: String,
+}
+
+/// Queue job for a requested export. On failure the request is removed so the user may try again.
+pub async fn run_data_export(state: ServerState, export_id: Snowflake, user_id: UserId) {
+    let Err(e) = build_data_export(&state, export_id, user_id).await else {
+        return;
+    };
+
+    log::error!("Error building data export {export_id} for {user_id}: {e}");
+
+    if let Err(e) = cancel_data_export(&state, export_id).await {
+        log::error!("Error cancelling data export {export_id}: {e}");
+    }
+}
+
+async fn cancel_data_export(state: &ServerState, export_id: Snowflake) -> Result<(), Error> {
+    match state.fs().delete(export_id).await {
+        Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+        _ => {}
+    }
+
+    #[rustfmt::skip]
+    state.db.write.get().await?.execute2(schema::sql! {
+        DELETE FROM UserDataExports WHERE UserDataExports.Id = #{&export_id as UserDataExports::Id}
+    }).await?;
+
+    Ok(())
+}
+
+async fn build_data_export(state: &ServerState, export_id: Snowflake, user_id: UserId) -> Result<(), Error> {
+    let (documents, files) = gather_documents(state, user_id).await?;
+
+    let fs = state.fs();
+    let file_key = state.config().local.keys.file_key;
+    let nonce: i64 = util::rng::crypto_thread_rng().gen();
+
+    let num_files = files.len();
+
+    // local copies may have been evicted after replication, and must be fetched before reading them synchronously
+    for file in &files {
+        if let Err(e) = crate::internal::files::ensure_local(state, file.stored_id).await {
+            log::warn!(
+                "Error fetching file {} from object storage during data export: {e}",
+                file.id
+            );
+        }
+    }
+
+    // zip writing and file decryption are synchronous
+    let size = tokio::task::spawn_blocking(move || -> Result<u64, Error> {
+        let options = CipherOptions::new_from_i64_nonce(file_key, nonce);
+        let out = fs.clone().open_crypt_write_sync(export_id, &options)?;
+
+        let mut zip = ZipWriter::new(out);
+
+        let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+        for (name, data) in documents {
+            zip.start_file(name, deflated)?;
+            zip.write_all(&data)?;
+        }
+
+        for file in files {
+            let options = CipherOptions::new_from_i64_nonce(file_key, file.nonce);
+
+            let mut src = match fs.clone().open_crypt_read_sync(file.stored_id, &options) {
+                Ok(src) => src,
+                Err(e) if e.kind() == ErrorKind::NotFound => {
+                    log::warn!("File {} missing from file store during data export", file.id);
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            // uploads are typically already-compressed media, so store them as-is
+            let stored = SimpleFileOptions::default()
+                .compression_method(CompressionMethod::Stored)
+                .large_file(file.size > u32::MAX as i64);
+
+            zip.start_file(format!("files/{}_{}", file.id, file.name), stored)?;
+            std::io::copy(&mut src, &mut zip)?;
+        }
+
+        let mut out = zip.finish()?;
+        out.flush()?;
+
+        Ok(out.get_ref().metadata()?.len())
+    })
+    .await??;
+
+    let token: [u8; 16] = util::rng::crypto_thread_rng().gen_bytes();
+    let expires = SystemTime::now() + state.config().shared.data_export_expiry;
+
+    let size = size as i64;
+    let token_bytes = &token[..];
+
+    #[rustfmt::skip]
+    let row = state.db.write.get().await?.query_one2(schema::sql! {
+        UPDATE UserDataExports SET (CompletedAt, Nonce, Size, Token, Expires) = (
+            now(),
+            #{&nonce       as UserDataExports::Nonce},
+            #{&size        as UserDataExports::Size},
+            #{&token_bytes as UserDataExports::Token},
+            #{&expires     as UserDataExports::Expires}
+        )
+        FROM Users
+        WHERE UserDataExports.Id = #{&export_id as UserDataExports::Id}
+          AND Users.Id = UserDataExports.UserId
+        RETURNING
+            Users.Username AS @Username,
+            Users.Email AS @Email
+    }).await?;
+
+    log::info!("Finished data export {export_id} for {user_id}, {num_files} files and {size} bytes");
+
+    let link = format!(
+        "https://{}/exports/{export_id}/{}",
+        state.config().shared.base_domain,
+        util::base64::encode_u128(u128::from_le_bytes(token)),
+    );
+
+    send_email(
+        state,
+        Email::new(
+            row.email::<String>()?,
+            "Your Lantern data export is ready",
+            DataExportReady::new(row.username::<String>()?, link, Timestamp::from(expires).to_string()),
+        ),
+    );
+
+    Ok(())
+}
+
+/// Streams the decrypted archive of a completed export, if the token matches and has not expired.
+pub async fn download_data_export(
+    state: ServerState,
+    export_id: Snowflake,
+    token: u128,
+) -> Result<impl Stream<Item = Result<Vec<u8>, Error>>, Error> {
+    let token = token.to_le_bytes();
+    let token_bytes = &token[..];
+
+    #[rustfmt::skip]
+    let Some(row) = state.db.read.get().await?.query_opt2(schema::sql! {
+        SELECT UserDataExports.Nonce AS @Nonce FROM UserDataExports
+        WHERE UserDataExports.Id = #{&export_id as UserDataExports::Id}
+          AND UserDataExports.Token = #{&token_bytes as UserDataExports::Token}
+          AND UserDataExports.Expires > now()
+          AND UserDataExports.CompletedAt IS NOT NULL
+    }).await? else {
+        return Err(Error::NotFound);
+    };
+
+    let nonce: i64 = row.nonce()?;
+
+    if !crate::internal::files::ensure_local(&state, export_id).await? {
+        log::warn!("Archive of data export {export_id} missing from file store");
+
+        return Err(Error::NotFound);
+    }
+
+    let options = CipherOptions::new_from_i64_nonce(state.config().local.keys.file_key, nonce);
+    let file = state.fs().open_crypt(export_id, OpenMode::Read, &options).await?;
+
+    Ok(tokio_util::io::ReaderStream::new(file).map(|chunk| Ok(chunk?.to_vec())))
+}
+
+/// Collects each JSON document of the archive, and the list of uploaded files to include.
+async fn gather_documents(
+    state: &ServerState,
+    user_id: UserId,
+) -> Result<(Vec<(&'static str, Vec<u8>)>, Vec<ExportedFile>), Error> {
+    let db = state.db.read.get().await?;
+
+    #[rustfmt::skip]
+    let user = db.query_one2(schema::sql! {
+        SELECT
+            Users.Username      AS @Username,
+            Users.Discriminator AS @Discriminator,
+            Users.Email         AS @Email,
+            Users.Dob           AS @Dob,
+            Users.Flags         AS @Flags,
+            Users.LastActive    AS @LastActive,
+            Users.Preferences   AS @Preferences
+        FROM Users WHERE Users.Id = #{&user_id as Users::Id}
+    }).await?;
+
+    let user = json!({
+        "id": user_id,
+        "username": user.username::<String>()?,
+        "discriminator": user.discriminator::<i32>()?,
+        "email": user.email::<String>()?,
+        "dob": user.dob::<time::Date>()?.to_string(),
+        "flags": user.flags::<i32>()?,
+        "last_active": user.last_active::<Option<SystemTime>>()?.map(Timestamp::from),
+        "preferences": user.preferences::<Option<Value>>()?,
+    });
+
+    #[rustfmt::skip]
+    let profiles = db.query2(schema::sql! {
+        SELECT
+            Profiles.PartyId      AS @PartyId,
+            Profiles.AvatarId     AS @AvatarId,
+            Profiles.BannerId     AS @BannerId,
+            Profiles.Bits         AS @Bits,
+            Profiles.Nickname     AS @Nickname,
+            Profiles.CustomStatus AS @CustomStatus,
+            Profiles.Biography    AS @Biography
+        FROM Profiles WHERE Profiles.UserId = #{&user_id as Profiles::UserId}
+    }).await?;
+
+    let profiles = profiles
+        .into_iter()
+        .map(|row| {
+            Ok(json!({
+                "party_id": row.party_id::<Option<PartyId>>()?,
+                "avatar_id": row.avatar_id::<Option<FileId>>()?,
+                "banner_id": row.banner_id::<Option<FileId>>()?,
+                "bits": row.bits::<i32>()?,
+                "nickname": row.nickname::<Option<String>>()?,
+                "custom_status": row.custom_status::<Option<String>>()?,
+                "biography": row.biography::<Option<String>>()?,
+            }))
+        })
+        .collect::<Result<Vec<Value>, Error>>()?;
+
+    #[rustfmt::skip]
+    let relationships = db.query2(schema::sql! {
+        SELECT
+            AggRelationships.FriendId  AS @FriendId,
+            AggRelationships.UpdatedAt AS @UpdatedAt,
+            AggRelationships.RelA      AS @RelA,
+            AggRelationships.Note      AS @Note
+        FROM AggRelationships WHERE AggRelationships.UserId = #{&user_id as AggRelationships::UserId}
+    }).await?;
+
+    let relationships = relationships
+        .into_iter()
+        .map(|row| {
+            Ok(json!({
+                "user_id": row.friend_id::<Option<UserId>>()?,
+                "updated_at": row.updated_at::<Option<SystemTime>>()?.map(Timestamp::from),
+                "relationship": row.rel_a::<Option<i32>>()?,
+                "note": row.note::<Option<String>>()?,
+            }))
+        })
+        .collect::<Result<Vec<Value>, Error>>()?;
+
+    // session tokens are deliberately excluded
+    #[rustfmt::skip]
+    let sessions = db.query2(schema::sql! {
+        SELECT
+            Sessions.Expires AS @Expires,
+            Sessions.Addr    AS @Addr
+        FROM Sessions WHERE Sessions.UserId = #{&user_id as Sessions::UserId}
+    }).await?;
+
+    let sessions = sessions
+        .into_iter()
+        .map(|row| {
+            Ok(json!({
+                "expires": Timestamp::from(row.expires::<SystemTime>()?),
+                "addr": row.addr::<IpAddr>()?,
+            }))
+        })
+        .collect::<Result<Vec<Value>, Error>>()?;
+
+    #[rustfmt::skip]
+    let parties = db.query2(schema::sql! {
+        SELECT
+            PartyMembers.PartyId  AS @PartyId,
+            PartyMembers.JoinedAt AS @JoinedAt,
+            Party.Name            AS @Name,
+            Party.OwnerId         AS @OwnerId
+        FROM PartyMembers INNER JOIN Party ON Party.Id = PartyMembers.PartyId
+        WHERE PartyMembers.UserId = #{&user_id as PartyMembers::UserId}
+    }).await?;
+
+    let parties = parties
+        .into_iter()
+        .map(|row| {
+            Ok(json!({
+                "party_id": row.party_id::<PartyId>()?,
+                "name": row.name::<String>()?,
+                "joined_at": Timestamp::from(row.joined_at::<SystemTime>()?),
+                "owner": row.owner_id::<UserId>()? == user_id,
+            }))
+        })
+        .collect::<Result<Vec<Value>, Error>>()?;
+
+    // messages are written as JSON Lines, since there may be very many of them
+    let mut messages = Vec::new();
+
+    #[rustfmt::skip]
+    let stream = db.query_stream2(schema::sql! {
+        SELECT
+            Messages.Id       AS @Id,
+            Messages.RoomId   AS @RoomId,
+            Messages.ParentId AS @ParentId,
+            Messages.EditedAt AS @EditedAt,
+            Messages.Content  AS @Content
+        FROM Messages
+        WHERE Messages.UserId = #{&user_id as Messages::UserId}
+          AND NOT Messages.Flags & const {MessageFlags::DELETED.bits()}
+        ORDER BY Messages.Id ASC
+    }).await?;
+
+    let mut stream = std::pin::pin!(stream);
+
+    while let Some(row) = stream.next().await {
+        let row = row?;
+
+        let msg = json!({
+            "id": row.id::<MessageId>()?,
+            "room_id": row.room_id::<RoomId>()?,
+            "parent_id": row.parent_id::<Option<MessageId>>()?,
+            "edited_at": row.edited_at::<Option<SystemTime>>()?.map(Timestamp::from),
+            "content": row.content::<Option<String>>()?,
+        });
+
+        if let Ok(()) = serde_json::to_writer(&mut messages, &msg) {
+            messages.push(b'\n');
+        }
+    }
+
+    #[rustfmt::skip]
+    let file_rows = db.query2(schema::sql! {
+        SELECT
+            Files.Id    AS @Id,
+            COALESCE(Files.BlobId, Files.Id) AS @StoredId,
+            Files.Nonce AS @Nonce,
+            Files.Size  AS @Size,
+            Files.Name  AS @Name,
+            Files.Mime  AS @Mime
+        FROM Files
+        WHERE Files.UserId = #{&user_id as Files::UserId}
+          AND Files.Nonce IS NOT NULL
+          AND Files.Flags & const {FileFlags::COMPLETE.bits()} != 0
+    }).await?;
+
+    let mut files = Vec::with_capacity(file_rows.len());
+    let mut file_list = Vec::with_capacity(file_rows.len());
+
+    for row in file_rows {
+        let file = ExportedFile {
+            id: row.id()?,
+            stored_id: row.stored_id()?,
+            nonce: row.nonce()?,
+            size: row.size()?,
+            name: sanitize_filename(&String::from_utf8_lossy(&row.name::<Vec<u8>>()?)),
+        };
+
+        file_list.push(json!({
+            "id": file.id,
+            "name": file.name,
+            "size": file.size,
+            "mime": row.mime::<Option<String>>()?,
+        }));
+
+        files.push(file);
+    }
+
+    let documents = vec![
+        ("user.json", serde_json::to_vec_pretty(&user)),
+        ("profiles.json", serde_json::to_vec_pretty(&profiles)),
+        ("relationships.json", serde_json::to_vec_pretty(&relationships)),
+        ("sessions.json", serde_json::to_vec_pretty(&sessions)),
+        ("parties.json", serde_json::to_vec_pretty(&parties)),
+        ("files.json", serde_json::to_vec_pretty(&file_list)),
+    ];
+
+    let mut documents = documents
+        .into_iter()
+        .map(|(name, data)| data.map(|data| (name, data)))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| Error::InternalErrorStatic("JSON Encoding Error"))?;
+
+    documents.push(("messages.jsonl", messages));
+
+    Ok((documents, files))
+}
+
+/// Strips path separators and control characters so the name is safe to use within the archive.
+fn sanitize_filename(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+
+    match name.trim_start_matches('.') {
+        "" => "file".to_owned(),
+        name => name.to_owned(),
+    }
+}
@@ -0,0 +1,82 @@
+use std::time::{Duration, SystemTime};
+
+use uuid::Uuid;
+
+use crate::prelude::*;
+
+/// How often faction nodes refresh their registration
+pub const FACTION_HEARTBEAT: Duration = Duration::from_secs(15);
+
+/// Factions without a heartbeat for this long are considered down, and their parties are served by the nexus
+pub const FACTION_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Factions down for this long are removed, and their parties placed elsewhere
+pub const FACTION_EVICTION: Duration = Duration::from_secs(60 * 10);
+
+/// How long a migration waits for the faction serving the party to acknowledge its fence
+pub const FENCE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Waits for every party request already in progress on this node to finish.
+///
+/// New party requests wait behind this, so the lock is released immediately.
+pub async fn drain_party_requests(state: &ServerState) {
+    drop(state.party_fence.write().await);
+}
+
+/// Drains party requests on this faction, then acknowledges any pending migration fences for it.
+pub async fn ack_party_fences(state: &ServerState) -> Result<(), Error> {
+    let Some(faction_id) = state.config().local.node.faction_id() else {
+        return Ok(());
+    };
+
+    drain_party_requests(state).await;
+
+    #[rustfmt::skip]
+    state.db.write.get().await?.execute2(schema::sql! {
+        UPDATE PartyFences SET (Acked) = (TRUE)
+        WHERE PartyFences.FactionId = #{&faction_id as PartyFences::FactionId}
+          AND NOT PartyFences.Acked
+    }).await?;
+
+    Ok(())
+}
+
+/// The live faction serving the party, or `None` if it falls to the user nexus.
+pub async fn serving_faction<DB: db::AnyClient>(db: &DB, party_id: PartyId) -> Result<Option<Uuid>, Error> {
+    let cutoff = SystemTime::now() - FACTION_TIMEOUT;
+
+    #[rustfmt::skip]
+    let row = db.query_opt2(schema::sql! {
+        SELECT FactionParties.FactionId AS @FactionId
+        FROM FactionParties INNER JOIN Factions ON Factions.Id = FactionParties.FactionId
+        WHERE FactionParties.PartyId = #{&party_id as FactionParties::PartyId}
+          AND Factions.LastSeen > #{&cutoff as Factions::LastSeen}
+    }).await?;
+
+    match row {
+        Some(row) => Ok(Some(row.faction_id()?)),
+        None => Ok(None),
+    }
+}
+
+/// Assigns the party to the live faction hosting the fewest parties, if there are any.
+pub async fn place_party<DB: db::AnyClient>(db: &DB, party_id: PartyId) -> Result<Option<Uuid>, Error> {
+    let cutoff = SystemTime::now() - FACTION_TIMEOUT;
+
+    #[rustfmt::skip]
+    let row = db.query_opt2(schema::sql! {
+        INSERT INTO FactionParties (FactionId, PartyId)
+        SELECT Factions.Id, #{&party_id as FactionParties::PartyId}
+        FROM Factions WHERE Factions.LastSeen > #{&cutoff as Factions::LastSeen}
+        ORDER BY (
+            SELECT COUNT(*) FROM FactionParties WHERE FactionParties.FactionId = Factions.Id
+        ) ASC
+        LIMIT 1
+        RETURNING FactionParties.FactionId AS @FactionId
+    }).await?;
+
+    match row {
+        Some(row) => Ok(Some(row.faction_id()?)),
+        None => Ok(None),
+    }
+}
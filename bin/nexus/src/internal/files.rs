@@ -0,0 +1,209 @@
+//! Content-addressed deduplication of uploaded files.
+//!
+//! Completed uploads are hashed with BLAKE3, keyed by the file encryption key so hashes reveal nothing
+//! about the contents on their own. Files with identical contents all point to a single `FileBlobs` entry,
+//! and only its encrypted copy is kept in the file store. Each file retains its own size for quota purposes.
+//!
+//! When object storage is configured, blobs are also replicated off-site, after which their local
+//! copies act as a cache that may be evicted and fetched again on demand.
+
+use std::io::ErrorKind;
+
+use filesystem::store::CipherOptions;
+
+use crate::{prelude::*, services::object_store::Bucket};
+
+/// Hashes a completed upload and either registers its contents as a new blob,
+/// or points it to an existing blob with identical contents and removes the duplicate copy.
+pub async fn dedup_file(state: &ServerState, file_id: FileId, nonce: i64, size: i64) -> Result<(), Error> {
+    let _file_lock = state.id_lock.lock(file_id).await;
+
+    let fs = state.fs();
+    let file_key = state.config().local.keys.file_key;
+
+    // file decryption is synchronous, and a fresh upload is not yet a replicated blob that could have been evicted
+    let hash = tokio::task::spawn_blocking(move || -> Result<Vec<u8>, Error> {
+        let options = CipherOptions::new_from_i64_nonce(file_key, nonce);
+        let mut src = fs.open_crypt_read_sync(file_id, &options)?;
+
+        let mut hasher = blake3::Hasher::new_keyed(&file_key.into());
+        std::io::copy(&mut src, &mut hasher)?;
+
+        Ok(hasher.finalize().as_bytes().to_vec())
+    })
+    .await??;
+
+    let mut db = state.db.write.get().await?;
+    let t = db.transaction().await?;
+
+    // the first file with these contents becomes the blob, keeping its copy where it already is
+    #[rustfmt::skip]
+    t.execute2(schema::sql! {
+        INSERT INTO FileBlobs (Id, Nonce, Size, Blake3) VALUES (
+            #{&file_id as FileBlobs::Id},
+            #{&nonce   as FileBlobs::Nonce},
+            #{&size    as FileBlobs::Size},
+            #{&hash    as FileBlobs::Blake3}
+        )
+        ON CONFLICT DO NOTHING
+    }).await?;
+
+    #[rustfmt::skip]
+    let row = t.query_one2(schema::sql! {
+        SELECT FileBlobs.Id AS @Id, FileBlobs.Nonce AS @Nonce, FileBlobs.Replicated AS @Replicated
+        FROM FileBlobs WHERE FileBlobs.Blake3 = #{&hash as FileBlobs::Blake3}
+    }).await?;
+
+    let blob_id: Snowflake = row.id()?;
+    let blob_nonce: i64 = row.nonce()?;
+    let replicated: bool = row.replicated()?;
+
+    #[rustfmt::skip]
+    t.execute2(schema::sql! {
+        UPDATE Files SET (BlobId, Nonce, Blake3) = (
+            #{&blob_id    as Files::BlobId},
+            #{&blob_nonce as Files::Nonce},
+            #{&hash       as Files::Blake3}
+        )
+        WHERE Files.Id = #{&file_id as Files::Id}
+    }).await?;
+
+    t.commit().await?;
+
+    if blob_id != file_id {
+        log::debug!("File {file_id} deduplicated to blob {blob_id}");
+
+        match state.fs().delete(file_id).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => {
+                log::error!("Error deleting duplicate copy of file {file_id}: {e}");
+            }
+            _ => {}
+        }
+    }
+
+    drop(_file_lock);
+
+    if !replicated && state.config().local.storage.write_through {
+        replicate_blob(state, blob_id).await?;
+    }
+
+    Ok(())
+}
+
+/// Copies the contents of a blob to object storage, returning `false` if object storage is disabled.
+pub async fn replicate_blob(state: &ServerState, blob_id: Snowflake) -> Result<bool, Error> {
+    let Some(bucket) = Bucket::from_config(&state.config()) else {
+        return Ok(false);
+    };
+
+    {
+        let _blob_lock = state.id_lock.lock(blob_id).await;
+
+        // the local copy may have been evicted if replication was retried after succeeding
+        ensure_local(state, blob_id).await?;
+
+        state.services.object_store.put(&bucket, blob_id, &state.fs().path(blob_id)).await?;
+    }
+
+    #[rustfmt::skip]
+    state.db.write.get().await?.execute2(schema::sql! {
+        UPDATE FileBlobs SET (Replicated) = (TRUE)
+        WHERE FileBlobs.Id = #{&blob_id as FileBlobs::Id}
+    }).await?;
+
+    log::debug!("Replicated blob {blob_id} to object storage");
+
+    Ok(true)
+}
+
+/// Makes sure the stored file is present locally, fetching it from object storage if needed.
+///
+/// Local copies of replicated blobs may be evicted at any time, so every read of stored contents
+/// must go through this first, as asset processing, data exports and replication do. Only uploads
+/// still being written or hashed by [`dedup_file`] are always local. The gateway never reads
+/// uploaded files, as its file cache only serves the web client.
+///
+/// Returns `false` if it could not be found in either.
+pub async fn ensure_local(state: &ServerState, id: Snowflake) -> Result<bool, Error> {
+    match state.fs().metadata(id).await {
+        Ok(_) => return Ok(true),
+        Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+        Err(_) => {}
+    }
+
+    let Some(bucket) = Bucket::from_config(&state.config()) else {
+        return Ok(false);
+    };
+
+    log::debug!("Stored file {id} missing locally, fetching from object storage");
+
+    state.services.object_store.get(&bucket, id, &state.fs().path(id)).await
+}
+
+/// Drops blobs no longer referenced by any file after the given files were deleted, within the same transaction.
+///
+/// Takes the `(Id, BlobId)` of each deleted file, and returns the ids to remove from the file store.
+pub async fn release_files<DB: db::AnyClient>(
+    db: &DB,
+    files: &[(FileId, Option<Snowflake>)],
+) -> Result<Vec<Snowflake>, Error> {
+    let mut stored = Vec::with_capacity(files.len());
+    let mut blob_ids = Vec::new();
+
+    for &(file_id, blob_id) in files {
+        match blob_id {
+            Some(blob_id) => blob_ids.push(blob_id),
+            None => stored.push(file_id),
+        }
+    }
+
+    if blob_ids.is_empty() {
+        return Ok(stored);
+    }
+
+    blob_ids.sort_unstable();
+    blob_ids.dedup();
+
+    #[rustfmt::skip]
+    let rows = db.query2(schema::sql! {
+        DELETE FROM FileBlobs
+        WHERE FileBlobs.Id = ANY(#{&blob_ids as SNOWFLAKE_ARRAY})
+          AND NOT EXISTS (SELECT FROM Files WHERE Files.BlobId = FileBlobs.Id)
+        RETURNING FileBlobs.Id AS @Id
+    }).await?;
+
+    for row in rows {
+        stored.push(row.id()?);
+    }
+
+    Ok(stored)
+}
+
+/// Removes released files from the file store and object storage, returning the number of bytes reclaimed locally.
+pub async fn remove_stored(state: &ServerState, ids: &[Snowflake]) -> u64 {
+    let bucket = Bucket::from_config(&state.config());
+
+    let mut bytes = 0;
+
+    for &id in ids {
+        // partial uploads may be smaller than their declared size, so measure what's on disk
+        if let Ok(meta) = state.fs().metadata(id).await {
+            bytes += meta.len();
+        }
+
+        match state.fs().delete(id).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => {
+                log::error!("Error deleting stored file {id}: {e}");
+            }
+            _ => {}
+        }
+
+        if let Some(ref bucket) = bucket {
+            if let Err(e) = state.services.object_store.delete(bucket, id).await {
+                log::error!("Error deleting stored file {id} from object storage: {e}");
+            }
+        }
+    }
+
+    bytes
+}
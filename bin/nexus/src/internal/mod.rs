@@ -1,10 +1,16 @@
 //! Shared functionality for the RPC system and elsewhere, split out for clarity.
 
+pub mod audit;
+pub mod data_export;
+pub mod faction;
+pub mod files;
 pub mod get_members;
 pub mod get_messages;
 pub mod get_rooms;
 pub mod login;
 pub mod mfa;
+pub mod moderation;
 pub mod password;
 pub mod role_overwrites;
+pub mod user_deletion;
 pub mod user_profile;
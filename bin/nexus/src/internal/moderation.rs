@@ -0,0 +1,91 @@
+use schema::roles::{CheckStatus, PartialRole, RoleChecker, UserAction};
+use sdk::models::*;
+
+use crate::prelude::*;
+
+/// Checks if `user_id` is allowed to perform the given moderation action upon `target_id`
+/// within a party, following the role hierarchy.
+///
+/// The party owner is exempt from the hierarchy, and can never be the target of an action.
+pub async fn check_member_action<DB: db::AnyClient>(
+    db: &DB,
+    party_id: PartyId,
+    user_id: UserId,
+    target_id: UserId,
+    action: UserAction,
+) -> Result<(), Error> {
+    if user_id == target_id {
+        return Err(Error::BadRequest);
+    }
+
+    #[rustfmt::skip]
+    let role_rows = db.query2(schema::sql! {
+        SELECT
+            Party.OwnerId AS @OwnerId,
+            Roles.Id AS @RoleId,
+            Roles.Position AS @Position,
+            Roles.Permissions1 AS @Permissions1,
+            Roles.Permissions2 AS @Permissions2,
+            EXISTS(
+                SELECT FROM RoleMembers
+                WHERE RoleMembers.RoleId = Roles.Id
+                  AND RoleMembers.UserId = #{&user_id as Users::Id}
+            ) AS @HasRole,
+            EXISTS(
+                SELECT FROM RoleMembers
+                WHERE RoleMembers.RoleId = Roles.Id
+                  AND RoleMembers.UserId = #{&target_id as Users::Id}
+            ) AS @TargetHasRole
+        FROM Roles INNER JOIN LiveParties AS Party ON Party.Id = Roles.PartyId
+        WHERE Roles.PartyId = #{&party_id as Party::Id}
+          AND EXISTS(
+            SELECT FROM PartyMembers
+            WHERE PartyMembers.PartyId = Roles.PartyId
+              AND PartyMembers.UserId = #{&user_id as Users::Id}
+          )
+    }).await?;
+
+    let Some(first) = role_rows.first() else {
+        return Err(Error::Unauthorized);
+    };
+
+    let owner_id: UserId = first.owner_id()?;
+
+    if target_id == owner_id {
+        return Err(Error::Unauthorized);
+    }
+
+    if user_id == owner_id {
+        return Ok(());
+    }
+
+    // @everyone applies to all members
+    let mut user_roles = vec![party_id];
+    let mut target_roles = Vec::new();
+    let mut roles = Vec::with_capacity(role_rows.len());
+
+    for row in role_rows {
+        let id: RoleId = row.role_id()?;
+
+        let role = PartialRole {
+            permissions: Permissions::from_i64(row.permissions1()?, row.permissions2()?),
+            position: row.position::<i16>()? as u8,
+        };
+
+        roles.push((id, role));
+
+        if row.has_role()? {
+            user_roles.push(id);
+        }
+
+        if row.target_has_role()? {
+            target_roles.push(id);
+        }
+    }
+
+    match RoleChecker::new(party_id, roles).check_user(&user_roles, &target_roles, action) {
+        CheckStatus::Allowed(()) => Ok(()),
+        // TODO: improve errors from CheckStatus
+        _ => Err(Error::Unauthorized),
+    }
+}
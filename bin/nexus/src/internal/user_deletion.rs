@@ -0,0 +1,52 @@
+use rand::distributions::{Alphanumeric, DistString};
+
+use crate::prelude::*;
+
+/// Whether the email is the tombstone left by the `purge_user` procedure, rather than an address to notify
+pub fn is_purged_email(email: &str) -> bool {
+    email.ends_with("@purged.invalid")
+}
+
+/// Soft-deletes a user, releasing their username and handing off any owned parties.
+/// Any applications they own are removed, with their bot users deleted the same way.
+///
+/// Returns the ids of deleted bots, whose cached authorizations should also be revoked.
+///
+/// Remaining personal data is purged by a background task after the configured `user_purge_delay`.
+pub async fn soft_delete_user<DB: db::AnyClient>(db: &DB, user_id: UserId) -> Result<Vec<UserId>, Error> {
+    #[rustfmt::skip]
+    let rows = db.query2(schema::sql! {
+        DELETE FROM Apps WHERE Apps.OwnerId = #{&user_id as Apps::OwnerId}
+        RETURNING Apps.BotId AS @BotId
+    }).await?;
+
+    let mut bot_ids = Vec::with_capacity(rows.len());
+
+    for row in rows {
+        if let Some(bot_id) = row.bot_id()? {
+            bot_ids.push(bot_id);
+        }
+    }
+
+    for &id in std::iter::once(&user_id).chain(&bot_ids) {
+        delete_user_row(db, id).await?;
+    }
+
+    Ok(bot_ids)
+}
+
+async fn delete_user_row<DB: db::AnyClient>(db: &DB, user_id: UserId) -> Result<(), Error> {
+    // generate 10 alphanumeric characters for the new username
+    let mut new_username = "DeletedUser ".to_owned();
+    Alphanumeric.append_string(&mut rand::thread_rng(), &mut new_username, 10);
+
+    db.execute2(schema::sql! {
+        CALL .soft_delete_user(
+            #{&user_id as Users::Id},
+            #{&new_username as Users::Username}
+        )
+    })
+    .await?;
+
+    Ok(())
+}
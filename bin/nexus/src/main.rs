@@ -53,8 +53,7 @@ async fn main() -> anyhow::Result<()> {
 
     log::debug!("Arguments: {:?}", args);
 
-    let mut local = config::LocalConfig::default();
-    ::config::Configuration::configure(&mut local);
+    let local: config::LocalConfig = ::config::load_file(args.config.as_deref())?;
 
     // setup full logger
     log::info!("Setting up log-file rotation in {}", local.paths.log_dir.display());
@@ -77,17 +76,25 @@ async fn main() -> anyhow::Result<()> {
     let shared = {
         let db = db.read.get().await?;
 
-        schema::config::SharedConfig::load(&db).await?
+        let shared = schema::config::SharedConfig::load(&db, Default::default()).await?;
+        shared.validate()?;
+        shared
     };
 
     let state = state::ServerState::new(config::Config { local, shared }, db);
 
     log::info!("Running startup tasks...");
-    // todo
+
+    if let Err(e) = state.services.mailer.templates.reload().await {
+        log::error!("Error loading email templates: {e}");
+    }
+
+    state.gateway.subscriptions.load(&state).await?;
 
     log::info!("Starting tasks...");
     let runner = tasks::TaskRunner::default();
     tasks::add_tasks(&state, &runner);
+    tasks::add_config_reload_task(&state, &runner, args.config.clone());
 
     log::trace!("Setting up shutdown signal for Ctrl+C");
     let shutdown = runner.signal();
@@ -96,6 +103,19 @@ async fn main() -> anyhow::Result<()> {
         shutdown.stop();
     });
 
+    #[cfg(unix)]
+    {
+        log::trace!("Setting up config reload signal for SIGHUP");
+        let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())?;
+        let state = state.clone();
+        tokio::spawn(async move {
+            while hangup.recv().await.is_some() {
+                log::info!("Received SIGHUP, reloading config");
+                state.config.trigger_reload();
+            }
+        });
+    }
+
     log::info!("Nexus server started");
     runner.wait().await?;
 
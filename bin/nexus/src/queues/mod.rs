@@ -6,12 +6,14 @@ pub use queue::{Queue, WorkItem};
 
 pub struct Queues {
     pub embed_processing: Queue,
+    pub data_export: Queue,
 }
 
 impl Default for Queues {
     fn default() -> Self {
         Queues {
             embed_processing: Queue::start(64),
+            data_export: Queue::start(2),
         }
     }
 }
@@ -0,0 +1,61 @@
+use std::{
+    net::IpAddr,
+    time::{Duration, SystemTime},
+};
+
+use iplist::IpNet;
+use sdk::{api::commands::admin::CreateIpBan, models::*};
+
+use crate::{internal::audit::MAX_REASON_LENGTH, prelude::*};
+
+pub async fn create_ip_ban(state: ServerState, auth: Authorization, cmd: &Archived<CreateIpBan>) -> Result<IpBan, Error> {
+    if !auth.is_admin() {
+        return Err(Error::Unauthorized);
+    }
+
+    let form = &cmd.body;
+
+    if matches!(form.reason.as_deref(), Some(reason) if reason.len() > MAX_REASON_LENGTH) {
+        return Err(Error::BadRequest);
+    }
+
+    // accept either a CIDR range or a single address, and clear any host bits of a range
+    let net = match form.address.parse::<IpNet>() {
+        Ok(net) => net.trunc(),
+        Err(_) => match form.address.parse::<IpAddr>() {
+            Ok(addr) => IpNet::from(addr),
+            Err(_) => return Err(Error::BadRequest),
+        },
+    };
+
+    // single addresses are stored as such, to use the btree index
+    let (address, network) = match net.prefix_len() == net.max_prefix_len() {
+        true => (Some(net.addr()), None),
+        false => (None, Some(net.to_string())),
+    };
+
+    let expires = form.duration.as_ref().map(|secs| SystemTime::now() + Duration::from_secs(secs.to_native()));
+    let reason = form.reason.as_deref();
+
+    let ban_id = state.sf.gen();
+
+    #[rustfmt::skip]
+    state.db.write.get().await?.execute2(schema::sql! {
+        INSERT INTO IpBans (Id, Expires, Address, Network, Reason) VALUES (
+            #{&ban_id   as IpBans::Id},
+            #{&expires  as IpBans::Expires},
+            #{&address  as IpBans::Address},
+            #{&network  as Type::TEXT}::cidr,
+            #{&reason   as IpBans::Reason}
+        )
+    }).await?;
+
+    log::info!("IP ban {ban_id} on {net} created by {}", auth.user_id());
+
+    Ok(IpBan {
+        id: ban_id,
+        address: SmolStr::from(net.to_string()),
+        expires: expires.map(Into::into),
+        reason: reason.map(SmolStr::from),
+    })
+}
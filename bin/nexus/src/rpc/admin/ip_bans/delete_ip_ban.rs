@@ -0,0 +1,24 @@
+use sdk::api::commands::admin::DeleteIpBan;
+
+use crate::prelude::*;
+
+pub async fn delete_ip_ban(state: ServerState, auth: Authorization, cmd: &Archived<DeleteIpBan>) -> Result<(), Error> {
+    if !auth.is_admin() {
+        return Err(Error::Unauthorized);
+    }
+
+    let ban_id: Snowflake = cmd.ban_id.into();
+
+    #[rustfmt::skip]
+    let res = state.db.write.get().await?.execute2(schema::sql! {
+        DELETE FROM IpBans WHERE IpBans.Id = #{&ban_id as IpBans::Id}
+    }).await?;
+
+    if res == 0 {
+        return Err(Error::NotFound);
+    }
+
+    log::info!("IP ban {ban_id} removed by {}", auth.user_id());
+
+    Ok(())
+}
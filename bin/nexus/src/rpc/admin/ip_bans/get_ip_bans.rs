@@ -0,0 +1,40 @@
+use std::net::IpAddr;
+
+use sdk::{api::commands::admin::GetIpBans, models::*};
+
+use crate::prelude::*;
+
+pub async fn get_ip_bans(
+    state: ServerState,
+    auth: Authorization,
+    _cmd: &Archived<GetIpBans>,
+) -> Result<impl Stream<Item = Result<IpBan, Error>>, Error> {
+    if !auth.is_admin() {
+        return Err(Error::Unauthorized);
+    }
+
+    #[rustfmt::skip]
+    let stream = state.db.read.get().await?.query_stream2(schema::sql! {
+        SELECT
+            IpBans.Id       AS @_,
+            IpBans.Expires  AS @_,
+            IpBans.Address  AS @_,
+            IpBans.Network::text AS @Network,
+            IpBans.Reason   AS @_
+        FROM IpBans
+        ORDER BY IpBans.Id DESC
+    }).await?;
+
+    Ok(stream.map(|row| match row {
+        Err(e) => Err(Error::from(e)),
+        Ok(row) => Ok(IpBan {
+            id: row.ip_bans_id()?,
+            address: match row.ip_bans_address::<Option<IpAddr>>()? {
+                Some(addr) => SmolStr::from(addr.to_string()),
+                None => row.network::<Option<SmolStr>>()?.unwrap_or_default(),
+            },
+            expires: row.ip_bans_expires()?,
+            reason: row.ip_bans_reason()?,
+        }),
+    }))
+}
@@ -1,7 +1,56 @@
+use sdk::models::{ElevationLevel, UserFlags};
+
+use crate::prelude::*;
+
 //pub mod auth;
 
+pub mod ip_bans {
+    pub mod create_ip_ban;
+    pub mod delete_ip_ban;
+    pub mod get_ip_bans;
+}
+
+pub mod party {
+    pub mod migrate;
+}
+
 pub mod user {
     pub mod ban;
     pub mod delete;
+    pub mod logout;
+    pub mod lookup;
     pub mod unban;
 }
+
+pub struct TargetUser {
+    pub username: String,
+    pub email: String,
+    pub flags: UserFlags,
+}
+
+/// Fetches the target of a site-admin action, refusing if the target is staff themselves
+pub async fn get_target_user<DB: db::AnyClient>(db: &DB, user_id: UserId) -> Result<TargetUser, Error> {
+    #[rustfmt::skip]
+    let Some(row) = db.query_opt2(schema::sql! {
+        SELECT
+            Users.Username  AS @Username,
+            Users.Email     AS @Email,
+            Users.Flags     AS @Flags
+        FROM LiveUsers AS Users
+        WHERE Users.Id = #{&user_id as Users::Id}
+    }).await? else {
+        return Err(Error::NotFound);
+    };
+
+    let flags = UserFlags::from_bits_truncate(row.flags()?);
+
+    if flags.elevation() >= ElevationLevel::Staff {
+        return Err(Error::Unauthorized);
+    }
+
+    Ok(TargetUser {
+        username: row.username()?,
+        email: row.email()?,
+        flags,
+    })
+}
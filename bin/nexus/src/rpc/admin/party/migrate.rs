@@ -0,0 +1,153 @@
+use std::time::{Duration, Instant, SystemTime};
+
+use rpc::request::PartyMigration;
+use sdk::api::commands::admin::MigrateParty;
+use uuid::Uuid;
+
+use crate::{
+    internal::faction::{drain_party_requests, FACTION_TIMEOUT, FENCE_TIMEOUT},
+    prelude::*,
+};
+
+/// How often to check whether the faction serving the party has acknowledged its fence
+const FENCE_POLL: Duration = Duration::from_millis(50);
+
+/// Moves a party to another faction node, either the one given or the least-loaded live faction.
+///
+/// Gateways hold new requests for the party while it moves, and the faction serving it acknowledges
+/// once requests already in progress have finished. Gateways then resolve its new location and
+/// release held requests, so clients should see no more than a brief pause.
+pub async fn migrate_party(state: ServerState, auth: Authorization, cmd: &Archived<MigrateParty>) -> Result<(), Error> {
+    if !auth.is_admin() {
+        return Err(Error::Unauthorized);
+    }
+
+    let party_id: PartyId = cmd.party_id.into();
+    let target = cmd.body.faction_id.as_ref().map(|&id| Uuid::from_u128(id.into()));
+    let cutoff = SystemTime::now() - FACTION_TIMEOUT;
+
+    let db = state.db.write.get().await?;
+
+    #[rustfmt::skip]
+    let Some(row) = db.query_opt2(schema::sql! {
+        SELECT
+            FactionParties.FactionId AS @FactionId,
+            Factions.LastSeen AS @LastSeen
+        FROM LiveParties AS Party
+            LEFT JOIN FactionParties ON FactionParties.PartyId = Party.Id
+            LEFT JOIN Factions ON Factions.Id = FactionParties.FactionId
+        WHERE Party.Id = #{&party_id as Party::Id}
+    }).await? else {
+        return Err(Error::NotFound);
+    };
+
+    let current: Option<Uuid> = row.faction_id()?;
+    let last_seen: Option<SystemTime> = row.last_seen()?;
+
+    // parties of a faction that has gone down are not being served by it
+    let serving = current.filter(|_| last_seen.is_some_and(|last_seen| last_seen > cutoff));
+
+    #[rustfmt::skip]
+    let Some(row) = db.query_opt2(schema::sql! {
+        SELECT Factions.Id AS @FactionId
+        FROM Factions
+        WHERE Factions.LastSeen > #{&cutoff as Factions::LastSeen}
+        if let Some(ref current) = current {
+            AND Factions.Id != #{current as Factions::Id}
+        }
+        match target {
+            Some(ref target) => { AND Factions.Id = #{target as Factions::Id} }
+            None => {
+                ORDER BY (
+                    SELECT COUNT(*) FROM FactionParties WHERE FactionParties.FactionId = Factions.Id
+                ) ASC
+            }
+        }
+        LIMIT 1
+    }).await? else {
+        return Err(Error::NotFound);
+    };
+
+    let target: Uuid = row.faction_id()?;
+
+    _ = state.party_migrations.send(PartyMigration::Freeze(party_id));
+
+    // let requests already in progress finish where the party is now
+    let res = match fence_party(&state, &db, party_id, serving).await {
+        Err(e) => Err(e),
+        #[rustfmt::skip]
+        Ok(()) => db.execute2(schema::sql! {
+            INSERT INTO FactionParties (FactionId, PartyId) VALUES (
+                #{&target as FactionParties::FactionId},
+                #{&party_id as FactionParties::PartyId}
+            )
+            ON CONFLICT (FactionParties./PartyId) DO UPDATE FactionParties SET (FactionId) = (
+                #{&target as FactionParties::FactionId}
+            )
+        }).await.map_err(Error::from),
+    };
+
+    #[rustfmt::skip]
+    let cleared = db.execute2(schema::sql! {
+        DELETE FROM PartyFences WHERE PartyFences.PartyId = #{&party_id as PartyFences::PartyId}
+    }).await;
+
+    // always release held requests, which resolve to the old faction if the move failed
+    _ = state.party_migrations.send(PartyMigration::Moved(party_id));
+
+    res?;
+    cleared?;
+
+    log::info!("Party {party_id} migrated from {current:?} to {target} by {}", auth.user_id());
+
+    Ok(())
+}
+
+/// Waits for the faction serving the party to acknowledge it has finished requests already in progress.
+///
+/// Parties not served by a live faction are only drained on this node.
+async fn fence_party(
+    state: &ServerState,
+    db: &db::Object,
+    party_id: PartyId,
+    serving: Option<Uuid>,
+) -> Result<(), Error> {
+    let Some(faction_id) = serving else {
+        drain_party_requests(state).await;
+
+        return Ok(());
+    };
+
+    #[rustfmt::skip]
+    db.execute2(schema::sql! {
+        INSERT INTO PartyFences (PartyId, FactionId) VALUES (
+            #{&party_id as PartyFences::PartyId},
+            #{&faction_id as PartyFences::FactionId}
+        )
+        ON CONFLICT (PartyFences./PartyId) DO UPDATE PartyFences SET (FactionId, Acked) = (
+            #{&faction_id as PartyFences::FactionId}, FALSE
+        )
+    }).await?;
+
+    let deadline = Instant::now() + FENCE_TIMEOUT;
+
+    loop {
+        #[rustfmt::skip]
+        let row = db.query_one2(schema::sql! {
+            SELECT PartyFences.Acked AS @Acked FROM PartyFences
+            WHERE PartyFences.PartyId = #{&party_id as PartyFences::PartyId}
+        }).await?;
+
+        if row.acked()? {
+            return Ok(());
+        }
+
+        if Instant::now() >= deadline {
+            log::warn!("Faction {faction_id} did not acknowledge the migration fence for party {party_id}");
+
+            return Err(Error::InternalErrorStatic("Migration fence not acknowledged"));
+        }
+
+        tokio::time::sleep(FENCE_POLL).await;
+    }
+}
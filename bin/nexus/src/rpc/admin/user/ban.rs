@@ -1,26 +1,78 @@
-use sdk::models::UserFlags;
+use std::time::{Duration, SystemTime};
 
-use crate::prelude::*;
+use email::{scenarios::UserBanned, Email};
+use sdk::{api::commands::admin::BanUser, models::UserFlags};
 
-pub async fn ban_user(state: ServerState, user_id: UserId) -> Result<(), Error> {
-    let mut db = state.db.write.get().await?;
+use crate::{internal::audit::MAX_REASON_LENGTH, prelude::*, services::email::send_email};
+
+pub async fn ban_user(state: ServerState, auth: Authorization, cmd: &Archived<BanUser>) -> Result<(), Error> {
+    if !auth.is_admin() {
+        return Err(Error::Unauthorized);
+    }
+
+    let user_id: UserId = cmd.user_id.into();
+    let form = &cmd.body;
+
+    if user_id == auth.user_id() {
+        return Err(Error::BadRequest);
+    }
 
+    if matches!(form.reason.as_deref(), Some(reason) if reason.len() > MAX_REASON_LENGTH) {
+        return Err(Error::BadRequest);
+    }
+
+    let expires = form.duration.as_ref().map(|secs| SystemTime::now() + Duration::from_secs(secs.to_native()));
+    let reason = form.reason.as_deref();
+
+    let mut db = state.db.write.get().await?;
     let t = db.transaction().await?;
 
+    let target = super::super::get_target_user(&t, user_id).await?;
+
     let do_ban_user = t.execute2(schema::sql! {
         UPDATE Users SET (Flags) = (Users.Flags | const {UserFlags::BANNED.bits()})
         WHERE Users.Id = #{&user_id as Users::Id}
     });
 
+    #[rustfmt::skip]
+    let record_ban = t.execute2(schema::sql! {
+        INSERT INTO UserBans (UserId, BannedBy, Expires, Reason) VALUES (
+            #{&user_id          as UserBans::UserId},
+            #{auth.user_id_ref() as UserBans::BannedBy},
+            #{&expires          as UserBans::Expires},
+            #{&reason           as UserBans::Reason}
+        )
+        ON CONFLICT (UserBans./UserId) DO UPDATE UserBans SET (BannedBy, BannedAt, Expires, Reason) = (
+            #{auth.user_id_ref() as UserBans::BannedBy},
+            now(),
+            #{&expires          as UserBans::Expires},
+            #{&reason           as UserBans::Reason}
+        )
+    });
+
     let clear_sessions = t.execute2(schema::sql! {
         DELETE FROM Sessions WHERE Sessions.UserId = #{&user_id as Users::Id}
     });
 
-    // TODO: Setup task to soft-delete user after 30 days or so.
-
-    tokio::try_join!(do_ban_user, clear_sessions)?;
+    tokio::try_join!(do_ban_user, record_ban, clear_sessions)?;
 
     t.commit().await?;
 
+    // drop any cached authorizations on the gateways
+    _ = state.revoked_users.send(user_id);
+
+    log::info!("User {user_id} banned by {}", auth.user_id());
+
+    let expires = expires.map(|expires| Timestamp::from(expires).to_string());
+
+    send_email(
+        &state,
+        Email::new(
+            target.email,
+            "Your Lantern account has been banned",
+            UserBanned::new(target.username, reason.unwrap_or("No reason given"), expires),
+        ),
+    );
+
     Ok(())
 }
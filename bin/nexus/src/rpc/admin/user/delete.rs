@@ -1,23 +1,34 @@
-use rand::distributions::{Alphanumeric, DistString};
-
-use crate::prelude::*;
-
-// NOTE: It's assumed that the calling user has permission to do this
-pub async fn delete_user(state: ServerState, user_id: UserId) -> Result<(), Error> {
-    // generate 10 alphanumeric characters for the new username
-    let mut new_username = "DeletedUser ".to_owned();
-    Alphanumeric.append_string(&mut rand::thread_rng(), &mut new_username, 10);
-
-    let db = state.db.write.get().await?;
-
-    // TODO: Typecheck this procedure
-    db.execute2(schema::sql! {
-        CALL .soft_delete_user(
-            #{&user_id as Users::Id},
-            #{&new_username as Users::Username}
-        )
-    })
-    .await?;
+use sdk::api::commands::admin::DeleteUser;
+
+use crate::{internal::user_deletion::soft_delete_user, prelude::*};
+
+/// Soft-deletes a user, which is then fully purged by a background task
+/// after the configured `user_purge_delay`.
+pub async fn delete_user(state: ServerState, auth: Authorization, cmd: &Archived<DeleteUser>) -> Result<(), Error> {
+    if !auth.is_admin() {
+        return Err(Error::Unauthorized);
+    }
+
+    let user_id: UserId = cmd.user_id.into();
+
+    if user_id == auth.user_id() {
+        return Err(Error::BadRequest);
+    }
+
+    let mut db = state.db.write.get().await?;
+    let t = db.transaction().await?;
+
+    super::super::get_target_user(&t, user_id).await?;
+
+    let bot_ids = soft_delete_user(&t, user_id).await?;
+
+    t.commit().await?;
+
+    for id in std::iter::once(user_id).chain(bot_ids) {
+        _ = state.revoked_users.send(id);
+    }
+
+    log::info!("User {user_id} deleted by {}", auth.user_id());
 
     Ok(())
 }
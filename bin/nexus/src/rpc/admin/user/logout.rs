@@ -0,0 +1,27 @@
+use sdk::api::commands::admin::LogoutUser;
+
+use crate::prelude::*;
+
+/// Clears all sessions of a user, logging them out everywhere
+pub async fn logout_user(state: ServerState, auth: Authorization, cmd: &Archived<LogoutUser>) -> Result<(), Error> {
+    if !auth.is_admin() {
+        return Err(Error::Unauthorized);
+    }
+
+    let user_id: UserId = cmd.user_id.into();
+
+    let db = state.db.write.get().await?;
+
+    super::super::get_target_user(&db, user_id).await?;
+
+    #[rustfmt::skip]
+    let sessions = db.execute2(schema::sql! {
+        DELETE FROM Sessions WHERE Sessions.UserId = #{&user_id as Users::Id}
+    }).await?;
+
+    _ = state.revoked_users.send(user_id);
+
+    log::info!("User {user_id} logged out of {sessions} sessions by {}", auth.user_id());
+
+    Ok(())
+}
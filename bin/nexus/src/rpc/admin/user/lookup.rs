@@ -0,0 +1,49 @@
+use sdk::{api::commands::admin::LookupUser, models::*};
+
+use crate::prelude::*;
+
+/// Finds a user by either their email address or `username#discriminator`
+pub async fn lookup_user(state: ServerState, auth: Authorization, cmd: &Archived<LookupUser>) -> Result<User, Error> {
+    if !auth.is_admin() {
+        return Err(Error::Unauthorized);
+    }
+
+    let form = &cmd.body;
+
+    let (username, discriminator) = match form.username.as_deref() {
+        None => (None, None),
+        Some(username) => match username.rsplit_once('#') {
+            Some((username, discriminator)) => match u16::from_str_radix(discriminator, 16) {
+                Ok(discriminator) => (Some(username), Some(discriminator as i32)),
+                Err(_) => return Err(Error::BadRequest),
+            },
+            None => return Err(Error::BadRequest),
+        },
+    };
+
+    let email = form.email.as_deref();
+
+    if username.is_none() && email.is_none() {
+        return Err(Error::BadRequest);
+    }
+
+    let db = state.db.read.get().await?;
+
+    #[rustfmt::skip]
+    let Some(row) = db.query_opt2(schema::sql! {
+        SELECT Users.Id AS @Id
+        FROM LiveUsers AS Users
+        WHERE TRUE
+
+        if username.is_some() {
+            AND Users.Username = #{&username as Users::Username}
+            AND Users.Discriminator = #{&discriminator as Users::Discriminator}
+        }
+
+        if email.is_some() { AND Users.Email = #{&email as Users::Email} }
+    }).await? else {
+        return Err(Error::NotFound);
+    };
+
+    crate::rpc::user::me::user_get_self::get_full_self_inner(&state, row.id()?, &db).await
+}
@@ -1,15 +1,57 @@
-use sdk::models::UserFlags;
+use email::{scenarios::UserUnbanned, Email};
+use sdk::{api::commands::admin::UnbanUser, models::UserFlags};
 
-use crate::prelude::*;
+use crate::{prelude::*, services::email::send_email};
 
-pub async fn unban_user(state: ServerState, user_id: UserId) -> Result<(), Error> {
-    let db = state.db.write.get().await?;
+pub async fn unban_user(state: ServerState, auth: Authorization, cmd: &Archived<UnbanUser>) -> Result<(), Error> {
+    if !auth.is_admin() {
+        return Err(Error::Unauthorized);
+    }
 
-    db.execute2(schema::sql! {
+    let user_id: UserId = cmd.user_id.into();
+
+    let mut db = state.db.write.get().await?;
+    let t = db.transaction().await?;
+
+    #[rustfmt::skip]
+    let Some(row) = t.query_opt2(schema::sql! {
         UPDATE Users SET (Flags) = (Users.Flags & ~const {UserFlags::BANNED.bits()})
         WHERE Users.Id = #{&user_id as Users::Id}
+          AND Users.Flags & const {UserFlags::BANNED.bits()} != 0
+        RETURNING
+            Users.Username AS @Username,
+            Users.Email AS @Email
+    }).await? else {
+        t.rollback().await?;
+
+        return Err(Error::NotFound);
+    };
+
+    t.execute2(schema::sql! {
+        DELETE FROM UserBans WHERE UserBans.UserId = #{&user_id as Users::Id}
     })
     .await?;
 
+    t.commit().await?;
+
+    log::info!("User {user_id} unbanned by {}", auth.user_id());
+
+    let username: String = row.username()?;
+    let email: String = row.email()?;
+
+    // purged accounts have no email to notify
+    if crate::internal::user_deletion::is_purged_email(&email) {
+        return Ok(());
+    }
+
+    send_email(
+        &state,
+        Email::new(
+            email,
+            "Your Lantern account has been unbanned",
+            UserUnbanned::new(username),
+        ),
+    );
+
     Ok(())
 }
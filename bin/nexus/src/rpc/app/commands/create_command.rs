@@ -0,0 +1,153 @@
+use std::collections::HashSet;
+
+use sdk::{api::commands::all::CreateAppCommand, models::*};
+use thorn::pg::Json;
+
+use crate::{prelude::*, rpc::room::messages::create_message::slash};
+
+/// Maximum number of commands a single app may register, across all parties
+pub const MAX_COMMANDS: i64 = 100;
+
+/// Maximum number of arguments a command may take
+pub const MAX_OPTIONS: usize = 10;
+
+const MAX_NAME_LENGTH: usize = 32;
+const MAX_DESCRIPTION_LENGTH: usize = 100;
+
+/// Command and option names are lowercase, so they can be typed without looking them up
+fn validate_name(name: &str) -> bool {
+    (1..=MAX_NAME_LENGTH).contains(&name.len())
+        && name.bytes().all(|b| matches!(b, b'a'..=b'z' | b'0'..=b'9' | b'_' | b'-'))
+}
+
+/// Registers a slash command for the authorized bot, either globally or for a single party it's a member of.
+///
+/// Registering a command with the same name and scope as an existing one replaces it.
+pub async fn create_command(
+    state: ServerState,
+    auth: Authorization,
+    cmd: &Archived<CreateAppCommand>,
+) -> Result<AppCommand, Error> {
+    // commands belong to the bot, not the user who owns it
+    if !auth.is_bot() {
+        return Err(Error::Unauthorized);
+    }
+
+    let form = &cmd.body;
+
+    if !validate_name(&form.name) || slash::is_builtin(&form.name) {
+        return Err(Error::BadRequest);
+    }
+
+    if form.description.len() > MAX_DESCRIPTION_LENGTH {
+        return Err(Error::BadRequest);
+    }
+
+    let options: ThinVec<AppCommandOption> =
+        form.options.deserialize_simple().expect("Unable to deserialize options");
+
+    if options.len() > MAX_OPTIONS {
+        return Err(Error::BadRequest);
+    }
+
+    let mut names = HashSet::with_capacity(options.len());
+    let mut optional = false;
+
+    for option in &options {
+        if !validate_name(&option.name) || !names.insert(&*option.name) {
+            return Err(Error::BadRequest);
+        }
+
+        if option.description.len() > MAX_DESCRIPTION_LENGTH {
+            return Err(Error::BadRequest);
+        }
+
+        // arguments are positional, so a required one can't follow an optional one
+        if option.required && optional {
+            return Err(Error::BadRequest);
+        }
+
+        optional |= !option.required;
+    }
+
+    let party_id: Option<PartyId> = form.party_id.as_ref().map(|&id| id.into());
+    let command_id = state.sf.gen();
+    let name = &*form.name;
+    let description = &*form.description;
+    let json_options = Json(&options);
+
+    let mut db = state.db.write.get().await?;
+    let t = db.transaction().await?;
+
+    #[rustfmt::skip]
+    let Some(row) = t.query_opt2(schema::sql! {
+        SELECT
+            Apps.Id AS @AppId,
+            (
+                SELECT COUNT(AppCommands.Id) FROM AppCommands
+                WHERE AppCommands.AppId = Apps.Id
+            ) AS @Count,
+            EXISTS(
+                SELECT FROM PartyMembers
+                WHERE PartyMembers.UserId = Apps.BotId
+                  AND PartyMembers.PartyId = #{&party_id as Party::Id}
+            ) AS @IsMember
+        FROM Apps WHERE Apps.BotId = #{auth.user_id_ref() as Apps::BotId}
+    }).await? else {
+        t.rollback().await?;
+
+        return Err(Error::NotFound);
+    };
+
+    if party_id.is_some() && !row.is_member()? {
+        t.rollback().await?;
+
+        return Err(Error::NotFound);
+    }
+
+    let app_id: Snowflake = row.app_id()?;
+
+    // replace any existing command of the same name and scope, which also frees up its place in the limit
+    #[rustfmt::skip]
+    let replaced = t.execute2(schema::sql! {
+        DELETE FROM AppCommands
+        WHERE AppCommands.AppId = #{&app_id as AppCommands::AppId}
+          AND AppCommands.Name = #{&name as AppCommands::Name}
+          AND if party_id.is_some() {
+                AppCommands.PartyId = #{&party_id as AppCommands::PartyId}
+            } else {
+                AppCommands.PartyId IS NULL
+            }
+    }).await?;
+
+    if row.count::<i64>()? - replaced as i64 >= MAX_COMMANDS {
+        t.rollback().await?;
+
+        return Err(Error::BadRequest);
+    }
+
+    #[rustfmt::skip]
+    t.execute2(schema::sql! {
+        INSERT INTO AppCommands (Id, AppId, PartyId, Name, Description, Options) VALUES (
+            #{&command_id   as AppCommands::Id},
+            #{&app_id       as AppCommands::AppId},
+            #{&party_id     as AppCommands::PartyId},
+            #{&name         as AppCommands::Name},
+            #{&description  as AppCommands::Description},
+            #{&json_options as AppCommands::Options}
+        )
+    }).await?;
+
+    t.commit().await?;
+
+    log::debug!("App {app_id} registered command /{name}");
+
+    Ok(AppCommand {
+        id: command_id,
+        app_id,
+        party_id,
+        name: SmolStr::from(name),
+        description: SmolStr::from(description),
+        options,
+    })
+}
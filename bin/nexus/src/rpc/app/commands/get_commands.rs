@@ -0,0 +1,40 @@
+use sdk::models::*;
+use thorn::pg::Json;
+
+use crate::prelude::*;
+
+/// Lists the commands registered by the authorized bot, in every scope
+pub async fn get_commands(
+    state: ServerState,
+    auth: Authorization,
+) -> Result<impl Stream<Item = Result<AppCommand, Error>>, Error> {
+    if !auth.is_bot() {
+        return Err(Error::Unauthorized);
+    }
+
+    #[rustfmt::skip]
+    let stream = state.db.read.get().await?.query_stream2(schema::sql! {
+        SELECT
+            AppCommands.Id          AS @_,
+            AppCommands.AppId       AS @_,
+            AppCommands.PartyId     AS @_,
+            AppCommands.Name        AS @_,
+            AppCommands.Description AS @_,
+            AppCommands.Options     AS @_
+        FROM AppCommands INNER JOIN Apps ON Apps.Id = AppCommands.AppId
+        WHERE Apps.BotId = #{auth.user_id_ref() as Apps::BotId}
+        ORDER BY AppCommands.Id ASC
+    }).await?;
+
+    Ok(stream.map(move |row| match row {
+        Err(e) => Err(Error::from(e)),
+        Ok(row) => Ok(AppCommand {
+            id: row.app_commands_id()?,
+            app_id: row.app_commands_app_id()?,
+            party_id: row.app_commands_party_id()?,
+            name: row.app_commands_name()?,
+            description: row.app_commands_description()?,
+            options: row.app_commands_options::<Json<_>>()?.0,
+        }),
+    }))
+}
@@ -0,0 +1,30 @@
+use sdk::api::commands::all::DeleteAppCommand;
+
+use crate::prelude::*;
+
+/// Unregisters one of the authorized bot's commands. Pending interactions with it can no longer be responded to.
+pub async fn remove_command(
+    state: ServerState,
+    auth: Authorization,
+    cmd: &Archived<DeleteAppCommand>,
+) -> Result<(), Error> {
+    if !auth.is_bot() {
+        return Err(Error::Unauthorized);
+    }
+
+    let command_id: Snowflake = cmd.command_id.into();
+
+    #[rustfmt::skip]
+    let removed = state.db.write.get().await?.execute2(schema::sql! {
+        DELETE FROM AppCommands USING Apps
+        WHERE AppCommands.Id = #{&command_id as AppCommands::Id}
+          AND Apps.Id = AppCommands.AppId
+          AND Apps.BotId = #{auth.user_id_ref() as Apps::BotId}
+    }).await?;
+
+    if removed == 0 {
+        return Err(Error::NotFound);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use std::time::{Duration, SystemTime};
+
+use sdk::{api::commands::all::CreateApp, models::*};
+
+use crate::prelude::*;
+
+/// Maximum number of applications a single user may own
+pub const MAX_APPS: i64 = 25;
+
+/// Creates an application along with its bot user.
+///
+/// The bot cannot log in by itself, so a token must be generated with `RegenerateBotToken` before use.
+pub async fn create_app(state: ServerState, auth: Authorization, cmd: &Archived<CreateApp>) -> Result<App, Error> {
+    // bots cannot create more bots
+    if !auth.is_user() {
+        return Err(Error::Unauthorized);
+    }
+
+    let form = &cmd.body;
+
+    {
+        let config = state.config();
+
+        if !schema::validation::validate_username(&form.name, config.shared.username_length.clone()) {
+            return Err(Error::InvalidUsername);
+        }
+
+        if matches!(form.description.as_deref(), Some(desc) if desc.len() > config.shared.max_bio_length) {
+            return Err(Error::BadRequest);
+        }
+    }
+
+    let app_id = state.sf.gen();
+    let bot_id = state.sf.gen();
+
+    // tokens only store whole seconds
+    let issued = SystemTime::UNIX_EPOCH
+        + Duration::from_secs(SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs());
+
+    // bots have no email or password, but emails must still be unique
+    let email = format!("{bot_id}@bots.invalid");
+    let dob = Timestamp::UNIX_EPOCH.date();
+    let bot_flags = UserFlags::empty().with_elevation(ElevationLevel::Bot).bits();
+
+    let mut db = state.db.write.get().await?;
+    let t = db.transaction().await?;
+
+    #[rustfmt::skip]
+    let row = t.query_one2(schema::sql! {
+        SELECT COUNT(Apps.Id) AS @Count FROM Apps
+        WHERE Apps.OwnerId = #{auth.user_id_ref() as Apps::OwnerId}
+    }).await?;
+
+    if row.count::<i64>()? >= MAX_APPS {
+        t.rollback().await?;
+
+        return Err(Error::BadRequest);
+    }
+
+    #[rustfmt::skip]
+    t.execute2(schema::sql! {
+        CALL .register_user(
+            #{&bot_id    as Users::Id},
+            #{&form.name as Users::Username},
+            #{&email     as Users::Email},
+            #{&""        as Users::Passhash},
+            #{&dob       as Users::Dob}
+        )
+    }).await?;
+
+    #[rustfmt::skip]
+    t.execute2(schema::sql! {
+        UPDATE Users SET (Flags) = (#{&bot_flags as Users::Flags})
+        WHERE Users.Id = #{&bot_id as Users::Id}
+    }).await?;
+
+    #[rustfmt::skip]
+    t.execute2(schema::sql! {
+        INSERT INTO Apps (Id, OwnerId, BotId, Issued, Flags, Name, Description) VALUES (
+            #{&app_id               as Apps::Id},
+            #{auth.user_id_ref()    as Apps::OwnerId},
+            #{&bot_id               as Apps::BotId},
+            #{&issued               as Apps::Issued},
+            #{&0i16                 as Apps::Flags},
+            #{&form.name            as Apps::Name},
+            #{&form.description     as Apps::Description}
+        )
+    }).await?;
+
+    t.commit().await?;
+
+    log::debug!("Created app {app_id} with bot {bot_id} for {}", auth.user_id());
+
+    Ok(App {
+        id: app_id,
+        owner_id: auth.user_id(),
+        bot_id: Some(bot_id),
+        name: SmolStr::from(&*form.name),
+        description: form.description.as_deref().map(SmolStr::from),
+        issued: issued.into(),
+    })
+}
@@ -0,0 +1,34 @@
+use sdk::models::*;
+
+use crate::prelude::*;
+
+/// Lists applications owned by the user
+pub async fn get_apps(
+    state: ServerState,
+    auth: Authorization,
+) -> Result<impl Stream<Item = Result<App, Error>>, Error> {
+    #[rustfmt::skip]
+    let stream = state.db.read.get().await?.query_stream2(schema::sql! {
+        SELECT
+            Apps.Id             AS @_,
+            Apps.BotId          AS @_,
+            Apps.Issued         AS @_,
+            Apps.Name           AS @_,
+            Apps.Description    AS @_
+        FROM Apps
+        WHERE Apps.OwnerId = #{auth.user_id_ref() as Apps::OwnerId}
+        ORDER BY Apps.Id ASC
+    }).await?;
+
+    Ok(stream.map(move |row| match row {
+        Err(e) => Err(Error::from(e)),
+        Ok(row) => Ok(App {
+            id: row.apps_id()?,
+            owner_id: auth.user_id(),
+            bot_id: row.apps_bot_id()?,
+            name: row.apps_name()?,
+            description: row.apps_description()?,
+            issued: row.apps_issued()?,
+        }),
+    }))
+}
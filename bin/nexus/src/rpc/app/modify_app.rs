@@ -0,0 +1,76 @@
+use sdk::{
+    api::commands::all::{PatchApp, PatchAppForm},
+    models::*,
+};
+
+use crate::prelude::*;
+
+pub async fn modify_app(state: ServerState, auth: Authorization, cmd: &Archived<PatchApp>) -> Result<App, Error> {
+    let app_id: Snowflake = cmd.app_id.into();
+    let form = &cmd.body;
+
+    if *form == PatchAppForm::default() {
+        return Err(Error::BadRequest);
+    }
+
+    {
+        let config = state.config();
+
+        if matches!(form.name.as_deref(), Some(name) if !schema::validation::validate_username(name, config.shared.username_length.clone()))
+        {
+            return Err(Error::InvalidUsername);
+        }
+
+        if matches!(form.description, Nullable::Some(ref desc) if desc.len() > config.shared.max_bio_length) {
+            return Err(Error::BadRequest);
+        }
+    }
+
+    let mut db = state.db.write.get().await?;
+    let t = db.transaction().await?;
+
+    #[rustfmt::skip]
+    let Some(row) = t.query_opt2(schema::sql! {
+        UPDATE Apps SET
+            if form.name.is_some()              { Apps./Name        = #{&form.name as Apps::Name}, }
+            if !form.description.is_undefined() { Apps./Description = #{&form.description as Apps::Description}, }
+            Apps./Flags = Apps.Flags
+        WHERE Apps.Id = #{&app_id as Apps::Id}
+          AND Apps.OwnerId = #{auth.user_id_ref() as Apps::OwnerId}
+        RETURNING
+            Apps.BotId          AS @_,
+            Apps.Issued         AS @_,
+            Apps.Name           AS @_,
+            Apps.Description    AS @_
+    }).await? else {
+        t.rollback().await?;
+
+        return Err(Error::NotFound);
+    };
+
+    let bot_id: Option<UserId> = row.apps_bot_id()?;
+
+    // the bot shares its name with the app, and renaming it may assign a new discriminator
+    if let (Some(ref bot_id), Some(name)) = (bot_id, form.name.as_deref()) {
+        #[rustfmt::skip]
+        t.execute2(schema::sql! {
+            CALL .update_user(
+                #{bot_id as Users::Id},
+                #{&name  as Users::Username},
+                NULL,
+                NULL
+            )
+        }).await?;
+    }
+
+    t.commit().await?;
+
+    Ok(App {
+        id: app_id,
+        owner_id: auth.user_id(),
+        bot_id,
+        name: row.apps_name()?,
+        description: row.apps_description()?,
+        issued: row.apps_issued()?,
+    })
+}
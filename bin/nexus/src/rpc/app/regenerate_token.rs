@@ -0,0 +1,61 @@
+use std::time::{Duration, SystemTime};
+
+use schema::auth::SplitBotToken;
+use sdk::{api::commands::all::RegenerateBotToken, models::BotToken};
+
+use crate::prelude::*;
+
+/// Issues a new token for the application's bot, invalidating any previous token.
+pub async fn regenerate_token(
+    state: ServerState,
+    auth: Authorization,
+    cmd: &Archived<RegenerateBotToken>,
+) -> Result<BotToken, Error> {
+    let app_id: Snowflake = cmd.app_id.into();
+
+    let db = state.db.write.get().await?;
+
+    #[rustfmt::skip]
+    let Some(row) = db.query_opt2(schema::sql! {
+        SELECT Apps.BotId AS @BotId, Apps.Issued AS @Issued
+        FROM Apps
+        WHERE Apps.Id = #{&app_id as Apps::Id}
+          AND Apps.OwnerId = #{auth.user_id_ref() as Apps::OwnerId}
+    }).await? else {
+        return Err(Error::NotFound);
+    };
+
+    let Some(bot_id) = row.bot_id::<Option<UserId>>()? else {
+        return Err(Error::NotFound);
+    };
+
+    let old_issued: SystemTime = row.issued()?;
+
+    let secs = |ts: SystemTime| ts.duration_since(SystemTime::UNIX_EPOCH).map_or(0, |d| d.as_secs());
+
+    // tokens are identified by their issue time in seconds, so make sure
+    // a token regenerated within the same second is still distinct
+    let issued = secs(SystemTime::now()).max(secs(old_issued) + 1);
+
+    let token = SplitBotToken::new_issued(&state.config().local.keys.bt_key, bot_id, issued);
+
+    let issued = SystemTime::UNIX_EPOCH + Duration::from_secs(issued);
+
+    // only succeeds if not regenerated concurrently, and also emits a token_refresh event
+    #[rustfmt::skip]
+    let updated = db.execute2(schema::sql! {
+        UPDATE Apps SET (Issued) = (#{&issued as Apps::Issued})
+        WHERE Apps.Id = #{&app_id as Apps::Id}
+          AND Apps.Issued = #{&old_issued as Apps::Issued}
+    }).await?;
+
+    if updated == 0 {
+        return Err(Error::Conflict);
+    }
+
+    _ = state.revoked_users.send(bot_id);
+
+    log::debug!("Regenerated token for bot {bot_id} of app {app_id}");
+
+    Ok(token.format())
+}
@@ -0,0 +1,39 @@
+use sdk::api::commands::all::DeleteApp;
+
+use crate::{internal::user_deletion::soft_delete_user, prelude::*};
+
+/// Deletes an application, soft-deleting its bot user and revoking its token.
+pub async fn remove_app(state: ServerState, auth: Authorization, cmd: &Archived<DeleteApp>) -> Result<(), Error> {
+    let app_id: Snowflake = cmd.app_id.into();
+
+    let mut db = state.db.write.get().await?;
+    let t = db.transaction().await?;
+
+    #[rustfmt::skip]
+    let Some(row) = t.query_opt2(schema::sql! {
+        DELETE FROM Apps
+        WHERE Apps.Id = #{&app_id as Apps::Id}
+          AND Apps.OwnerId = #{auth.user_id_ref() as Apps::OwnerId}
+        RETURNING Apps.BotId AS @BotId
+    }).await? else {
+        t.rollback().await?;
+
+        return Err(Error::NotFound);
+    };
+
+    let bot_id: Option<UserId> = row.bot_id()?;
+
+    if let Some(bot_id) = bot_id {
+        soft_delete_user(&t, bot_id).await?;
+    }
+
+    t.commit().await?;
+
+    if let Some(bot_id) = bot_id {
+        _ = state.revoked_users.send(bot_id);
+    }
+
+    log::debug!("App {app_id} deleted by {}", auth.user_id());
+
+    Ok(())
+}
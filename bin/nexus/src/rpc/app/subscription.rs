@@ -0,0 +1,135 @@
+use sdk::{
+    api::commands::all::{DeleteAppSubscription, GetAppSubscription, SetAppSubscription},
+    models::*,
+};
+
+use crate::prelude::*;
+
+/// Maximum length of a subscription's endpoint URL
+const MAX_URL_LENGTH: usize = 2048;
+
+/// Encodes a stored subscription secret as it is given to the app owner
+fn encode_secret(secret: &[u8]) -> Result<FixedStr<22>, Error> {
+    match <[u8; 16]>::try_from(secret) {
+        Ok(secret) => Ok(util::base64::encode_u128(u128::from_le_bytes(secret))),
+        Err(_) => Err(Error::InternalErrorStatic("Invalid Subscription Secret")),
+    }
+}
+
+/// Sets the HTTPS endpoint and intents the app's bot receives events for.
+///
+/// A secret for signing deliveries is generated for new subscriptions, or when `reset_secret` is given.
+pub async fn set_subscription(
+    state: ServerState,
+    auth: Authorization,
+    cmd: &Archived<SetAppSubscription>,
+) -> Result<AppSubscription, Error> {
+    let app_id: Snowflake = cmd.app_id.into();
+    let form = &cmd.body;
+
+    if form.url.len() > MAX_URL_LENGTH {
+        return Err(Error::BadRequest);
+    }
+
+    // deliveries include message content, so never send them in plaintext or to internal hosts
+    if let Err(e) = crate::services::event_delivery::check_url(&form.url).await {
+        log::debug!("Rejected subscription URL for app {app_id}: {e}");
+
+        return Err(Error::BadRequest);
+    }
+
+    let intents = Intent::from_bits_truncate(form.intents.bits());
+
+    if intents.is_empty() {
+        return Err(Error::BadRequest);
+    }
+
+    let secret = util::rng::crypto_thread_rng().gen_bytes::<16>();
+    let secret = &secret[..];
+    let url = &*form.url;
+    let bits = intents.bits() as i32;
+
+    let db = state.db.write.get().await?;
+
+    #[rustfmt::skip]
+    let Some(row) = db.query_opt2(schema::sql! {
+        INSERT INTO AppSubscriptions (AppId, Intents, Url, Secret) (
+            SELECT
+                Apps.Id,
+                #{&bits   as AppSubscriptions::Intents},
+                #{&url    as AppSubscriptions::Url},
+                #{&secret as AppSubscriptions::Secret}
+            FROM Apps
+            WHERE Apps.Id = #{&app_id as Apps::Id}
+              AND Apps.OwnerId = #{auth.user_id_ref() as Apps::OwnerId}
+              AND Apps.BotId IS NOT NULL
+        )
+        ON CONFLICT (AppSubscriptions./AppId) DO UPDATE SET
+            if form.reset_secret { AppSubscriptions./Secret = #{&secret as AppSubscriptions::Secret}, }
+            AppSubscriptions./Intents = #{&bits as AppSubscriptions::Intents},
+            AppSubscriptions./Url     = #{&url as AppSubscriptions::Url}
+        RETURNING AppSubscriptions.Secret AS @Secret
+    }).await? else {
+        return Err(Error::NotFound);
+    };
+
+    log::debug!("Set event subscription for app {app_id}");
+
+    Ok(AppSubscription {
+        app_id,
+        url: SmolStr::from(url),
+        intents,
+        secret: encode_secret(row.secret()?)?,
+    })
+}
+
+pub async fn get_subscription(
+    state: ServerState,
+    auth: Authorization,
+    cmd: &Archived<GetAppSubscription>,
+) -> Result<AppSubscription, Error> {
+    let app_id: Snowflake = cmd.app_id.into();
+
+    #[rustfmt::skip]
+    let Some(row) = state.db.read.get().await?.query_opt2(schema::sql! {
+        SELECT
+            AppSubscriptions.Intents AS @_,
+            AppSubscriptions.Url     AS @_,
+            AppSubscriptions.Secret  AS @_
+        FROM AppSubscriptions INNER JOIN Apps ON Apps.Id = AppSubscriptions.AppId
+        WHERE AppSubscriptions.AppId = #{&app_id as Apps::Id}
+          AND Apps.OwnerId = #{auth.user_id_ref() as Apps::OwnerId}
+    }).await? else {
+        return Err(Error::NotFound);
+    };
+
+    Ok(AppSubscription {
+        app_id,
+        url: row.app_subscriptions_url()?,
+        intents: Intent::from_bits_truncate(row.app_subscriptions_intents::<i32>()? as _),
+        secret: encode_secret(row.app_subscriptions_secret()?)?,
+    })
+}
+
+/// Stops HTTP event delivery for the app. Any dead-letter entries are kept until they expire.
+pub async fn remove_subscription(
+    state: ServerState,
+    auth: Authorization,
+    cmd: &Archived<DeleteAppSubscription>,
+) -> Result<(), Error> {
+    let app_id: Snowflake = cmd.app_id.into();
+
+    #[rustfmt::skip]
+    let removed = state.db.write.get().await?.execute2(schema::sql! {
+        DELETE FROM AppSubscriptions USING Apps
+        WHERE AppSubscriptions.AppId = #{&app_id as Apps::Id}
+          AND Apps.Id = AppSubscriptions.AppId
+          AND Apps.OwnerId = #{auth.user_id_ref() as Apps::OwnerId}
+    }).await?;
+
+    if removed == 0 {
+        return Err(Error::NotFound);
+    }
+
+    Ok(())
+}
@@ -62,7 +62,7 @@ pub async fn do_bot_auth(
     state: ServerState,
     token: &Archived<SplitBotToken>,
 ) -> Result<Option<Authorization>, Error> {
-    use std::time::Duration;
+    use std::time::{Duration, SystemTime};
 
     let db = state.db.read.get().await?;
 
@@ -75,7 +75,8 @@ pub async fn do_bot_auth(
         return Ok(None);
     };
 
-    let issued = row.issued::<i64>()? as u64;
+    let issued: SystemTime = row.issued()?;
+    let issued = issued.duration_since(SystemTime::UNIX_EPOCH).map_or(0, |d| d.as_secs());
 
     // Once the token has passed the HMAC check, we know it's genuine, but
     // it could have still been revoked, so we need to check the issued time
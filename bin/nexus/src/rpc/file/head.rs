@@ -1,6 +1,7 @@
 use std::io::ErrorKind;
 
 use crate::prelude::*;
+use schema::flags::FileFlags;
 use smol_str::SmolStr;
 
 pub struct UploadHead {
@@ -58,7 +59,10 @@ pub async fn head(state: ServerState, auth: Authorization, file_id: FileId) -> R
 
     let (mut head, metadata) = tokio::try_join!(fetch_record, fetch_metadata)?;
 
-    if let Some(meta) = metadata {
+    if FileFlags::from_bits_truncate(head.flags).contains(FileFlags::COMPLETE) {
+        // completed files may have been deduplicated, leaving no copy under their own id
+        head.offset = head.size;
+    } else if let Some(meta) = metadata {
         head.offset = meta.len() as i32;
     } else {
         log::trace!("File HEAD on file that doesn't exist yet");
@@ -207,9 +207,30 @@ pub async fn patch_file(
         }).await?;
 
         file_patch.complete = true;
-    }
 
-    drop(_file_lock);
+        // deduplication needs the file lock itself
+        drop(_file_lock);
+
+        let size = size as i64;
+
+        if state.config().local.storage.write_through {
+            // failed replication is retried in the background, the upload itself is still complete
+            if let Err(e) = crate::internal::files::dedup_file(&state, file_id, nonce, size).await {
+                log::warn!("Error deduplicating or replicating file {file_id}: {e}");
+            }
+        } else {
+            // hashing may take a while for large files, so don't hold up the response
+            let state = state.clone();
+
+            tokio::spawn(async move {
+                if let Err(e) = crate::internal::files::dedup_file(&state, file_id, nonce, size).await {
+                    log::warn!("Error deduplicating file {file_id}: {e}");
+                }
+            });
+        }
+    } else {
+        drop(_file_lock);
+    }
 
     crate::metrics::API_METRICS.load().upload.add(params.content_length);
 
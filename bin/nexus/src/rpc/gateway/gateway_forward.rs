@@ -0,0 +1,53 @@
+use rpc::event::{ArchivedClientCommand, ClientCommand, ClientCommandReply};
+use schema::flags::MemberFlags;
+use sdk::models::gateway::message::ClientMsg;
+
+use crate::prelude::*;
+
+use super::gateway_presence::{clear_presence, set_presence};
+
+/// Handles a client command forwarded by a gateway on behalf of one of its connections,
+/// yielding any replies to be routed back to that connection.
+pub async fn forward_client_command(
+    state: ServerState,
+    user_id: UserId,
+    conn_id: ConnectionId,
+    cmd: &ArchivedClientCommand,
+) -> Result<impl Stream<Item = Result<ClientCommandReply, Error>>, Error> {
+    let cmd: ClientCommand = cmd.deserialize_simple().map_err(|_| Error::RkyvEncodingError)?;
+
+    let reply = match cmd {
+        ClientCommand::Regular(ClientMsg::SetPresence(payload)) => {
+            set_presence(state, user_id, conn_id, payload.inner.presence).await?;
+
+            None
+        }
+        ClientCommand::Regular(ClientMsg::Subscribe(payload)) => {
+            let party_id = payload.party_id;
+
+            #[rustfmt::skip]
+            let member = state.db.read.get().await?.query_opt2(schema::sql! {
+                SELECT PartyMembers.PartyId AS @PartyId
+                FROM PartyMembers
+                WHERE PartyMembers.PartyId = #{&party_id as PartyMembers::PartyId}
+                  AND PartyMembers.UserId  = #{&user_id  as PartyMembers::UserId}
+                  AND PartyMembers.Flags & const {MemberFlags::BANNED.bits()} = 0
+            }).await?;
+
+            if member.is_none() {
+                return Err(Error::NotFound);
+            }
+
+            Some(ClientCommandReply::Subscribed(party_id))
+        }
+        ClientCommand::Disconnected => {
+            clear_presence(state, user_id, conn_id).await?;
+
+            None
+        }
+        // everything else is handled by the gateway itself
+        ClientCommand::Regular(_) => return Err(Error::BadRequest),
+    };
+
+    Ok(futures::stream::iter(reply.map(Ok)))
+}
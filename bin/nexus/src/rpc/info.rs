@@ -1,6 +1,12 @@
-use rpc::request::PartyInfo;
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::SystemTime,
+};
 
-use crate::prelude::*;
+use rpc::request::{FactionInfo, PartyInfo};
+use uuid::Uuid;
+
+use crate::{internal::faction::FACTION_TIMEOUT, prelude::*};
 
 #[derive(Debug, Clone, Copy)]
 pub enum InfoRequest {
@@ -11,7 +17,7 @@ pub enum InfoRequest {
 pub async fn get_party_info(state: ServerState, req: InfoRequest) -> Result<PartyInfo, Error> {
     let db = state.db.read.get().await?;
 
-    match &req {
+    let (party_id, room_ids) = match &req {
         // easy case where we just need to get the room_ids from the party_id
         InfoRequest::GetPartyInfoFromPartyId(party_id) => {
             #[rustfmt::skip]
@@ -20,10 +26,7 @@ pub async fn get_party_info(state: ServerState, req: InfoRequest) -> Result<Part
                 FROM Rooms WHERE Rooms.PartyId = #{party_id as Rooms::PartyId}
             }).await?;
 
-            Ok(PartyInfo {
-                party_id: *party_id,
-                room_ids: res.room_ids()?,
-            })
+            (*party_id, res.room_ids()?)
         }
 
         // get the party_id from the room_id and then get the room_ids from the party_id
@@ -44,10 +47,35 @@ pub async fn get_party_info(state: ServerState, req: InfoRequest) -> Result<Part
                 GROUP BY Rooms.PartyId
             }).await?;
 
-            Ok(PartyInfo {
-                party_id: res.party_id()?,
-                room_ids: res.room_ids()?,
-            })
+            (res.party_id()?, res.room_ids()?)
         }
-    }
+    };
+
+    let cutoff = SystemTime::now() - FACTION_TIMEOUT;
+
+    // parties on factions that have stopped sending heartbeats fall back to the nexus
+    #[rustfmt::skip]
+    let faction = db.query_opt2(schema::sql! {
+        SELECT
+            Factions.Id     AS @Id,
+            Factions.Addr   AS @Addr,
+            Factions.Port   AS @Port
+        FROM FactionParties INNER JOIN Factions ON Factions.Id = FactionParties.FactionId
+        WHERE FactionParties.PartyId = #{&party_id as FactionParties::PartyId}
+          AND Factions.LastSeen > #{&cutoff as Factions::LastSeen}
+    }).await?;
+
+    let faction = match faction {
+        None => None,
+        Some(row) => Some(FactionInfo {
+            id: row.id::<Uuid>()?.as_u128(),
+            addr: SocketAddr::new(row.addr::<IpAddr>()?, row.port::<i32>()? as u16),
+        }),
+    };
+
+    Ok(PartyInfo {
+        party_id,
+        room_ids,
+        faction,
+    })
 }
@@ -0,0 +1,76 @@
+use std::{
+    net::IpAddr,
+    time::{Duration, SystemTime},
+};
+
+use rpc::request::IpBanList;
+
+use crate::prelude::*;
+
+/// How often the ban list is re-sent to gateways even if nothing has changed
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 5);
+
+/// Fetches all active IP bans, alongside the time at which the next one expires, if any.
+pub async fn get_ip_bans(state: &ServerState) -> Result<(IpBanList, Option<SystemTime>), Error> {
+    #[rustfmt::skip]
+    let rows = state.db.read.get().await?.query2(schema::sql! {
+        SELECT
+            AggIpBans.Expires AS @Expires,
+            AggIpBans.Address AS @Address,
+            AggIpBans.Network AS @Network,
+            AggIpBans.Prefix AS @Prefix
+        FROM AggIpBans
+    }).await?;
+
+    let mut list = IpBanList::default();
+    let mut next_expiry: Option<SystemTime> = None;
+
+    for row in rows {
+        if let Some(expires) = row.expires::<Option<SystemTime>>()? {
+            next_expiry = Some(next_expiry.map_or(expires, |next| next.min(expires)));
+        }
+
+        if let Some(addr) = row.address::<Option<IpAddr>>()? {
+            list.addresses.push(addr);
+        }
+
+        if let (Some(network), Some(prefix)) = (row.network::<Option<IpAddr>>()?, row.prefix::<Option<i16>>()?) {
+            list.networks.push((network, prefix as u8));
+        }
+    }
+
+    Ok((list, next_expiry))
+}
+
+/// Yields the current IP ban list immediately, then again whenever the bans change,
+/// one expires, or [`REFRESH_INTERVAL`] elapses.
+pub async fn watch_ip_bans(state: ServerState) -> Result<impl Stream<Item = Result<IpBanList, Error>>, Error> {
+    let changed = state.ip_bans_changed.subscribe();
+
+    Ok(futures::stream::unfold(
+        (state, changed, None::<SystemTime>, true),
+        |(state, mut changed, next_expiry, first)| async move {
+            if !first {
+                let mut wait = REFRESH_INTERVAL;
+
+                if let Some(next_expiry) = next_expiry {
+                    let until = next_expiry.duration_since(SystemTime::now()).unwrap_or_default();
+                    wait = wait.min(until + Duration::from_secs(1));
+                }
+
+                tokio::select! {
+                    res = changed.changed() => if res.is_err() { return None },
+                    _ = tokio::time::sleep(wait) => {},
+                }
+            }
+
+            // mark as seen before fetching, so changes made during the query are not missed
+            changed.borrow_and_update();
+
+            match get_ip_bans(&state).await {
+                Ok((list, next_expiry)) => Some((Ok(list), (state, changed, next_expiry, false))),
+                Err(e) => Some((Err(e), (state, changed, None, false))),
+            }
+        },
+    ))
+}
@@ -1,9 +1,13 @@
 #![allow(clippy::redundant_closure)]
 
-//pub mod admin;
+pub mod admin;
 pub mod auth;
 pub mod info;
+pub mod ip_bans;
+pub mod party_migrations;
 pub mod perm;
+pub mod rate_limits;
+pub mod revocations;
 
 #[derive(Debug, Clone, Copy)]
 pub enum SearchMode<'a> {
@@ -11,6 +15,11 @@ pub enum SearchMode<'a> {
     Many(&'a [schema::Snowflake]),
 }
 
+pub mod gateway {
+    pub mod gateway_forward;
+    pub mod gateway_presence;
+}
+
 pub mod user {
     pub mod user_get_user;
     pub mod user_login;
@@ -19,6 +28,8 @@ pub mod user {
     pub mod me {
         pub mod user_account;
         pub mod user_change_password;
+        pub mod user_data_export;
+        pub mod user_delete_account;
 
         pub mod user_get_self;
         pub mod user_logout;
@@ -34,16 +45,51 @@ pub mod user {
     }
 }
 
+pub mod app {
+    pub mod commands {
+        pub mod create_command;
+        pub mod get_commands;
+        pub mod remove_command;
+    }
+
+    pub mod create_app;
+    pub mod get_apps;
+    pub mod modify_app;
+    pub mod regenerate_token;
+    pub mod remove_app;
+    pub mod subscription;
+}
+
 pub mod party {
+    pub mod audit {
+        pub mod get_audit_log;
+    }
+
+    pub mod bans {
+        pub mod ban_member;
+        pub mod get_bans;
+        pub mod unban_member;
+    }
+
+    pub mod party_add_bot;
+    pub mod party_commands;
     pub mod party_create;
     pub mod party_emotes;
     pub mod party_get;
+    pub mod party_member_kick;
     pub mod party_member_profile;
+    pub mod party_member_timeout;
     pub mod party_members;
     pub mod party_modify;
     pub mod party_remove;
     pub mod party_stats;
 
+    pub mod emotes {
+        pub mod create_emote;
+        pub mod modify_emote;
+        pub mod remove_emote;
+    }
+
     pub mod rooms {
         pub mod create_room;
         pub mod get_rooms;
@@ -61,6 +107,8 @@ pub mod room {
     pub mod get_room;
     pub mod modify_room;
     pub mod remove_room;
+    pub mod respond_interaction;
+    pub mod room_member_timeout;
     pub mod start_typing;
 
     pub mod messages {
@@ -68,6 +116,7 @@ pub mod room {
         pub mod delete_message;
         pub mod edit_message;
         pub mod get_messages;
+        pub mod get_revisions;
 
         pub mod reactions {
             pub mod add_reaction;
@@ -79,6 +128,14 @@ pub mod room {
         pub mod edit;
         pub mod get;
     }
+
+    pub mod webhooks {
+        pub mod create_webhook;
+        pub mod execute_webhook;
+        pub mod get_webhooks;
+        pub mod modify_webhook;
+        pub mod remove_webhook;
+    }
 }
 
 pub mod invite {
@@ -134,6 +191,12 @@ where
         pin::Pin,
     };
 
+    #[rustfmt::skip]
+    let is_party_request = matches!(cmd, ArchivedRpcRequest::ApiProcedure { proc, .. }
+        if matches!(proc.endpoint(), rpc::client::Resolve::Party(_) | rpc::client::Resolve::Room(_)));
+
+    let fence_state = state.clone();
+
     #[allow(clippy::type_complexity)]
     // using a closure here allows for early returns of the future for auth and others
     let gen_dispatch = move || -> Result<Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>, Error> {
@@ -148,6 +211,13 @@ where
                 return c0!(future::ready(Ok(config.shared.clone())));
             }
 
+            ArchivedRpcRequest::WatchSharedConfig => {
+                let configs =
+                    state.clone().config_full_stream().map(|config| Ok::<_, Error>(config.shared.clone()));
+
+                return s0!(future::ready(Ok::<_, Error>(configs)));
+            }
+
             // just returns a `Result<(), Error>`, as the action is handled above dispatch
             // by `RpcConnection::handle_rpc`
             ArchivedRpcRequest::OpenGateway => return c0!(future::ready(Ok(()))),
@@ -174,7 +244,68 @@ where
                 return c0!(info::get_party_info(state, req));
             }
 
-            ArchivedRpcRequest::ForwardedClientCommand(_) => todo!(),
+            ArchivedRpcRequest::GetIpBans | ArchivedRpcRequest::WatchIpBans => {
+                if !is_nexus {
+                    // IP bans are only served from the nexus
+                    return Err(Error::BadRequest);
+                }
+
+                if let ArchivedRpcRequest::GetIpBans = cmd {
+                    return c0!(async move { ip_bans::get_ip_bans(&state).await.map(|(list, _)| list) });
+                }
+
+                return s0!(ip_bans::watch_ip_bans(state));
+            }
+
+            ArchivedRpcRequest::WatchRevokedUsers => {
+                if !is_nexus {
+                    return Err(Error::BadRequest);
+                }
+
+                return s0!(revocations::watch_revoked_users(state));
+            }
+
+            ArchivedRpcRequest::WatchPartyMigrations => {
+                if !is_nexus {
+                    return Err(Error::BadRequest);
+                }
+
+                return s0!(party_migrations::watch_party_migrations(state));
+            }
+
+            ArchivedRpcRequest::ReportRateLimitViolations(violations) => {
+                if !is_nexus {
+                    return Err(Error::BadRequest);
+                }
+
+                return c0!(rate_limits::report_violations(state, violations));
+            }
+
+            ArchivedRpcRequest::ExecuteWebhook { webhook_id, token, body } => {
+                if !is_nexus {
+                    return Err(Error::BadRequest);
+                }
+
+                let (webhook_id, token) = ((*webhook_id).into(), (*token).into());
+
+                return c0!(room::webhooks::execute_webhook::execute_webhook(state, webhook_id, token, body));
+            }
+
+            ArchivedRpcRequest::DownloadDataExport { export_id, token } => {
+                if !is_nexus {
+                    return Err(Error::BadRequest);
+                }
+
+                let (export_id, token) = ((*export_id).into(), (*token).into());
+
+                return s0!(crate::internal::data_export::download_data_export(state, export_id, token));
+            }
+
+            ArchivedRpcRequest::ForwardedClientCommand { user_id, conn_id, cmd } => {
+                let (user_id, conn_id) = ((*user_id).into(), (*conn_id).into());
+
+                return s0!(gateway::gateway_forward::forward_client_command(state, user_id, conn_id, cmd));
+            }
         };
 
         use rpc::{client::Resolve, procedure::ArchivedProcedure as Proc};
@@ -199,7 +330,7 @@ where
         // let _ is used to allow rustfmt::skip
         #[allow(unused_variables, clippy::let_unit_value)] #[rustfmt::skip]
         let _ = match proc {
-            Proc::GetServerConfig(cmd) => todo!("GetServerConfig"),
+            Proc::GetServerConfig(_) => c!(future::ready(Ok(state.config().shared.public()))),
             Proc::UserRegister(cmd) => c!(user::user_register::register_user(state, addr.as_ipaddr(), cmd)),
             Proc::UserLogin(cmd) => c!(user::user_login::login(state, addr.as_ipaddr(), cmd)),
             Proc::UserLogout(_) => c!(user::me::user_logout::logout_user(state, auth()?)),
@@ -214,6 +345,9 @@ where
             Proc::UpdateUserProfile(cmd) => c!(user::me::user_profile::patch_user_profile(state, auth()?, cmd)),
             Proc::GetUser(cmd) => c!(user::user_get_user::get_full_user(state, auth()?, cmd)),
             Proc::UpdateUserPrefs(cmd) => c!(user::me::user_prefs::update_prefs(state, auth()?, cmd)),
+            Proc::RequestDataExport(_) => c!(user::me::user_data_export::request_data_export(state, auth()?)),
+            Proc::DeleteAccount(cmd) => c!(user::me::user_delete_account::delete_account(state, auth()?, cmd)),
+            Proc::CancelAccountDeletion(_) => c!(user::me::user_delete_account::cancel_account_deletion(state, auth()?)),
             Proc::CreateFile(cmd) => todo!("CreateFile"),
             Proc::GetFilesystemStatus(cmd) => todo!("GetFilesystemStatus"),
             Proc::GetFileStatus(cmd) => todo!("GetFileStatus"),
@@ -227,7 +361,7 @@ where
             Proc::TransferOwnership(cmd) => todo!("TransferOwnership"),
             Proc::CreateRole(cmd) => c!(party::roles::create_role::create_role(state, auth()?, cmd)),
             Proc::PatchRole(cmd) => c!(party::roles::modify_role::modify_role(state, auth()?, cmd)),
-            Proc::DeleteRole(cmd) => todo!("DeleteRole"),
+            Proc::DeleteRole(cmd) => c!(party::roles::remove_role::remove_role(state, auth()?, cmd.party_id.into(), cmd.role_id.into())),
             Proc::GetPartyMembers(cmd) => s!(party::party_members::get_many(state, auth()?, cmd)),
             Proc::GetPartyMember(cmd) => c!(party::party_members::get_one(state, auth()?, cmd)),
             Proc::GetPartyRooms(cmd) => s!(party::rooms::get_rooms::get_party_rooms(state, auth()?, cmd)),
@@ -238,6 +372,17 @@ where
             Proc::CreatePinFolder(cmd) => todo!("CreatePinFolder"),
             Proc::CreateRoom(cmd) => c!(party::rooms::create_room::create_room(state, auth()?, cmd)),
             Proc::SearchParty(cmd) => todo!("SearchParty"),
+            Proc::CreateEmote(cmd) => c!(party::emotes::create_emote::create_emote(state, auth()?, cmd)),
+            Proc::PatchEmote(cmd) => c!(party::emotes::modify_emote::modify_emote(state, auth()?, cmd)),
+            Proc::DeleteEmote(cmd) => c!(party::emotes::remove_emote::remove_emote(state, auth()?, cmd)),
+            Proc::BanMember(cmd) => c!(party::bans::ban_member::ban_member(state, auth()?, cmd)),
+            Proc::UnbanMember(cmd) => c!(party::bans::unban_member::unban_member(state, auth()?, cmd)),
+            Proc::GetPartyBans(cmd) => s!(party::bans::get_bans::get_bans(state, auth()?, cmd)),
+            Proc::TimeoutMember(cmd) => c!(party::party_member_timeout::timeout_member(state, auth()?, cmd)),
+            Proc::KickMember(cmd) => c!(party::party_member_kick::kick_member(state, auth()?, cmd)),
+            Proc::GetAuditLog(cmd) => s!(party::audit::get_audit_log::get_audit_log(state, auth()?, cmd)),
+            Proc::AddBotToParty(cmd) => c!(party::party_add_bot::add_bot(state, auth()?, cmd)),
+            Proc::GetPartyCommands(cmd) => s!(party::party_commands::get_party_commands(state, auth()?, cmd)),
             Proc::CreateMessage(cmd) => c!(room::messages::create_message::create_message(state, auth()?, cmd)),
             Proc::EditMessage(cmd) => c!(room::messages::edit_message::edit_message(state, auth()?, cmd)),
             Proc::GetMessage(cmd) => todo!("GetMessage"),
@@ -256,8 +401,43 @@ where
             Proc::PatchRoom(cmd) => c!(room::modify_room::modify_room(state, auth()?, cmd)),
             Proc::DeleteRoom(cmd) => c!(room::remove_room::remove_room(state, auth()?, cmd)),
             Proc::GetRoom(cmd) => c!(room::get_room::get_room(state, auth()?, cmd)),
+            Proc::TimeoutRoomMember(cmd) => c!(room::room_member_timeout::timeout_room_member(state, auth()?, cmd)),
+            Proc::CreateWebhook(cmd) => c!(room::webhooks::create_webhook::create_webhook(state, auth()?, cmd)),
+            Proc::GetWebhooks(cmd) => s!(room::webhooks::get_webhooks::get_webhooks(state, auth()?, cmd)),
+            Proc::PatchWebhook(cmd) => c!(room::webhooks::modify_webhook::modify_webhook(state, auth()?, cmd)),
+            Proc::DeleteWebhook(cmd) => c!(room::webhooks::remove_webhook::remove_webhook(state, auth()?, cmd)),
+            Proc::RespondToInteraction(cmd) => c!(room::respond_interaction::respond_interaction(state, auth()?, cmd)),
+            Proc::GetMessageRevisions(cmd) => s!(room::messages::get_revisions::get_revisions(state, auth()?, cmd)),
+
+            Proc::CreateIpBan(cmd) => c!(admin::ip_bans::create_ip_ban::create_ip_ban(state, auth()?, cmd)),
+            Proc::DeleteIpBan(cmd) => c!(admin::ip_bans::delete_ip_ban::delete_ip_ban(state, auth()?, cmd)),
+            Proc::GetIpBans(cmd) => s!(admin::ip_bans::get_ip_bans::get_ip_bans(state, auth()?, cmd)),
+            Proc::BanUser(cmd) => c!(admin::user::ban::ban_user(state, auth()?, cmd)),
+            Proc::UnbanUser(cmd) => c!(admin::user::unban::unban_user(state, auth()?, cmd)),
+            Proc::DeleteUser(cmd) => c!(admin::user::delete::delete_user(state, auth()?, cmd)),
+            Proc::LogoutUser(cmd) => c!(admin::user::logout::logout_user(state, auth()?, cmd)),
+            Proc::LookupUser(cmd) => c!(admin::user::lookup::lookup_user(state, auth()?, cmd)),
+            Proc::MigrateParty(cmd) => c!(admin::party::migrate::migrate_party(state, auth()?, cmd)),
+
+            Proc::CreateApp(cmd) => c!(app::create_app::create_app(state, auth()?, cmd)),
+            Proc::GetApps(_) => s!(app::get_apps::get_apps(state, auth()?)),
+            Proc::PatchApp(cmd) => c!(app::modify_app::modify_app(state, auth()?, cmd)),
+            Proc::DeleteApp(cmd) => c!(app::remove_app::remove_app(state, auth()?, cmd)),
+            Proc::RegenerateBotToken(cmd) => c!(app::regenerate_token::regenerate_token(state, auth()?, cmd)),
+            Proc::SetAppSubscription(cmd) => c!(app::subscription::set_subscription(state, auth()?, cmd)),
+            Proc::GetAppSubscription(cmd) => c!(app::subscription::get_subscription(state, auth()?, cmd)),
+            Proc::DeleteAppSubscription(cmd) => c!(app::subscription::remove_subscription(state, auth()?, cmd)),
+            Proc::CreateAppCommand(cmd) => c!(app::commands::create_command::create_command(state, auth()?, cmd)),
+            Proc::GetAppCommands(_) => s!(app::commands::get_commands::get_commands(state, auth()?)),
+            Proc::DeleteAppCommand(cmd) => c!(app::commands::remove_command::remove_command(state, auth()?, cmd)),
         };
     };
 
+    // party requests in progress hold off migration fences until they finish, see `internal::faction`
+    let _fence = match is_party_request {
+        true => Some(fence_state.party_fence.read().await),
+        false => None,
+    };
+
     gen_dispatch()?.await
 }
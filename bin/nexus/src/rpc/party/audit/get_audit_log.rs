@@ -0,0 +1,64 @@
+use sdk::{api::commands::party::GetAuditLog, models::*};
+
+use crate::prelude::*;
+
+pub async fn get_audit_log(
+    state: ServerState,
+    auth: Authorization,
+    cmd: &Archived<GetAuditLog>,
+) -> Result<impl Stream<Item = Result<AuditLogEntry, Error>>, Error> {
+    let party_id: PartyId = cmd.party_id.into();
+    let form = &cmd.body;
+
+    // limit the limit
+    let limit = match form.limit.as_ref() {
+        Some(&limit) if limit < 100 => limit as i16,
+        _ => 100,
+    };
+
+    let before: Option<Snowflake> = form.before.as_ref().map(|&id| id.into());
+    let after: Option<Snowflake> = form.after.as_ref().map(|&id| id.into());
+    let user_id: Option<UserId> = form.user_id.as_ref().map(|&id| id.into());
+    let target_id: Option<Snowflake> = form.target_id.as_ref().map(|&id| id.into());
+    let action: Option<i16> = form.action.as_ref().map(|action| action.to_native() as i16);
+
+    #[rustfmt::skip]
+    let stream = state.db.read.get().await?.query_stream2(schema::sql! {
+        SELECT
+            AuditLog.Id         AS @_,
+            AuditLog.UserId     AS @_,
+            AuditLog.TargetId   AS @_,
+            AuditLog.Action     AS @_,
+            AuditLog.Reason     AS @_,
+            AuditLog.Changes::text AS @Changes
+        FROM AuditLog INNER JOIN PartyMembers ON PartyMembers.PartyId = AuditLog.PartyId
+        WHERE AuditLog.PartyId = #{&party_id as Party::Id}
+          AND PartyMembers.UserId = #{auth.user_id_ref() as Users::Id}
+
+        const PERMS: [i64; 2] = Permissions::VIEW_AUDIT_LOG.to_i64();
+        const_assert!(PERMS[1] == 0);
+
+        AND PartyMembers.Permissions1 & const {PERMS[0]} = const {PERMS[0]}
+
+        if before.is_some()    { AND AuditLog.Id < #{&before as AuditLog::Id} }
+        if after.is_some()     { AND AuditLog.Id > #{&after as AuditLog::Id} }
+        if user_id.is_some()   { AND AuditLog.UserId = #{&user_id as AuditLog::UserId} }
+        if target_id.is_some() { AND AuditLog.TargetId = #{&target_id as AuditLog::TargetId} }
+        if action.is_some()    { AND AuditLog.Action = #{&action as AuditLog::Action} }
+
+        ORDER BY AuditLog.Id DESC
+        LIMIT #{&limit as Type::INT2}
+    }).await?;
+
+    Ok(stream.map(|row| match row {
+        Err(e) => Err(Error::from(e)),
+        Ok(row) => Ok(AuditLogEntry {
+            id: row.audit_log_id()?,
+            user_id: row.audit_log_user_id()?,
+            target_id: row.audit_log_target_id()?,
+            action: row.audit_log_action::<i16>()? as u16,
+            reason: row.audit_log_reason()?,
+            changes: row.changes()?,
+        }),
+    }))
+}
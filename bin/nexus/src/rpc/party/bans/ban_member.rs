@@ -0,0 +1,94 @@
+use std::time::{Duration, SystemTime};
+
+use schema::{audit::AuditAction, flags::MemberFlags, roles::UserAction, SnowflakeExt};
+use sdk::{api::commands::party::BanMember, models::*};
+
+use crate::{
+    internal::audit::{AuditChanges, AuditEntry, MAX_REASON_LENGTH},
+    prelude::*,
+};
+
+/// Maximum age of messages that can be removed alongside a ban, 7 days
+const MAX_DELETE_MESSAGES: Duration = Duration::from_secs(60 * 60 * 24 * 7);
+
+pub async fn ban_member(state: ServerState, auth: Authorization, cmd: &Archived<BanMember>) -> Result<(), Error> {
+    let party_id: PartyId = cmd.party_id.into();
+    let member_id: UserId = cmd.member_id.into();
+    let form = &cmd.body;
+
+    if matches!(form.reason.as_deref(), Some(reason) if reason.len() > MAX_REASON_LENGTH) {
+        return Err(Error::BadRequest);
+    }
+
+    let now = SystemTime::now();
+
+    let expires = form.duration.as_ref().map(|secs| now + Duration::from_secs(secs.to_native() as u64));
+
+    // lower bound for messages to be removed, as a snowflake
+    let delete_after = form.delete_messages.as_ref().map(|secs| {
+        Snowflake::timestamp_only(now - Duration::from_secs(secs.to_native() as u64).min(MAX_DELETE_MESSAGES))
+    });
+
+    let mut db = state.db.write.get().await?;
+    let t = db.transaction().await?;
+
+    crate::internal::moderation::check_member_action(&t, party_id, auth.user_id(), member_id, UserAction::Ban)
+        .await?;
+
+    #[rustfmt::skip]
+    t.execute2(schema::sql! {
+        INSERT INTO PartyBans (PartyId, UserId, Expires, Reason) VALUES (
+            #{&party_id     as PartyBans::PartyId},
+            #{&member_id    as PartyBans::UserId},
+            #{&expires      as PartyBans::Expires},
+            #{&form.reason  as PartyBans::Reason}
+        )
+        ON CONFLICT (PartyBans./PartyId, PartyBans./UserId) DO UPDATE PartyBans SET (BannedAt, Expires, Reason) = (
+            now(),
+            #{&expires      as PartyBans::Expires},
+            #{&form.reason  as PartyBans::Reason}
+        )
+    }).await?;
+
+    // flag the member as banned, which emits the member_ban event. If they weren't a member, that's fine.
+    #[rustfmt::skip]
+    t.execute2(schema::sql! {
+        UPDATE PartyMembers SET (Flags) = (PartyMembers.Flags | const {MemberFlags::BANNED.bits()})
+        WHERE PartyMembers.PartyId = #{&party_id as Party::Id}
+          AND PartyMembers.UserId = #{&member_id as Users::Id}
+          AND PartyMembers.Flags & const {MemberFlags::BANNED.bits()} = 0
+    }).await?;
+
+    if let Some(ref delete_after) = delete_after {
+        #[rustfmt::skip]
+        t.execute2(schema::sql! {
+            UPDATE Messages SET (Flags) = (Messages.Flags | const {(MessageFlags::DELETED.union(MessageFlags::REMOVED)).bits()})
+            FROM Rooms
+            WHERE Rooms.PartyId = #{&party_id as Party::Id}
+              AND Messages.RoomId = Rooms.Id
+              AND Messages.UserId = #{&member_id as Users::Id}
+              AND Messages.Id > #{delete_after as Messages::Id}
+              AND Messages.Flags & const {MessageFlags::DELETED.bits()} = 0 // prevent double updates
+        }).await?;
+    }
+
+    let mut changes = AuditChanges::default();
+    if let Some(secs) = form.duration.as_ref() {
+        changes.set("duration", None, secs.to_native());
+    }
+
+    AuditEntry {
+        party_id,
+        user_id: auth.user_id(),
+        target_id: Some(member_id),
+        action: AuditAction::MemberBan,
+        reason: form.reason.as_deref(),
+        changes,
+    }
+    .record(&state, &t)
+    .await?;
+
+    t.commit().await?;
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use sdk::{api::commands::party::GetPartyBans, models::*};
+
+use crate::prelude::*;
+
+pub async fn get_bans(
+    state: ServerState,
+    auth: Authorization,
+    cmd: &Archived<GetPartyBans>,
+) -> Result<impl Stream<Item = Result<PartyBan, Error>>, Error> {
+    let party_id: PartyId = cmd.party_id.into();
+
+    #[rustfmt::skip]
+    let stream = state.db.read.get().await?.query_stream2(schema::sql! {
+        SELECT
+            PartyBans.UserId    AS @_,
+            PartyBans.BannedAt  AS @_,
+            PartyBans.Expires   AS @_,
+            PartyBans.Reason    AS @_
+        FROM PartyBans INNER JOIN PartyMembers ON PartyMembers.PartyId = PartyBans.PartyId
+        WHERE PartyBans.PartyId = #{&party_id as Party::Id}
+          AND PartyMembers.UserId = #{auth.user_id_ref() as Users::Id}
+
+        const PERMS: [i64; 2] = Permissions::BAN_MEMBERS.to_i64();
+        const_assert!(PERMS[1] == 0);
+
+        AND PartyMembers.Permissions1 & const {PERMS[0]} = const {PERMS[0]}
+        ORDER BY PartyBans.BannedAt DESC
+    }).await?;
+
+    Ok(stream.map(|row| match row {
+        Err(e) => Err(Error::from(e)),
+        Ok(row) => Ok(PartyBan {
+            user_id: row.party_bans_user_id()?,
+            banned_at: row.party_bans_banned_at()?,
+            expires: row.party_bans_expires()?,
+            reason: row.party_bans_reason()?,
+        }),
+    }))
+}
@@ -0,0 +1,56 @@
+use schema::{audit::AuditAction, flags::MemberFlags};
+use sdk::{api::commands::party::UnbanMember, models::*};
+
+use crate::{internal::audit::AuditEntry, prelude::*};
+
+pub async fn unban_member(state: ServerState, auth: Authorization, cmd: &Archived<UnbanMember>) -> Result<(), Error> {
+    let party_id: PartyId = cmd.party_id.into();
+    let member_id: UserId = cmd.member_id.into();
+
+    let mut db = state.db.write.get().await?;
+    let t = db.transaction().await?;
+
+    #[rustfmt::skip]
+    let res = t.execute2(schema::sql! {
+        DELETE FROM PartyBans USING PartyMembers
+        WHERE PartyBans.PartyId = #{&party_id as Party::Id}
+          AND PartyBans.UserId = #{&member_id as Users::Id}
+          AND PartyMembers.PartyId = PartyBans.PartyId
+          AND PartyMembers.UserId = #{auth.user_id_ref() as Users::Id}
+
+        const PERMS: [i64; 2] = Permissions::BAN_MEMBERS.to_i64();
+        const_assert!(PERMS[1] == 0);
+
+        AND PartyMembers.Permissions1 & const {PERMS[0]} = const {PERMS[0]}
+    }).await?;
+
+    if res == 0 {
+        t.rollback().await?;
+
+        return Err(Error::NotFound);
+    }
+
+    // Deleting the banned member row lifts the ban and emits member_unban, see the ban lifecycle in seed.sql
+    #[rustfmt::skip]
+    t.execute2(schema::sql! {
+        DELETE FROM PartyMembers
+        WHERE PartyMembers.PartyId = #{&party_id as Party::Id}
+          AND PartyMembers.UserId = #{&member_id as Users::Id}
+          AND PartyMembers.Flags & const {MemberFlags::BANNED.bits()} != 0
+    }).await?;
+
+    AuditEntry {
+        party_id,
+        user_id: auth.user_id(),
+        target_id: Some(member_id),
+        action: AuditAction::MemberUnban,
+        reason: None,
+        changes: Default::default(),
+    }
+    .record(&state, &t)
+    .await?;
+
+    t.commit().await?;
+
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use schema::audit::AuditAction;
+use sdk::{api::commands::party::CreateEmote, models::*};
+
+use crate::{
+    asset::{maybe_add_asset, AssetMode},
+    internal::audit::{AuditChanges, AuditEntry},
+    prelude::*,
+};
+
+/// Maximum length of emote alt text, in bytes
+pub const MAX_ALT_LENGTH: usize = 256;
+
+pub async fn create_emote(
+    state: ServerState,
+    auth: Authorization,
+    cmd: &Archived<CreateEmote>,
+) -> Result<CustomEmote, Error> {
+    let party_id: PartyId = cmd.party_id.into();
+    let form = &cmd.body;
+
+    let config = state.config_full();
+
+    if !schema::validation::validate_name(&form.name, config.shared.emote_name_length.clone()) {
+        return Err(Error::InvalidName);
+    }
+
+    if matches!(form.alt.as_deref(), Some(alt) if alt.len() > MAX_ALT_LENGTH) {
+        return Err(Error::BadRequest);
+    }
+
+    // check permissions AND check for the emote limit at the same time.
+    #[rustfmt::skip]
+    let Some(row) = state.db.read.get().await?.query_opt2(schema::sql! {
+        const_assert!(!Columns::IS_DYNAMIC);
+
+        SELECT
+            (SELECT COUNT(Emotes.Id)::int4 FROM Emotes WHERE Emotes.PartyId = PartyMembers.PartyId) AS @TotalEmotes
+        FROM PartyMembers
+        WHERE PartyMembers.PartyId = #{&party_id as Party::Id}
+          AND PartyMembers.UserId = #{auth.user_id_ref() as Users::Id}
+
+        const PERMS: [i64; 2] = Permissions::MANAGE_EXPRESSIONS.to_i64();
+        const_assert!(PERMS[1] == 0);
+
+        AND PartyMembers.Permissions1 & const {PERMS[0]} = const {PERMS[0]}
+    }).await? else {
+        return Err(Error::Unauthorized);
+    };
+
+    let max_emotes = config.shared.max_emotes as i32;
+
+    if row.total_emotes::<i32>()? >= max_emotes {
+        return Err(Error::BadRequest);
+    }
+
+    drop(config);
+
+    // process the uploaded file into an emote asset
+    let Nullable::Some(asset_id) =
+        maybe_add_asset(&state, AssetMode::Emote, auth.user_id(), Nullable::Some(form.file.into())).await?
+    else {
+        return Err(Error::InvalidImageFormat);
+    };
+
+    let emote_id = state.sf.gen();
+
+    // the animated flag is determined by the asset, so never take it from the client
+    let flags = form.flags.difference(EmoteFlags::ANIMATED);
+
+    let mut db = state.db.write.get().await?;
+    let t = db.transaction().await?;
+
+    #[rustfmt::skip]
+    let Some(row) = t.query_opt2(schema::sql! {
+        const_assert!(!Columns::IS_DYNAMIC);
+
+        struct Asset {
+            Id: UserAssets::Id,
+            AspectRatio: Emotes::AspectRatio,
+            Animated: Type::BOOL,
+        }
+
+        // the aspect ratio is taken from the processed versions, as they may have been cropped
+        WITH Asset AS (
+            SELECT
+                UserAssets.Id AS Asset.Id,
+                (Files.Width::float4 / GREATEST(Files.Height, 1)::float4) AS Asset.AspectRatio,
+                EXISTS(
+                    SELECT FROM UserAssetFiles
+                    WHERE UserAssetFiles.AssetId = UserAssets.Id
+                      AND UserAssetFiles.Flags & const {AssetFlags::ANIMATED.bits()} != 0
+                ) AS Asset.Animated
+            FROM UserAssets
+                INNER JOIN UserAssetFiles ON UserAssetFiles.AssetId = UserAssets.Id
+                INNER JOIN Files ON Files.Id = UserAssetFiles.FileId
+            WHERE UserAssets.Id = #{&asset_id as UserAssets::Id}
+              AND UserAssetFiles.Flags & const {AssetFlags::ANIMATED.bits()} = 0
+              AND Files.Width IS NOT NULL AND Files.Height IS NOT NULL
+            LIMIT 1
+        )
+        INSERT INTO Emotes (Id, PartyId, AssetId, AspectRatio, Flags, Name, Alt) (
+            SELECT
+                #{&emote_id as Emotes::Id},
+                #{&party_id as Emotes::PartyId},
+                Asset.Id,
+                Asset.AspectRatio,
+                #{&flags as Emotes::Flags} | CASE WHEN Asset.Animated
+                    THEN const {EmoteFlags::ANIMATED.bits()} ELSE 0 END,
+                #{&form.name as Emotes::Name},
+                #{&form.alt as Emotes::Alt}
+            FROM Asset
+            // re-check the limit within the transaction to avoid racing other inserts
+            WHERE (SELECT COUNT(Emotes.Id)::int4 FROM Emotes
+                WHERE Emotes.PartyId = #{&party_id as Party::Id}) < #{&max_emotes as Type::INT4}
+        )
+        RETURNING
+            Emotes.AspectRatio AS @AspectRatio,
+            Emotes.Flags AS @Flags
+    }).await? else {
+        t.rollback().await?;
+
+        // either the asset was invalid or the limit was reached
+        return Err(Error::BadRequest);
+    };
+
+    let mut changes = AuditChanges::default();
+    changes.set("name", None, &*form.name);
+
+    AuditEntry {
+        party_id,
+        user_id: auth.user_id(),
+        target_id: Some(emote_id),
+        action: AuditAction::EmoteCreate,
+        reason: None,
+        changes,
+    }
+    .record(&state, &t)
+    .await?;
+
+    t.commit().await?;
+
+    Ok(CustomEmote {
+        id: emote_id,
+        party_id,
+        asset: asset_id,
+        name: SmolStr::from(&*form.name),
+        alt: form.alt.as_deref().map(SmolStr::from),
+        flags: row.flags()?,
+        aspect_ratio: row.aspect_ratio()?,
+    })
+}
@@ -0,0 +1,104 @@
+use schema::audit::AuditAction;
+use sdk::api::commands::party::{PatchEmote, PatchEmoteForm};
+use sdk::models::*;
+
+use crate::{
+    internal::audit::{AuditChanges, AuditEntry},
+    prelude::*,
+};
+
+pub async fn modify_emote(
+    state: ServerState,
+    auth: Authorization,
+    cmd: &Archived<PatchEmote>,
+) -> Result<CustomEmote, Error> {
+    let party_id: PartyId = cmd.party_id.into();
+    let emote_id: EmoteId = cmd.emote_id.into();
+    let form = &cmd.body;
+
+    if *form == PatchEmoteForm::default() {
+        return Err(Error::BadRequest);
+    }
+
+    if matches!(form.name.as_deref(), Some(name) if !schema::validation::validate_name(name, state.config().shared.emote_name_length.clone()))
+    {
+        return Err(Error::InvalidName);
+    }
+
+    if matches!(form.alt, Nullable::Some(ref alt) if alt.len() > super::create_emote::MAX_ALT_LENGTH) {
+        return Err(Error::BadRequest);
+    }
+
+    // the animated flag is determined by the asset, so never allow it to be changed directly
+    let flags = form.flags.map(|flags| flags.difference(EmoteFlags::ANIMATED));
+
+    let mut db = state.db.write.get().await?;
+    let t = db.transaction().await?;
+
+    #[rustfmt::skip]
+    let Some(row) = t.query_opt2(schema::sql! {
+        type OldEmotes = Emotes;
+
+        UPDATE Emotes SET
+            if form.name.is_some()      { Emotes./Name  = #{&form.name as Emotes::Name}, }
+            if !form.alt.is_undefined() { Emotes./Alt   = #{&form.alt as Emotes::Alt}, }
+
+            Emotes./Flags = COALESCE(
+                #{&flags as Emotes::Flags} | (Emotes.Flags & const {EmoteFlags::ANIMATED.bits()}),
+                Emotes./Flags
+            )
+        FROM PartyMembers INNER JOIN Emotes AS OldEmotes ON OldEmotes.PartyId = PartyMembers.PartyId
+        WHERE Emotes.Id = #{&emote_id as Emotes::Id}
+          AND Emotes.PartyId = #{&party_id as Party::Id}
+          AND OldEmotes.Id = Emotes.Id
+          AND PartyMembers.PartyId = Emotes.PartyId
+          AND PartyMembers.UserId = #{auth.user_id_ref() as Users::Id}
+
+        const PERMS: [i64; 2] = Permissions::MANAGE_EXPRESSIONS.to_i64();
+        const_assert!(PERMS[1] == 0);
+
+        AND PartyMembers.Permissions1 & const {PERMS[0]} = const {PERMS[0]}
+        RETURNING
+            Emotes.AssetId      AS @_,
+            Emotes.Name         AS @_,
+            Emotes.Alt          AS @_,
+            Emotes.Flags        AS @_,
+            Emotes.AspectRatio  AS @_,
+            OldEmotes.Name      AS @OldName,
+            OldEmotes.Alt       AS @OldAlt,
+            OldEmotes.Flags     AS @OldFlags
+    }).await? else {
+        t.rollback().await?;
+
+        return Err(Error::NotFound);
+    };
+
+    let mut changes = AuditChanges::default();
+    changes
+        .diff("name", row.old_name::<SmolStr>()?, row.emotes_name()?)
+        .diff("alt", row.old_alt::<Option<SmolStr>>()?, row.emotes_alt()?)
+        .diff("flags", row.old_flags::<EmoteFlags>()?, row.emotes_flags()?);
+
+    AuditEntry {
+        party_id,
+        user_id: auth.user_id(),
+        target_id: Some(emote_id),
+        action: AuditAction::EmoteUpdate,
+        reason: None,
+        changes,
+    }
+    .record(&state, &t)
+    .await?;
+
+    t.commit().await?;
+
+    Ok(CustomEmote {
+        id: emote_id,
+        party_id,
+        asset: row.emotes_asset_id()?,
+        name: row.emotes_name()?,
+        alt: row.emotes_alt()?,
+        flags: row.emotes_flags()?,
+        aspect_ratio: row.emotes_aspect_ratio()?,
+    })
+}
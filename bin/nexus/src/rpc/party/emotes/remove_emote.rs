@@ -0,0 +1,55 @@
+use schema::audit::AuditAction;
+use sdk::api::commands::party::DeleteEmote;
+use sdk::models::*;
+
+use crate::{
+    internal::audit::{AuditChanges, AuditEntry},
+    prelude::*,
+};
+
+pub async fn remove_emote(state: ServerState, auth: Authorization, cmd: &Archived<DeleteEmote>) -> Result<(), Error> {
+    let party_id: PartyId = cmd.party_id.into();
+    let emote_id: EmoteId = cmd.emote_id.into();
+
+    let mut db = state.db.write.get().await?;
+    let t = db.transaction().await?;
+
+    // NOTE: Reactions using this emote are removed by the foreign key cascade,
+    // and the asset, once no longer in `AggUsedAssets`, by the orphan cleanup task.
+    #[rustfmt::skip]
+    let Some(row) = t.query_opt2(schema::sql! {
+        DELETE FROM Emotes USING PartyMembers
+        WHERE Emotes.Id = #{&emote_id as Emotes::Id}
+          AND Emotes.PartyId = #{&party_id as Party::Id}
+          AND PartyMembers.PartyId = Emotes.PartyId
+          AND PartyMembers.UserId = #{auth.user_id_ref() as Users::Id}
+
+        const PERMS: [i64; 2] = Permissions::MANAGE_EXPRESSIONS.to_i64();
+        const_assert!(PERMS[1] == 0);
+
+        AND PartyMembers.Permissions1 & const {PERMS[0]} = const {PERMS[0]}
+        RETURNING Emotes.Name AS @Name
+    }).await? else {
+        t.rollback().await?;
+
+        return Err(Error::NotFound);
+    };
+
+    let mut changes = AuditChanges::default();
+    changes.diff("name", Some(row.name::<SmolStr>()?), None);
+
+    AuditEntry {
+        party_id,
+        user_id: auth.user_id(),
+        target_id: Some(emote_id),
+        action: AuditAction::EmoteDelete,
+        reason: None,
+        changes,
+    }
+    .record(&state, &t)
+    .await?;
+
+    t.commit().await?;
+
+    Ok(())
+}
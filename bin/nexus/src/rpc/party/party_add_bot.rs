@@ -0,0 +1,166 @@
+use schema::audit::AuditAction;
+use sdk::{api::commands::all::AddBotToParty, models::*};
+
+use crate::{
+    internal::audit::{AuditChanges, AuditEntry},
+    prelude::*,
+};
+
+/// Adds a bot owned by the user to a party, granting it the requested permissions
+/// through a role of its own. Permissions the user doesn't have themselves are dropped,
+/// and the role is placed directly below the user's own highest role so the bot never outranks them.
+pub async fn add_bot(state: ServerState, auth: Authorization, cmd: &Archived<AddBotToParty>) -> Result<(), Error> {
+    if !auth.is_user() {
+        return Err(Error::Unauthorized);
+    }
+
+    let party_id: PartyId = cmd.party_id.into();
+    let bot_id: UserId = cmd.bot_id.into();
+    let form = &cmd.body;
+
+    let requested = form.permissions.deserialize_simple().expect("Unable to deserialize permissions");
+
+    let mut db = state.db.write.get().await?;
+    let t = db.transaction().await?;
+
+    #[rustfmt::skip]
+    let Some(row) = t.query_opt2(schema::sql! {
+        SELECT
+            PartyMembers.Permissions1 AS @Permissions1,
+            PartyMembers.Permissions2 AS @Permissions2,
+            Users.Username AS @Username,
+            Party.OwnerId AS @OwnerId,
+            (
+                SELECT MAX(Roles.Position) FROM Roles
+                WHERE Roles.PartyId = PartyMembers.PartyId
+            ) AS @MaxPosition,
+            (
+                SELECT MAX(Roles.Position) FROM Roles INNER JOIN RoleMembers ON RoleMembers.RoleId = Roles.Id
+                WHERE Roles.PartyId = PartyMembers.PartyId
+                  AND RoleMembers.UserId = PartyMembers.UserId
+            ) AS @OwnPosition,
+            EXISTS(
+                SELECT FROM PartyMembers AS Existing
+                WHERE Existing.PartyId = PartyMembers.PartyId
+                  AND Existing.UserId = Apps.BotId
+            ) AS @IsMember,
+            EXISTS(
+                SELECT FROM PartyBans
+                WHERE PartyBans.PartyId = PartyMembers.PartyId
+                  AND PartyBans.UserId = Apps.BotId
+            ) AS @Banned
+        FROM PartyMembers
+            INNER JOIN LiveParties AS Party ON Party.Id = PartyMembers.PartyId
+            INNER JOIN Apps ON Apps.OwnerId = PartyMembers.UserId
+            INNER JOIN Users ON Users.Id = Apps.BotId
+        WHERE PartyMembers.PartyId = #{&party_id as Party::Id}
+          AND PartyMembers.UserId = #{auth.user_id_ref() as Users::Id}
+          AND Apps.BotId = #{&bot_id as Apps::BotId}
+    }).await? else {
+        t.rollback().await?;
+
+        return Err(Error::NotFound);
+    };
+
+    let perms = Permissions::from_i64(row.permissions1()?, row.permissions2()?);
+
+    if !perms.contains(Permissions::MANAGE_PARTY) {
+        t.rollback().await?;
+
+        return Err(Error::Unauthorized);
+    }
+
+    if row.is_member()? || row.banned()? {
+        t.rollback().await?;
+
+        return Err(Error::Conflict);
+    }
+
+    // closed parties without roles have no position
+    let Some(max_position) = row.max_position::<Option<i16>>()? else {
+        t.rollback().await?;
+
+        return Err(Error::BadRequest);
+    };
+
+    // TODO: Handle this limit better, same as creating roles
+    if max_position >= 255 {
+        t.rollback().await?;
+
+        return Err(Error::BadRequest);
+    }
+
+    let is_owner = row.owner_id::<Option<UserId>>()? == Some(auth.user_id());
+
+    // the owner outranks every role, so their bots go above all others like any new role,
+    // while anyone else's take the position of their highest role, which moves up by one
+    let position = match row.own_position::<Option<i16>>()? {
+        _ if is_owner => max_position + 1,
+        Some(position) => position,
+        None => {
+            t.rollback().await?;
+
+            return Err(Error::Unauthorized);
+        }
+    };
+
+    let granted = requested.intersection(perms);
+    let [perms1, perms2] = granted.to_i64();
+
+    let role_id = state.sf.gen();
+    let role_name: SmolStr = row.username()?;
+
+    #[rustfmt::skip]
+    t.execute2(schema::sql! {
+        CALL .add_member(#{&bot_id as Users::Id}, #{&party_id as Party::Id}, NULL)
+    }).await?;
+
+    if !is_owner {
+        #[rustfmt::skip]
+        t.execute2(schema::sql! {
+            UPDATE Roles SET (Position) = (Roles.Position + 1)
+            WHERE Roles.PartyId = #{&party_id as Roles::PartyId}
+              AND Roles.Position >= #{&position as Roles::Position}
+        }).await?;
+    }
+
+    #[rustfmt::skip]
+    t.execute2(schema::sql! {
+        INSERT INTO Roles (Id, PartyId, Name, Position, Permissions1, Permissions2) VALUES (
+            #{&role_id      as Roles::Id},
+            #{&party_id     as Roles::PartyId},
+            #{&role_name    as Roles::Name},
+            #{&position     as Roles::Position},
+            #{&perms1       as Roles::Permissions1},
+            #{&perms2       as Roles::Permissions2}
+        )
+    }).await?;
+
+    #[rustfmt::skip]
+    t.execute2(schema::sql! {
+        INSERT INTO RoleMembers (RoleId, UserId) VALUES (
+            #{&role_id as RoleMembers::RoleId},
+            #{&bot_id  as RoleMembers::UserId}
+        )
+    }).await?;
+
+    let mut changes = AuditChanges::default();
+    changes.set("permissions", None, granted);
+
+    AuditEntry {
+        party_id,
+        user_id: auth.user_id(),
+        target_id: Some(bot_id),
+        action: AuditAction::BotAdd,
+        reason: None,
+        changes,
+    }
+    .record(&state, &t)
+    .await?;
+
+    t.commit().await?;
+
+    log::debug!("Bot {bot_id} added to party {party_id} by {}", auth.user_id());
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use sdk::{api::commands::all::GetPartyCommands, models::*};
+use thorn::pg::Json;
+
+use crate::prelude::*;
+
+/// Lists the commands available in a party, from every bot that is a member of it,
+/// so clients can suggest them and their arguments.
+pub async fn get_party_commands(
+    state: ServerState,
+    auth: Authorization,
+    cmd: &Archived<GetPartyCommands>,
+) -> Result<impl Stream<Item = Result<AppCommand, Error>>, Error> {
+    let party_id: PartyId = cmd.party_id.into();
+
+    #[rustfmt::skip]
+    let stream = state.db.read.get().await?.query_stream2(schema::sql! {
+        SELECT
+            AppCommands.Id          AS @_,
+            AppCommands.AppId       AS @_,
+            AppCommands.PartyId     AS @_,
+            AppCommands.Name        AS @_,
+            AppCommands.Description AS @_,
+            AppCommands.Options     AS @_
+        FROM AppCommands
+            INNER JOIN Apps ON Apps.Id = AppCommands.AppId
+            INNER JOIN PartyMembers ON PartyMembers.UserId = Apps.BotId
+        WHERE PartyMembers.PartyId = #{&party_id as Party::Id}
+          AND (AppCommands.PartyId IS NULL OR AppCommands.PartyId = PartyMembers.PartyId)
+          // only members may see what commands are available
+          AND #{auth.user_id_ref() as Users::Id} IN (
+            SELECT PartyMembers.UserId FROM PartyMembers
+            WHERE PartyMembers.PartyId = #{&party_id as Party::Id}
+          )
+        ORDER BY AppCommands.Name ASC, AppCommands.Id ASC
+    }).await?;
+
+    Ok(stream.map(move |row| match row {
+        Err(e) => Err(Error::from(e)),
+        Ok(row) => Ok(AppCommand {
+            id: row.app_commands_id()?,
+            app_id: row.app_commands_app_id()?,
+            party_id: row.app_commands_party_id()?,
+            name: row.app_commands_name()?,
+            description: row.app_commands_description()?,
+            options: row.app_commands_options::<Json<_>>()?.0,
+        }),
+    }))
+}
@@ -117,6 +117,11 @@ pub async fn create_party(
     )
     .await?;
 
+    match crate::internal::faction::place_party(&t, party.id).await? {
+        Some(faction_id) => log::debug!("Placed party {} on faction {faction_id}", party.id),
+        None => log::debug!("No live factions, party {} will be served by the nexus", party.id),
+    }
+
     t.commit().await?;
 
     party.roles.push(default_role);
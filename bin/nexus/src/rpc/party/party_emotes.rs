@@ -13,6 +13,7 @@ pub async fn get_custom_emotes_raw<'a, DB: db::AnyClient>(
                 Emotes.PartyId      AS @_,
                 Emotes.AssetId      AS @_,
                 Emotes.Name         AS @_,
+                Emotes.Alt          AS @_,
                 Emotes.Flags        AS @_,
                 Emotes.AspectRatio  AS @_
             FROM Emotes WHERE match party_id {
@@ -29,6 +30,7 @@ pub async fn get_custom_emotes_raw<'a, DB: db::AnyClient>(
             party_id: row.emotes_party_id()?,
             asset: row.emotes_asset_id()?,
             name: row.emotes_name()?,
+            alt: row.emotes_alt()?,
             flags: row.emotes_flags()?,
             aspect_ratio: row.emotes_aspect_ratio()?,
         }),
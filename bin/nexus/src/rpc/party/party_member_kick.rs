@@ -0,0 +1,57 @@
+use schema::{audit::AuditAction, flags::MemberFlags, roles::UserAction};
+use sdk::{api::commands::party::KickMember, models::*};
+
+use crate::{
+    internal::audit::{AuditEntry, MAX_REASON_LENGTH},
+    prelude::*,
+};
+
+pub async fn kick_member(state: ServerState, auth: Authorization, cmd: &Archived<KickMember>) -> Result<(), Error> {
+    let party_id: PartyId = cmd.party_id.into();
+    let member_id: UserId = cmd.member_id.into();
+    let reason = cmd.body.reason.as_deref();
+
+    if matches!(reason, Some(reason) if reason.len() > MAX_REASON_LENGTH) {
+        return Err(Error::BadRequest);
+    }
+
+    let mut db = state.db.write.get().await?;
+    let t = db.transaction().await?;
+
+    crate::internal::moderation::check_member_action(&t, party_id, auth.user_id(), member_id, UserAction::Kick)
+        .await?;
+
+    // NOTE: Deleting the member row emits the member_left event, and the
+    // delete trigger cleans up their room and role memberships.
+    #[rustfmt::skip]
+    let res = t.execute2(schema::sql! {
+        DELETE FROM PartyMembers
+        WHERE PartyMembers.PartyId = #{&party_id as Party::Id}
+          AND PartyMembers.UserId = #{&member_id as Users::Id}
+          // banned members are removed via unbanning
+          AND PartyMembers.Flags & const {MemberFlags::BANNED.bits()} = 0
+    }).await?;
+
+    if res == 0 {
+        t.rollback().await?;
+
+        return Err(Error::NotFound);
+    }
+
+    AuditEntry {
+        party_id,
+        user_id: auth.user_id(),
+        target_id: Some(member_id),
+        action: AuditAction::MemberKick,
+        reason,
+        changes: Default::default(),
+    }
+    .record(&state, &t)
+    .await?;
+
+    t.commit().await?;
+
+    state.perm_cache.clear_user(member_id).await;
+
+    Ok(())
+}
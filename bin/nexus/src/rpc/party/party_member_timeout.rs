@@ -0,0 +1,71 @@
+use std::time::{Duration, SystemTime};
+
+use schema::{audit::AuditAction, roles::UserAction};
+use sdk::{api::commands::party::TimeoutMember, models::*};
+
+use crate::{
+    internal::audit::{AuditChanges, AuditEntry},
+    prelude::*,
+};
+
+/// Maximum duration of a timeout, 28 days
+pub const MAX_TIMEOUT: Duration = Duration::from_secs(60 * 60 * 24 * 28);
+
+/// Applies, changes or lifts (if no duration is given) a party-wide timeout
+pub async fn timeout_member(
+    state: ServerState,
+    auth: Authorization,
+    cmd: &Archived<TimeoutMember>,
+) -> Result<(), Error> {
+    let party_id: PartyId = cmd.party_id.into();
+    let member_id: UserId = cmd.member_id.into();
+
+    let mute_until = match cmd.body.duration.as_ref() {
+        Some(secs) if secs.to_native() == 0 => return Err(Error::BadRequest),
+        Some(secs) => {
+            Some(SystemTime::now() + Duration::from_secs(secs.to_native() as u64).min(MAX_TIMEOUT))
+        }
+        None => None,
+    };
+
+    let mut db = state.db.write.get().await?;
+    let t = db.transaction().await?;
+
+    crate::internal::moderation::check_member_action(&t, party_id, auth.user_id(), member_id, UserAction::Mute)
+        .await?;
+
+    // NOTE: The member update trigger will emit the member_updated event
+    #[rustfmt::skip]
+    let res = t.execute2(schema::sql! {
+        UPDATE PartyMembers SET (MuteUntil) = (#{&mute_until as PartyMembers::MuteUntil})
+        WHERE PartyMembers.PartyId = #{&party_id as Party::Id}
+          AND PartyMembers.UserId = #{&member_id as Users::Id}
+    }).await?;
+
+    if res == 0 {
+        t.rollback().await?;
+
+        return Err(Error::NotFound);
+    }
+
+    let mut changes = AuditChanges::default();
+    changes.set("duration", None, cmd.body.duration.as_ref().map(|secs| secs.to_native()));
+
+    AuditEntry {
+        party_id,
+        user_id: auth.user_id(),
+        target_id: Some(member_id),
+        action: AuditAction::MemberTimeout,
+        reason: None,
+        changes,
+    }
+    .record(&state, &t)
+    .await?;
+
+    t.commit().await?;
+
+    // any cached permissions would not reflect the mute
+    state.perm_cache.clear_user(member_id).await;
+
+    Ok(())
+}
@@ -1,6 +1,9 @@
 use crate::asset::{maybe_add_asset, AssetMode};
+use crate::internal::audit::{AuditChanges, AuditEntry};
 use crate::prelude::*;
 
+use schema::audit::AuditAction;
+
 use sdk::api::commands::all::{PatchParty, PatchPartyForm};
 use sdk::models::*;
 
@@ -94,9 +97,11 @@ pub async fn modify_party(
     let t = db.transaction().await?;
 
     #[rustfmt::skip]
-    let res = t.execute2(schema::sql! {
+    let row = t.query_opt2(schema::sql! {
         struct TempDefaultRoom { Id: Rooms::Id }
 
+        type OldParty = Party;
+
         if set_room {
             // verify the room is within this party
             WITH TempDefaultRoom AS (
@@ -113,15 +118,49 @@ pub async fn modify_party(
             if set_room                         { Party./DefaultRoom = TempDefaultRoom.Id, }
 
             Party./Flags = COALESCE(#{&form.flags as Party::Flags}, Party./Flags)
-        if set_room { FROM TempDefaultRoom }
+        FROM Party AS OldParty if set_room { INNER JOIN TempDefaultRoom ON TRUE }
         WHERE Party.Id = #{&party_id as Party::Id}
+          AND OldParty.Id = Party.Id
+        RETURNING
+            Party.Name              AS @Name,
+            Party.Description       AS @Description,
+            Party.AvatarId          AS @AvatarId,
+            Party.BannerId          AS @BannerId,
+            Party.DefaultRoom       AS @DefaultRoom,
+            Party.Flags             AS @Flags,
+            OldParty.Name           AS @OldName,
+            OldParty.Description    AS @OldDescription,
+            OldParty.AvatarId       AS @OldAvatarId,
+            OldParty.BannerId       AS @OldBannerId,
+            OldParty.DefaultRoom    AS @OldDefaultRoom,
+            OldParty.Flags          AS @OldFlags
     }).await?;
 
-    if res != 1 {
+    let Some(row) = row else {
         t.rollback().await?;
 
         return Err(Error::InternalErrorStatic("Unable to update party"));
+    };
+
+    let mut changes = AuditChanges::default();
+    changes
+        .diff("name", row.old_name::<SmolStr>()?, row.name()?)
+        .diff("description", row.old_description::<Option<SmolStr>>()?, row.description()?)
+        .diff("avatar", row.old_avatar_id::<Option<FileId>>()?, row.avatar_id()?)
+        .diff("banner", row.old_banner_id::<Option<FileId>>()?, row.banner_id()?)
+        .diff("default_room", row.old_default_room::<RoomId>()?, row.default_room()?)
+        .diff("flags", row.old_flags::<PartyFlags>()?, row.flags()?);
+
+    AuditEntry {
+        party_id,
+        user_id: auth.user_id(),
+        target_id: None,
+        action: AuditAction::PartyUpdate,
+        reason: None,
+        changes,
     }
+    .record(&state, &t)
+    .await?;
 
     t.commit().await?;
 
@@ -1,6 +1,10 @@
+use schema::audit::AuditAction;
 use sdk::{api::commands::party::CreateRole, models::*};
 
-use crate::prelude::*;
+use crate::{
+    internal::audit::{AuditChanges, AuditEntry},
+    prelude::*,
+};
 
 pub async fn create_role(
     state: ServerState,
@@ -82,6 +86,20 @@ pub async fn create_role(
         return Err(Error::BadRequest);
     };
 
+    let mut changes = AuditChanges::default();
+    changes.set("name", None, &*form.name);
+
+    AuditEntry {
+        party_id,
+        user_id: auth.user_id(),
+        target_id: Some(role_id),
+        action: AuditAction::RoleCreate,
+        reason: None,
+        changes,
+    }
+    .record(&state, &t)
+    .await?;
+
     t.commit().await?;
 
     Ok(Role {
@@ -1,11 +1,12 @@
 use futures::TryFutureExt;
-use schema::roles::RoleChange;
+use schema::{audit::AuditAction, roles::RoleChange};
 
 use sdk::api::commands::all::{PatchRole, PatchRoleForm};
 use sdk::models::*;
 
 use crate::{
     asset::{maybe_add_asset, AssetMode},
+    internal::audit::{AuditChanges, AuditEntry},
     prelude::*,
     util::encrypted_asset::encrypt_snowflake_opt,
 };
@@ -139,6 +140,8 @@ pub async fn modify_role(
 
     #[rustfmt::skip]
     let updating_role = t.query_one2(schema::sql! {
+        type OldRoles = Roles;
+
         UPDATE Roles SET
             if form.name.is_some()        { Roles./Name     = #{&form.name as Roles::Name}, }
             if !avatar_id.is_undefined()  { Roles./AvatarId = #{&avatar_id as Roles::AvatarId}, }
@@ -150,7 +153,9 @@ pub async fn modify_role(
             }
 
             Roles./Position = #{&new_position as Roles::Position}
+        FROM Roles AS OldRoles
         WHERE Roles.Id = #{&role_id as Roles::Id}
+          AND OldRoles.Id = Roles.Id
         RETURNING
             Roles.AvatarId      AS @AvatarId,
             Roles.Name          AS @Name,
@@ -158,7 +163,14 @@ pub async fn modify_role(
             Roles.Permissions2  AS @Permissions2,
             Roles.Color         AS @Color,
             Roles.Position      AS @Position,
-            Roles.Flags         AS @Flags
+            Roles.Flags         AS @Flags,
+            OldRoles.AvatarId       AS @OldAvatarId,
+            OldRoles.Name           AS @OldName,
+            OldRoles.Permissions1   AS @OldPermissions1,
+            OldRoles.Permissions2   AS @OldPermissions2,
+            OldRoles.Color          AS @OldColor,
+            OldRoles.Position       AS @OldPosition,
+            OldRoles.Flags          AS @OldFlags
     }).map_err(Error::from);
 
     let updating_role_positions = async {
@@ -172,6 +184,30 @@ pub async fn modify_role(
 
     let (row, _) = tokio::try_join!(updating_role, updating_role_positions)?;
 
+    let mut changes = AuditChanges::default();
+    changes
+        .diff("name", row.old_name::<SmolStr>()?, row.name()?)
+        .diff("avatar", row.old_avatar_id::<Option<FileId>>()?, row.avatar_id()?)
+        .diff(
+            "permissions",
+            Permissions::from_i64(row.old_permissions1()?, row.old_permissions2()?),
+            Permissions::from_i64(row.permissions1()?, row.permissions2()?),
+        )
+        .diff("color", row.old_color::<Option<i32>>()?, row.color()?)
+        .diff("position", row.old_position::<i16>()?, row.position()?)
+        .diff("flags", row.old_flags::<RoleFlags>()?, row.flags()?);
+
+    AuditEntry {
+        party_id,
+        user_id: auth.user_id(),
+        target_id: Some(role_id),
+        action: AuditAction::RoleUpdate,
+        reason: None,
+        changes,
+    }
+    .record(&state, &t)
+    .await?;
+
     t.commit().await?;
 
     Ok(Role {
@@ -1,6 +1,10 @@
+use schema::audit::AuditAction;
 use sdk::models::*;
 
-use crate::prelude::*;
+use crate::{
+    internal::audit::{AuditChanges, AuditEntry},
+    prelude::*,
+};
 
 pub async fn remove_role(
     state: ServerState,
@@ -13,10 +17,11 @@ pub async fn remove_role(
         return Err(Error::BadRequest);
     }
 
-    let db = state.db.read.get().await?;
+    let mut db = state.db.write.get().await?;
+    let t = db.transaction().await?;
 
     #[rustfmt::skip]
-    let role_rows = db.query2(schema::sql! {
+    let role_rows = t.query2(schema::sql! {
         SELECT
             Roles.Id AS @RoleId,
             Roles.Position AS @Position,
@@ -31,9 +36,9 @@ pub async fn remove_role(
         WHERE Roles.PartyId = #{&party_id as Party::Id}
     }).await?;
 
-    drop(db);
-
     if role_rows.is_empty() {
+        t.rollback().await?;
+
         return Err(Error::Unauthorized);
     }
 
@@ -57,13 +62,45 @@ pub async fn remove_role(
         }
     }
 
-    let target_role = match RoleChecker::new(party_id, roles).check_modify(&user_roles, role_id, None) {
-        CheckStatus::Allowed(target_role) => target_role,
+    match RoleChecker::new(party_id, roles).check_modify(&user_roles, role_id, None) {
+        CheckStatus::Allowed(_) => {}
         _ => {
+            t.rollback().await?;
+
             // TODO: improve errors from CheckStatus
             return Err(Error::Unauthorized);
         }
+    }
+
+    #[rustfmt::skip]
+    let Some(row) = t.query_opt2(schema::sql! {
+        DELETE FROM Roles
+        WHERE Roles.Id = #{&role_id as Roles::Id}
+          AND Roles.PartyId = #{&party_id as Roles::PartyId}
+        RETURNING Roles.Name AS @Name
+    }).await? else {
+        t.rollback().await?;
+
+        return Err(Error::NotFound);
     };
 
-    unimplemented!()
+    let name: &str = row.name()?;
+
+    let mut changes = AuditChanges::default();
+    changes.diff("name", Some(name), None);
+
+    AuditEntry {
+        party_id,
+        user_id: auth.user_id(),
+        target_id: Some(role_id),
+        action: AuditAction::RoleDelete,
+        reason: None,
+        changes,
+    }
+    .record(&state, &t)
+    .await?;
+
+    t.commit().await?;
+
+    Ok(())
 }
@@ -1,9 +1,13 @@
 use crate::prelude::*;
 
+use schema::audit::AuditAction;
 use sdk::api::commands::party::{CreateRoom, CreateRoomKind};
 use sdk::models::*;
 
-use crate::internal::role_overwrites::RawOverwrites;
+use crate::internal::{
+    audit::{AuditChanges, AuditEntry},
+    role_overwrites::RawOverwrites,
+};
 
 pub async fn create_room(
     state: ServerState,
@@ -118,6 +122,20 @@ pub async fn create_room(
     })
     .await?;
 
+    let mut changes = AuditChanges::default();
+    changes.set("name", None, &name);
+
+    AuditEntry {
+        party_id,
+        user_id: auth.user_id(),
+        target_id: Some(room_id),
+        action: AuditAction::RoomCreate,
+        reason: None,
+        changes,
+    }
+    .record(&state, &t)
+    .await?;
+
     t.commit().await?;
 
     // TODO: should really reuse the db conn, but this api is called so infrequently that I don't care
@@ -0,0 +1,28 @@
+use rpc::request::PartyMigration;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::prelude::*;
+
+/// Yields each stage of parties moving between faction nodes.
+///
+/// If the receiver falls behind, the stream ends with an error so the gateway knows
+/// to forget all party locations rather than miss a migration.
+pub async fn watch_party_migrations(
+    state: ServerState,
+) -> Result<impl Stream<Item = Result<PartyMigration, Error>>, Error> {
+    let rx = state.party_migrations.subscribe();
+
+    Ok(futures::stream::unfold(Some(rx), |rx| async move {
+        let mut rx = rx?;
+
+        match rx.recv().await {
+            Ok(migration) => Some((Ok(migration), Some(rx))),
+            Err(RecvError::Lagged(n)) => {
+                log::warn!("Gateway fell behind by {n} party migrations");
+
+                Some((Err(Error::InternalErrorStatic("Party Migrations Lagged")), None))
+            }
+            Err(RecvError::Closed) => None,
+        }
+    }))
+}
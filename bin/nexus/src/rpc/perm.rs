@@ -1,6 +1,7 @@
 use db::Client;
+use schema::flags::RoomMemberFlags;
 
-use crate::prelude::*;
+use crate::{prelude::*, state::permission_cache::PermMute};
 
 use sdk::models::*;
 
@@ -32,20 +33,31 @@ pub async fn get_cached_room_permissions(
 }
 
 pub async fn get_room_permissions(db: &Client, user_id: UserId, room_id: RoomId) -> Result<Permissions, Error> {
+    get_room_perm_mute(db, user_id, room_id).await.map(|perm| perm.perms)
+}
+
+/// Like [`get_room_permissions`], but also includes if the user is currently timed out,
+/// either party-wide or in this specific room.
+pub async fn get_room_perm_mute(db: &Client, user_id: UserId, room_id: RoomId) -> Result<PermMute, Error> {
     #[rustfmt::skip]
     let row = db.query_opt2(schema::sql! {
         SELECT
              AggRoomPerms.Permissions1 AS @Permissions1,
-             AggRoomPerms.Permissions2 AS @Permissions2
+             AggRoomPerms.Permissions2 AS @Permissions2,
+             AggRoomPerms.Muted        AS @Muted
         FROM AggRoomPerms WHERE
              AggRoomPerms.UserId = #{&user_id as AggRoomPerms::UserId}
          AND AggRoomPerms.Id     = #{&room_id as AggRoomPerms::Id}
     }).await?;
 
-    let mut perm = Permissions::empty();
+    let mut perm = PermMute {
+        perms: Permissions::empty(),
+        flags: RoomMemberFlags::empty(),
+    };
 
     if let Some(row) = row {
-        perm = Permissions::from_i64(row.permissions1()?, row.permissions2()?).normalize();
+        perm.perms = Permissions::from_i64(row.permissions1()?, row.permissions2()?).normalize();
+        perm.flags.set(RoomMemberFlags::MUTED, row.muted()?);
     }
 
     Ok(perm)
@@ -0,0 +1,100 @@
+use std::{
+    net::IpAddr,
+    time::{Duration, SystemTime},
+};
+
+use iplist::IpNet;
+use rpc::request::ArchivedRateLimitViolation;
+
+use crate::prelude::*;
+
+/// Upper limit for escalated automatic IP bans, 30 days
+const MAX_AUTO_BAN: Duration = Duration::from_secs(60 * 60 * 24 * 30);
+
+/// Records rate-limit violations reported by a gateway, and issues a temporary
+/// IP ban for any address that crosses the configured threshold.
+///
+/// Each repeated ban for the same address doubles the ban duration.
+pub async fn report_violations(state: ServerState, violations: &[ArchivedRateLimitViolation]) -> Result<(), Error> {
+    if violations.is_empty() {
+        return Ok(());
+    }
+
+    let (threshold, base_duration, decay) = {
+        let config = state.config();
+        let shared = &config.shared;
+
+        (shared.rl_ban_threshold as i32, shared.rl_ban_duration, shared.rl_violation_decay)
+    };
+
+    let now = SystemTime::now();
+
+    // violations older than this are forgotten rather than accumulated
+    let decay_cutoff = now - decay;
+
+    let mut db = state.db.write.get().await?;
+    let t = db.transaction().await?;
+
+    for violation in violations {
+        let addr = violation.addr.as_ipaddr();
+        let count = violation.count.to_native().min(i32::MAX as u32) as i32;
+
+        #[rustfmt::skip]
+        let row = t.query_one2(schema::sql! {
+            INSERT INTO RateLimits (Addr, Violations) VALUES (
+                #{&addr  as RateLimits::Addr},
+                #{&count as RateLimits::Violations}
+            )
+            ON CONFLICT (RateLimits./Addr) DO UPDATE RateLimits SET (Violations, LastViolation) = (
+                CASE WHEN RateLimits.LastViolation < #{&decay_cutoff as RateLimits::LastViolation}
+                    THEN 0 ELSE RateLimits.Violations END + #{&count as RateLimits::Violations},
+                now()
+            )
+            RETURNING
+                RateLimits.Violations AS @Violations,
+                RateLimits.Bans AS @Bans
+        }).await?;
+
+        let total: i32 = row.violations()?;
+
+        if total < threshold {
+            continue;
+        }
+
+        let bans: i16 = row.bans()?;
+        let duration = base_duration.saturating_mul(1u32 << bans.clamp(0, 16)).min(MAX_AUTO_BAN);
+        let expires = now + duration;
+
+        // IPv6 addresses are privacy-masked by the gateway, so ban the whole /64 they came from
+        let (address, network) = match addr {
+            IpAddr::V4(_) => (Some(addr), None),
+            IpAddr::V6(_) => (None, IpNet::new(addr, 64).ok().map(|net| net.trunc().to_string())),
+        };
+
+        let reason = format!("Automatic: {total} rate-limit violations, repeat offense {}", bans + 1);
+        let ban_id = state.sf.gen();
+
+        #[rustfmt::skip]
+        t.execute2(schema::sql! {
+            INSERT INTO IpBans (Id, Expires, Address, Network, Reason) VALUES (
+                #{&ban_id   as IpBans::Id},
+                #{&expires  as IpBans::Expires},
+                #{&address  as IpBans::Address},
+                #{&network  as Type::TEXT}::cidr,
+                #{&reason   as IpBans::Reason}
+            )
+        }).await?;
+
+        #[rustfmt::skip]
+        t.execute2(schema::sql! {
+            UPDATE RateLimits SET (Violations, Bans) = (0, RateLimits.Bans + 1)
+            WHERE RateLimits.Addr = #{&addr as RateLimits::Addr}
+        }).await?;
+
+        log::warn!("Automatically banned {addr} for {duration:?} after {total} rate-limit violations");
+    }
+
+    t.commit().await?;
+
+    Ok(())
+}
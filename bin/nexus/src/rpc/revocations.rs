@@ -0,0 +1,26 @@
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::prelude::*;
+
+/// Yields the id of each user whose sessions are forcibly revoked, such as when banned or logged out by an admin,
+/// and of each bot whose token was regenerated or application deleted.
+///
+/// If the receiver falls behind, the stream ends with an error so the gateway knows
+/// to drop its entire auth cache rather than miss a revocation.
+pub async fn watch_revoked_users(state: ServerState) -> Result<impl Stream<Item = Result<UserId, Error>>, Error> {
+    let rx = state.revoked_users.subscribe();
+
+    Ok(futures::stream::unfold(Some(rx), |rx| async move {
+        let mut rx = rx?;
+
+        match rx.recv().await {
+            Ok(user_id) => Some((Ok(user_id), Some(rx))),
+            Err(RecvError::Lagged(n)) => {
+                log::warn!("Gateway fell behind by {n} session revocations");
+
+                Some((Err(Error::InternalErrorStatic("Revocations Lagged")), None))
+            }
+            Err(RecvError::Closed) => None,
+        }
+    }))
+}
@@ -0,0 +1,310 @@
+use sdk::{
+    framework_utils::args::ArgumentSplitter,
+    models::{gateway::message::ServerMsg, *},
+};
+use thorn::pg::Json;
+
+use crate::{gateway::task::event_processors::emit, prelude::*};
+
+use super::slash;
+
+/// Looks up a command registered by a bot in the room's party, and if found, validates the given arguments
+/// against its options and sends the resulting interaction to the bot.
+///
+/// Returns `false` if there is no such command, in which case the content should be posted as usual.
+pub async fn process_command(
+    state: &ServerState,
+    db: &db::Client,
+    auth: Authorization,
+    room_id: RoomId,
+    content: &str,
+) -> Result<bool, Error> {
+    let Some(rest) = content.strip_prefix('/') else {
+        return Ok(false);
+    };
+
+    let (name, input) = match rest.find(char::is_whitespace) {
+        Some(idx) => (&rest[..idx], rest[idx..].trim_start()),
+        None => (rest, ""),
+    };
+
+    if name.is_empty() || slash::is_builtin(name) {
+        return Ok(false);
+    }
+
+    #[rustfmt::skip]
+    let rows = db.query2(schema::sql! {
+        SELECT
+            AppCommands.Id          AS @_,
+            AppCommands.AppId       AS @_,
+            AppCommands.PartyId     AS @_,
+            AppCommands.Options     AS @_,
+            Apps.BotId              AS @_,
+            Rooms.PartyId           AS @RoomPartyId
+        FROM AppCommands
+            INNER JOIN Apps ON Apps.Id = AppCommands.AppId
+            INNER JOIN PartyMembers ON PartyMembers.UserId = Apps.BotId
+            INNER JOIN LiveRooms AS Rooms ON Rooms.PartyId = PartyMembers.PartyId
+        WHERE Rooms.Id = #{&room_id as Rooms::Id}
+          AND AppCommands.Name = #{&name as AppCommands::Name}
+          AND (AppCommands.PartyId IS NULL OR AppCommands.PartyId = Rooms.PartyId)
+        ORDER BY AppCommands.Id ASC
+    }).await?;
+
+    // commands registered for the party take precedence over global ones, then the oldest wins
+    let mut command = None;
+
+    for row in &rows {
+        if row.app_commands_party_id::<Option<PartyId>>()?.is_some() {
+            command = Some(row);
+            break;
+        }
+    }
+
+    let Some(row) = command.or(rows.first()) else {
+        return Ok(false);
+    };
+
+    let bot_id: UserId = row.apps_bot_id()?;
+
+    // same as for gateway events, the bot must be able to see the room to receive the interaction
+    let bot_perms = crate::rpc::perm::get_room_permissions(db, bot_id, room_id).await?;
+
+    if !bot_perms.contains(Permissions::VIEW_ROOM) {
+        return Ok(false);
+    }
+
+    let Json(options) = row.app_commands_options::<Json<Vec<AppCommandOption>>>()?;
+
+    let Some(values) = parse_arguments(&options, input) else {
+        return Err(Error::BadRequest);
+    };
+
+    let interaction_id = state.sf.gen();
+    let command_id: Snowflake = row.app_commands_id()?;
+
+    #[rustfmt::skip]
+    db.execute2(schema::sql! {
+        INSERT INTO Interactions (Id, CommandId, UserId, RoomId) VALUES (
+            #{&interaction_id    as Interactions::Id},
+            #{&command_id        as Interactions::CommandId},
+            #{auth.user_id_ref() as Interactions::UserId},
+            #{&room_id           as Interactions::RoomId}
+        )
+    }).await?;
+
+    let interaction = Interaction {
+        id: interaction_id,
+        command_id,
+        app_id: row.app_commands_app_id()?,
+        name: SmolStr::from(name),
+        party_id: row.room_party_id()?,
+        room_id,
+        user_id: auth.user_id(),
+        options: values,
+    };
+
+    emit(
+        state,
+        ServerEvent::user(bot_id, Some(room_id), ServerMsg::new_interaction_create(interaction)),
+    )
+    .await?;
+
+    Ok(true)
+}
+
+/// Matches positional arguments to the command's options, returning `None` if any are
+/// missing, malformed or left over.
+fn parse_arguments(options: &[AppCommandOption], input: &str) -> Option<ThinVec<InteractionOption>> {
+    let args = ArgumentSplitter::split(input);
+    let args = args.arguments();
+
+    let mut values = ThinVec::with_capacity(options.len());
+    let mut consumed_rest = false;
+
+    for (idx, option) in options.iter().enumerate() {
+        let Some(arg) = args.get(idx) else {
+            // stored options are validated on creation, but don't rely on that to skip a required one
+            if options[idx..].iter().any(|option| option.required) {
+                return None;
+            }
+
+            break;
+        };
+
+        let inner = arg.inner_str();
+
+        let value = match option.kind {
+            // a trailing string takes the rest of the input, so it doesn't need to be quoted
+            AppCommandOptionKind::String if idx + 1 == options.len() && !arg.is_quoted() => {
+                consumed_rest = true;
+
+                InteractionValue::String(SmolStr::from(arg.orig()[arg.outer().start..].trim_end()))
+            }
+            AppCommandOptionKind::String => InteractionValue::String(SmolStr::from(inner)),
+            AppCommandOptionKind::Integer => InteractionValue::Integer(inner.parse().ok()?),
+            AppCommandOptionKind::Number => match inner.parse::<f64>() {
+                Ok(n) if n.is_finite() => InteractionValue::Number(n),
+                _ => return None,
+            },
+            AppCommandOptionKind::Boolean => match inner {
+                "true" | "yes" | "on" => InteractionValue::Boolean(true),
+                "false" | "no" | "off" => InteractionValue::Boolean(false),
+                _ => return None,
+            },
+            AppCommandOptionKind::User => InteractionValue::User(parse_mention(inner, "<@")?),
+            AppCommandOptionKind::Role => InteractionValue::Role(parse_mention(inner, "<@&")?),
+            AppCommandOptionKind::Room => InteractionValue::Room(parse_mention(inner, "<#")?),
+        };
+
+        values.push(InteractionOption {
+            name: option.name.clone(),
+            value,
+        });
+    }
+
+    if !consumed_rest && args.len() > options.len() {
+        return None;
+    }
+
+    Some(values)
+}
+
+/// Accepts either a mention, like `<@id>`, or a bare id
+fn parse_mention(arg: &str, prefix: &str) -> Option<Snowflake> {
+    let id = match arg.strip_prefix(prefix) {
+        Some(rest) => rest.strip_suffix('>')?,
+        None => arg,
+    };
+
+    id.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn option(name: &str, kind: AppCommandOptionKind, required: bool) -> AppCommandOption {
+        AppCommandOption {
+            name: SmolStr::from(name),
+            description: SmolStr::default(),
+            kind,
+            required,
+        }
+    }
+
+    fn string(value: &InteractionValue) -> &str {
+        match value {
+            InteractionValue::String(s) => s,
+            _ => panic!("expected a string"),
+        }
+    }
+
+    #[test]
+    fn test_trailing_string() {
+        let options = [
+            option("count", AppCommandOptionKind::Integer, true),
+            option("reason", AppCommandOptionKind::String, true),
+        ];
+
+        let values = parse_arguments(&options, "3 spam and  more spam  ").unwrap();
+
+        assert!(matches!(values[0].value, InteractionValue::Integer(3)));
+        assert_eq!(string(&values[1].value), "spam and  more spam");
+    }
+
+    #[test]
+    fn test_quoting() {
+        let options = [
+            option("title", AppCommandOptionKind::String, true),
+            option("flag", AppCommandOptionKind::Boolean, true),
+        ];
+
+        let values = parse_arguments(&options, r#""hello world" yes"#).unwrap();
+
+        assert_eq!(string(&values[0].value), "hello world");
+        assert!(matches!(values[1].value, InteractionValue::Boolean(true)));
+
+        // a quoted trailing string is taken as one argument, not the rest of the input
+        let options = [option("title", AppCommandOptionKind::String, true)];
+
+        assert_eq!(
+            string(&parse_arguments(&options, r#""hello world""#).unwrap()[0].value),
+            "hello world"
+        );
+        assert!(parse_arguments(&options, r#""hello world" extra"#).is_none());
+    }
+
+    #[test]
+    fn test_required_after_optional() {
+        let options = [
+            option("a", AppCommandOptionKind::Integer, false),
+            option("b", AppCommandOptionKind::Integer, true),
+        ];
+
+        assert!(parse_arguments(&options, "").is_none());
+        assert!(parse_arguments(&options, "1").is_none());
+        assert_eq!(parse_arguments(&options, "1 2").unwrap().len(), 2);
+
+        let options = [
+            option("a", AppCommandOptionKind::Integer, true),
+            option("b", AppCommandOptionKind::Integer, false),
+        ];
+
+        assert_eq!(parse_arguments(&options, "1").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_extra_arguments() {
+        let options = [option("n", AppCommandOptionKind::Integer, true)];
+
+        assert!(parse_arguments(&options, "1 2").is_none());
+        assert!(parse_arguments(&[], "anything").is_none());
+        assert!(parse_arguments(&[], "").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_booleans_and_numbers() {
+        let options = [
+            option("b", AppCommandOptionKind::Boolean, true),
+            option("n", AppCommandOptionKind::Number, true),
+        ];
+
+        for (input, b, n) in [
+            ("true 1.5", true, 1.5),
+            ("off -2", false, -2.0),
+            ("no 1e3", false, 1000.0),
+        ] {
+            let values = parse_arguments(&options, input).unwrap();
+
+            assert!(
+                matches!(values[0].value, InteractionValue::Boolean(v) if v == b),
+                "{input}"
+            );
+            assert!(
+                matches!(values[1].value, InteractionValue::Number(v) if v == n),
+                "{input}"
+            );
+        }
+
+        for input in ["maybe 1", "true NaN", "true inf", "true x"] {
+            assert!(parse_arguments(&options, input).is_none(), "{input}");
+        }
+
+        let options = [option("i", AppCommandOptionKind::Integer, true)];
+
+        assert!(parse_arguments(&options, "1.5").is_none());
+    }
+
+    #[test]
+    fn test_parse_mention() {
+        let mention = |arg, prefix| parse_mention(arg, prefix).map(|id| id.to_string());
+
+        assert_eq!(mention("<@123>", "<@").as_deref(), Some("123"));
+        assert_eq!(mention("123", "<@").as_deref(), Some("123"));
+        assert_eq!(mention("<@&123>", "<@&").as_deref(), Some("123"));
+        assert_eq!(mention("<@123", "<@"), None);
+        assert_eq!(mention("<#123>", "<@"), None);
+        assert_eq!(mention("abc", "<@"), None);
+    }
+}
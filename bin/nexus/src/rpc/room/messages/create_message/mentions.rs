@@ -0,0 +1,235 @@
+use md_utils::{Span, SpanType};
+use schema::flags::MemberFlags;
+use sdk::models::{
+    gateway::{events::MessageMentionEvent, message::ServerMsg},
+    Message, MessageFlags, Permissions,
+};
+use smallvec::SmallVec;
+
+use crate::{gateway::task::event_processors::emit, prelude::*};
+
+/// Mentions extracted from message content, before validation against the room's party.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct MessageMentions {
+    pub users: SmallVec<[UserId; 8]>,
+    pub roles: SmallVec<[RoleId; 4]>,
+    pub rooms: SmallVec<[RoomId; 4]>,
+    pub everyone: bool,
+    pub here: bool,
+}
+
+impl MessageMentions {
+    /// Extracts `<@user>`, `<@&role>`, `<#room>`, `@everyone` and `@here` mentions
+    /// from the given content, ignoring any within code.
+    pub fn extract(content: &str, spans: &[Span]) -> MessageMentions {
+        let mut mentions = MessageMentions::default();
+
+        for span in spans {
+            let id = || content[span.range()].parse::<Snowflake>().ok();
+
+            match span.kind() {
+                SpanType::UserMention => mentions.users.extend(id()),
+                SpanType::RoleMention => mentions.roles.extend(id()),
+                SpanType::RoomMention => mentions.rooms.extend(id()),
+                _ => {}
+            }
+        }
+
+        mentions.users.sort_unstable();
+        mentions.users.dedup();
+        mentions.roles.sort_unstable();
+        mentions.roles.dedup();
+        mentions.rooms.sort_unstable();
+        mentions.rooms.dedup();
+
+        let in_code = |idx: usize| {
+            spans.iter().any(|span| {
+                matches!(span.kind(), SpanType::InlineCode | SpanType::BlockCode) && span.range().contains(&idx)
+            })
+        };
+
+        let find = |word: &str| {
+            content.match_indices(word).any(|(idx, _)| {
+                let prev = content[..idx].chars().next_back();
+                let next = content[idx + word.len()..].chars().next();
+
+                !prev.is_some_and(|c| c.is_alphanumeric() || c == '\\')
+                    && !next.is_some_and(char::is_alphanumeric)
+                    && !in_code(idx)
+            })
+        };
+
+        mentions.everyone = find("@everyone");
+        mentions.here = find("@here");
+
+        mentions
+    }
+
+    /// Drops `@everyone` and `@here` if the author is not allowed to use them.
+    pub fn restrict(&mut self, perms: Permissions) {
+        if !perms.contains(Permissions::MENTION_EVERYONE) {
+            self.everyone = false;
+            self.here = false;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty() && self.roles.is_empty() && self.rooms.is_empty()
+    }
+
+    /// Message flags for the `@everyone` and `@here` mentions, which are not stored as rows.
+    pub fn flags(&self) -> MessageFlags {
+        let mut flags = MessageFlags::empty();
+
+        flags.set(MessageFlags::MENTIONS_EVERYONE, self.everyone);
+        flags.set(MessageFlags::MENTIONS_HERE, self.here);
+
+        flags
+    }
+
+    /// Mentions present in `self` but not in `other`, ignoring `@everyone` and `@here`.
+    pub fn difference(&self, other: &MessageMentions) -> MessageMentions {
+        MessageMentions {
+            users: self.users.iter().filter(|id| !other.users.contains(id)).copied().collect(),
+            roles: self.roles.iter().filter(|id| !other.roles.contains(id)).copied().collect(),
+            rooms: self.rooms.iter().filter(|id| !other.rooms.contains(id)).copied().collect(),
+            everyone: false,
+            here: false,
+        }
+    }
+}
+
+/// Stores mentions of the message, silently dropping any users, roles or rooms
+/// not belonging to the party of the room the message was sent in.
+///
+/// The `@everyone` role is never stored, as that is covered by [`MessageFlags::MENTIONS_EVERYONE`].
+pub async fn insert_mentions(
+    t: &db::Transaction<'_>,
+    msg_id: MessageId,
+    room_id: RoomId,
+    mentions: &MessageMentions,
+) -> Result<(), Error> {
+    use futures::future::{ok, Either};
+
+    let (users, roles, rooms) = (mentions.users.as_slice(), mentions.roles.as_slice(), mentions.rooms.as_slice());
+
+    // queue up queries to be pipelined
+    let mut insert_users = Either::Left(ok::<(), Error>(()));
+    let mut insert_roles = Either::Left(ok::<(), Error>(()));
+    let mut insert_rooms = Either::Left(ok::<(), Error>(()));
+
+    if !users.is_empty() {
+        insert_users = Either::Right(async move {
+            #[rustfmt::skip]
+            t.execute2(schema::sql! {
+                INSERT INTO Mentions (MsgId, UserId)
+                SELECT #{&msg_id as Mentions::MsgId}, PartyMembers.UserId
+                FROM LiveRooms AS Rooms INNER JOIN PartyMembers ON PartyMembers.PartyId = Rooms.PartyId
+                WHERE Rooms.Id = #{&room_id as Rooms::Id}
+                  AND PartyMembers.UserId = ANY(#{&users as SNOWFLAKE_ARRAY})
+                  AND PartyMembers.Flags & const {MemberFlags::BANNED.bits()} = 0
+            }).await?;
+
+            Ok(())
+        });
+    }
+
+    if !roles.is_empty() {
+        insert_roles = Either::Right(async move {
+            #[rustfmt::skip]
+            t.execute2(schema::sql! {
+                INSERT INTO Mentions (MsgId, RoleId)
+                SELECT #{&msg_id as Mentions::MsgId}, Roles.Id
+                FROM LiveRooms AS Rooms INNER JOIN Roles ON Roles.PartyId = Rooms.PartyId
+                WHERE Rooms.Id = #{&room_id as Rooms::Id}
+                  AND Roles.Id != Rooms.PartyId
+                  AND Roles.Id = ANY(#{&roles as SNOWFLAKE_ARRAY})
+            }).await?;
+
+            Ok(())
+        });
+    }
+
+    if !rooms.is_empty() {
+        insert_rooms = Either::Right(async move {
+            #[rustfmt::skip]
+            t.execute2(schema::sql! {
+                INSERT INTO Mentions (MsgId, RoomId)
+                SELECT #{&msg_id as Mentions::MsgId}, Other.Id
+                FROM LiveRooms AS Rooms INNER JOIN LiveRooms AS Other ON Other.PartyId = Rooms.PartyId
+                WHERE Rooms.Id = #{&room_id as Rooms::Id}
+                  AND Other.Id = ANY(#{&rooms as SNOWFLAKE_ARRAY})
+            }).await?;
+
+            Ok(())
+        });
+    }
+
+    tokio::try_join!(insert_users, insert_roles, insert_rooms)?;
+
+    Ok(())
+}
+
+/// Removes the given mentions of the message, such as when edited out.
+pub async fn remove_mentions(
+    t: &db::Transaction<'_>,
+    msg_id: MessageId,
+    mentions: &MessageMentions,
+) -> Result<(), Error> {
+    if mentions.is_empty() {
+        return Ok(());
+    }
+
+    let (users, roles, rooms) = (mentions.users.as_slice(), mentions.roles.as_slice(), mentions.rooms.as_slice());
+
+    #[rustfmt::skip]
+    t.execute2(schema::sql! {
+        DELETE FROM Mentions
+        WHERE Mentions.MsgId = #{&msg_id as Mentions::MsgId}
+          AND (Mentions.UserId = ANY(#{&users as SNOWFLAKE_ARRAY})
+            OR Mentions.RoleId = ANY(#{&roles as SNOWFLAKE_ARRAY})
+            OR Mentions.RoomId = ANY(#{&rooms as SNOWFLAKE_ARRAY}))
+    }).await?;
+
+    Ok(())
+}
+
+/// Notifies party members newly mentioned by the message, either directly or through one of their roles.
+///
+/// Authors are never notified of their own mentions, and `@everyone` and `@here` are left
+/// to clients, as everyone able to see the message receives it anyway.
+pub async fn notify_mentions(state: &ServerState, msg: &Message, mentions: &MessageMentions) -> Result<(), Error> {
+    let (users, roles) = (mentions.users.as_slice(), mentions.roles.as_slice());
+
+    if users.is_empty() && roles.is_empty() {
+        return Ok(());
+    }
+
+    let party_id = msg.party_id;
+    let author_id = msg.author.user.id;
+
+    #[rustfmt::skip]
+    let rows = state.db.read.get().await?.query2(schema::sql! {
+        SELECT PartyMembers.UserId AS @UserId FROM PartyMembers
+        WHERE PartyMembers.PartyId = #{&party_id as PartyMembers::PartyId}
+          AND PartyMembers.UserId != #{&author_id as PartyMembers::UserId}
+          AND PartyMembers.Flags & const {MemberFlags::BANNED.bits()} = 0
+          AND (PartyMembers.UserId = ANY(#{&users as SNOWFLAKE_ARRAY}) OR EXISTS(
+            SELECT FROM RoleMembers INNER JOIN Roles ON Roles.Id = RoleMembers.RoleId
+            WHERE RoleMembers.UserId = PartyMembers.UserId
+              AND Roles.PartyId = #{&party_id as Roles::PartyId}
+              AND Roles.Id = ANY(#{&roles as SNOWFLAKE_ARRAY})
+          ))
+    }).await?;
+
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    let user_ids = rows.iter().map(|row| row.user_id::<UserId>()).collect::<Result<Vec<_>, _>>()?;
+
+    #[rustfmt::skip]
+    emit(state, ServerEvent::new_iter(user_ids, [], Some(msg.room_id), ServerMsg::new_message_mention(
+        MessageMentionEvent { msg_id: msg.id, room_id: msg.room_id, party_id, author_id },
+    ))).await
+}
@@ -1,38 +1,74 @@
 use futures::FutureExt;
+use schema::flags::RoomMemberFlags;
 use sdk::api::commands::all::CreateMessage;
 
 use crate::{prelude::*, state::permission_cache::PermMute};
 
 use sdk::models::*;
 
+pub mod command;
 pub mod embed;
+pub mod mentions;
 pub mod slash;
 pub mod verify;
 
 use sdk::api::commands::room::CreateMessageBody;
 
+use self::mentions::MessageMentions;
+
 /// Returns an `Option<Message>` because slash-commands may not actually create a message
 pub async fn create_message(
     state: ServerState,
     auth: Authorization,
     cmd: &Archived<CreateMessage>,
 ) -> Result<Option<Message>, Error> {
-    let room_id: RoomId = cmd.room_id.into();
+    do_create_message(state, auth, cmd.room_id.into(), &cmd.body, None).await
+}
+
+/// Creates a message from a body that didn't arrive as a `CreateMessage` command, such as an interaction response
+pub(crate) async fn create_message_body(
+    state: ServerState,
+    auth: Authorization,
+    room_id: RoomId,
+    body: &Archived<CreateMessageBody>,
+) -> Result<Option<Message>, Error> {
+    do_create_message(state, auth, room_id, body, None).await
+}
+
+/// Creates a message as a webhook's user, which is not a member of the room,
+/// so the given permissions are used in place of its own.
+pub(crate) async fn create_webhook_message(
+    state: ServerState,
+    auth: Authorization,
+    room_id: RoomId,
+    body: &Archived<CreateMessageBody>,
+    perms: Permissions,
+) -> Result<Option<Message>, Error> {
+    do_create_message(state, auth, room_id, body, Some(perms)).await
+}
 
+async fn do_create_message(
+    state: ServerState,
+    auth: Authorization,
+    room_id: RoomId,
+    body: &Archived<CreateMessageBody>,
+    webhook_perms: Option<Permissions>,
+) -> Result<Option<Message>, Error> {
     // fast-path for if the perm_cache does contain a value, otherwise defer until content is checked
-    let perms = match state.perm_cache.get(auth.user_id(), room_id).await {
-        Some(PermMute { perms, .. }) => {
-            if !perms.contains(Permissions::SEND_MESSAGES) {
-                return Err(Error::Unauthorized);
+    let perms = match webhook_perms {
+        Some(perms) => Some(perms),
+        None => match state.perm_cache.get(auth.user_id(), room_id).await {
+            Some(PermMute { perms, flags }) => {
+                if !perms.contains(Permissions::SEND_MESSAGES) || flags.contains(RoomMemberFlags::MUTED) {
+                    return Err(Error::Unauthorized);
+                }
+
+                Some(perms)
             }
-
-            Some(perms)
-        }
-        None => None,
+            None => None,
+        },
     };
 
-    let body = &cmd.body;
-
     let trimmed_content = body.content.as_str().trim();
 
     // if empty but not containing attachments
@@ -63,9 +99,10 @@ pub async fn create_message(
         None => {
             let db = state.db.write.get().await?;
 
-            let perms = crate::rpc::perm::get_room_permissions(&db, auth.user_id(), room_id).await?;
+            let PermMute { perms, flags } = crate::rpc::perm::get_room_perm_mute(&db, auth.user_id(), room_id).await?;
 
-            if !perms.contains(Permissions::SEND_MESSAGES) {
+            // timed out members cannot send messages
+            if !perms.contains(Permissions::SEND_MESSAGES) || flags.contains(RoomMemberFlags::MUTED) {
                 return Err(Error::Unauthorized);
             }
 
@@ -80,6 +117,23 @@ pub async fn create_message(
         return Err(Error::Unauthorized);
     }
 
+    // commands registered by bots are sent to them as interactions instead of being posted,
+    // but only users may invoke them, so bots can't trigger each other in a loop
+    if auth.is_user()
+        && body.attachments.is_empty()
+        && trimmed_content.starts_with('/')
+        && perms.contains(Permissions::USE_SLASH_COMMANDS)
+    {
+        let db: &db::Client = match maybe_db {
+            Some(ref db) => db,
+            None => maybe_db.insert(state.db.write.get().await?),
+        };
+
+        if command::process_command(&state, db, auth, room_id, &trimmed_content).await? {
+            return Ok(None);
+        }
+    }
+
     // modify content before inserting it into the database
     let modified_content =
         match slash::process_slash(&trimmed_content, perms.contains(Permissions::USE_SLASH_COMMANDS)) {
@@ -90,9 +144,6 @@ pub async fn create_message(
 
     let spans = md_utils::scan_markdown(&modified_content);
 
-    // TODO: Set flags
-    let flags = MessageFlags::empty();
-
     let msg_id = state.sf.gen();
 
     // if we avoided getting a database connection until now, do it now
@@ -108,10 +159,28 @@ pub async fn create_message(
     // Do not assume spans are valid after this call
     let modified_content = verify::verify(&t, &state, auth, room_id, perms, modified_content).await?;
 
-    let msg = insert_message(t, state.clone(), auth, room_id, msg_id, body, &modified_content, flags)
+    verify::verify_attachments(&t, &state, auth, body.attachments.iter().map(|&id| id.into())).await?;
+
+    let mut mentions = MessageMentions::extract(&modified_content, &md_utils::scan_markdown(&modified_content));
+    mentions.restrict(perms);
+
+    let mut flags = mentions.flags();
+
+    if webhook_perms.is_some() {
+        // webhooks may ping individual users, but not entire roles
+        mentions.roles.clear();
+        flags |= MessageFlags::WEBHOOK;
+    }
+
+    let msg = insert_message(t, state.clone(), auth, room_id, msg_id, body, &modified_content, flags, &mentions)
         .boxed()
         .await?;
 
+    // the message has already been sent, so failing to notify mentions shouldn't fail the request
+    if let Err(e) = mentions::notify_mentions(&state, &msg, &mentions).await {
+        log::warn!("Error notifying mentions of message {msg_id}: {e}");
+    }
+
     // message has been inserted, so fire off the embed processing
     if !spans.is_empty() && perms.contains(Permissions::EMBED_LINKS) {
         embed::process_embeds(state, msg_id, &modified_content, &spans);
@@ -130,6 +199,7 @@ pub(crate) async fn insert_message(
     body: &Archived<CreateMessageBody>,
     content: &str,
     flags: MessageFlags,
+    mentions: &MessageMentions,
 ) -> Result<Message, Error> {
     let flags = flags.bits();
 
@@ -165,6 +235,8 @@ pub(crate) async fn insert_message(
         }
     }
 
+    mentions::insert_mentions(&t, msg_id, room_id, mentions).await?;
+
     let msg = crate::internal::get_messages::get_one(state, &t, msg_id).await?;
 
     t.commit().await?;
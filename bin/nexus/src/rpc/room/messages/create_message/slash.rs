@@ -41,6 +41,11 @@ decl_patterns! {
     Gimme, Shrug, TableFlip, Unflip, Lenny, Disapprove, Me, Spoiler
 }
 
+/// Returns `true` if the name is taken by a built-in command, which always take precedence
+pub fn is_builtin(name: &str) -> bool {
+    Pattern::NAMES.contains(&name)
+}
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum Align {
     Left,
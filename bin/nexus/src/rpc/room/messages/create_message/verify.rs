@@ -5,6 +5,7 @@ use smallvec::SmallVec;
 
 use crate::prelude::*;
 use md_utils::SpanType;
+use schema::flags::FileFlags;
 
 pub async fn verify<'a>(
     t: &db::Transaction<'_>,
@@ -89,3 +90,59 @@ pub async fn verify<'a>(
 
     Ok(new_content.into())
 }
+
+/// Checks that each attachment is a completed upload by the author that isn't attached to any other message,
+/// and that there aren't too many of them.
+pub async fn verify_attachments(
+    t: &db::Transaction<'_>,
+    state: &ServerState,
+    auth: Authorization,
+    attachments: impl IntoIterator<Item = FileId>,
+) -> Result<(), Error> {
+    let mut file_ids: SmallVec<[FileId; 8]> = attachments.into_iter().collect();
+
+    if file_ids.is_empty() {
+        return Ok(());
+    }
+
+    let count = file_ids.len();
+
+    file_ids.sort_unstable();
+    file_ids.dedup();
+
+    if count != file_ids.len() || count > state.config().shared.max_attachments as usize {
+        return Err(Error::BadRequest);
+    }
+
+    let file_ids = file_ids.as_slice();
+
+    #[rustfmt::skip]
+    let rows = t.query2(schema::sql! {
+        SELECT
+            Files.UserId AS @UserId,
+            Files.Flags AS @Flags,
+            EXISTS(
+                SELECT FROM Attachments WHERE Attachments.FileId = Files.Id
+            ) AS @Attached
+        FROM Files
+        WHERE Files.Id = ANY(#{&file_ids as SNOWFLAKE_ARRAY})
+    }).await?;
+
+    if rows.len() != file_ids.len() {
+        return Err(Error::NotFound);
+    }
+
+    for row in rows {
+        let user_id: UserId = row.user_id()?;
+
+        if user_id != auth.user_id() || row.attached()? {
+            return Err(Error::Unauthorized);
+        }
+
+        if !FileFlags::from_bits_truncate(row.flags()?).contains(FileFlags::COMPLETE) {
+            return Err(Error::BadRequest);
+        }
+    }
+
+    Ok(())
+}
@@ -4,6 +4,8 @@ use sdk::models::*;
 
 use crate::{prelude::*, state::permission_cache::PermMute};
 
+use super::create_message::mentions::{self, MessageMentions};
+
 use sdk::api::commands::room::EditMessage;
 
 pub async fn edit_message(
@@ -115,6 +117,20 @@ pub async fn edit_message(
         // TODO: Reprocess embeds
     }
 
+    // only mentions that changed are touched, so unchanged mentions aren't notified again
+    let prev_mentions = match prev_content {
+        Some(prev_content) => MessageMentions::extract(prev_content, &md_utils::scan_markdown(prev_content)),
+        None => MessageMentions::default(),
+    };
+
+    let new_spans = md_utils::scan_markdown(&modified_content);
+    let mut new_mentions = MessageMentions::extract(&modified_content, &new_spans);
+    new_mentions.restrict(perms);
+
+    let added_mentions = new_mentions.difference(&prev_mentions);
+    let removed_mentions = prev_mentions.difference(&new_mentions);
+    let mention_flags = new_mentions.flags().bits();
+
     // this must go above the query futures creation to span their entire lexical lifetime
     let t = db.transaction().await?;
 
@@ -147,6 +163,12 @@ pub async fn edit_message(
             return Err(Error::Unauthorized);
         }
 
+        if new_set.len() > state.config().shared.max_attachments as usize {
+            return Err(Error::BadRequest);
+        }
+
+        super::create_message::verify::verify_attachments(&t, &state, auth, added.iter().copied()).await?;
+
         let removed = pre_set.difference(&new_set).copied().collect::<Vec<_>>();
 
         let t = &t; // hackery, can't take ownership of t within below async move blocks, so move a reference
@@ -183,8 +205,26 @@ pub async fn edit_message(
     // avoid reprocessing the message content if it's identical
     if prev_content.unwrap_or("") != modified_content {
         update_message = Either::Right(async {
+            let revision_id = state.sf.gen();
+
+            // keep the previous content around for moderation, until the revision retention expires
             t.execute2(schema::sql! {
-                UPDATE Messages SET (Content, EditedAt) = (NULLIF(#{&modified_content as Messages::Content}, ""), NOW())
+                INSERT INTO MessageRevisions (Id, MsgId, EditorId, Content) VALUES (
+                    #{&revision_id       as MessageRevisions::Id},
+                    #{&msg_id            as MessageRevisions::MsgId},
+                    #{auth.user_id_ref() as MessageRevisions::EditorId},
+                    #{&prev_content      as MessageRevisions::Content}
+                )
+            }).await?;
+
+            t.execute2(schema::sql! {
+                UPDATE Messages SET (Content, EditedAt, Flags) = (
+                    NULLIF(#{&modified_content as Messages::Content}, ""),
+                    NOW(),
+                    Messages.Flags
+                        & const {!MessageFlags::MENTIONS_EVERYONE.union(MessageFlags::MENTIONS_HERE).bits()}
+                        | #{&mention_flags as Messages::Flags}
+                )
                  WHERE Messages.Id = #{&msg_id as Messages::Id}
             }).await?;
 
@@ -192,11 +232,21 @@ pub async fn edit_message(
         });
     }
 
-    tokio::try_join!(add_attachments, orphan_attachments, update_message)?;
+    tokio::try_join!(
+        add_attachments,
+        orphan_attachments,
+        update_message,
+        mentions::insert_mentions(&t, msg_id, room_id, &added_mentions),
+        mentions::remove_mentions(&t, msg_id, &removed_mentions),
+    )?;
 
-    let msg = crate::internal::get_messages::get_one(state, &t, msg_id).await?;
+    let msg = crate::internal::get_messages::get_one(state.clone(), &t, msg_id).await?;
 
     t.commit().await?;
 
+    if let Err(e) = mentions::notify_mentions(&state, &msg, &added_mentions).await {
+        log::warn!("Error notifying mentions of message {msg_id}: {e}");
+    }
+
     Ok(Some(msg))
 }
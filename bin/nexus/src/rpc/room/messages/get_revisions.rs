@@ -0,0 +1,62 @@
+use sdk::{api::commands::all::GetMessageRevisions, models::*};
+
+use crate::prelude::*;
+
+/// Lists the previous versions of an edited message, oldest first.
+///
+/// Available to the author of the message, or members allowed to manage messages in the room.
+/// Deleted messages are included, so moderators can still see what was there.
+pub async fn get_revisions(
+    state: ServerState,
+    auth: Authorization,
+    cmd: &Archived<GetMessageRevisions>,
+) -> Result<impl Stream<Item = Result<MessageRevision, Error>>, Error> {
+    let room_id: RoomId = cmd.room_id.into();
+    let msg_id: MessageId = cmd.msg_id.into();
+
+    let db = state.db.read.get().await?;
+
+    let perms = crate::rpc::perm::get_cached_room_permissions_with_conn(&state, &db, auth.user_id(), room_id).await?;
+
+    if !perms.contains(Permissions::READ_MESSAGE_HISTORY) {
+        return Err(Error::Unauthorized);
+    }
+
+    #[rustfmt::skip]
+    let Some(row) = db.query_opt2(schema::sql! {
+        SELECT Messages.UserId AS @UserId FROM Messages
+        WHERE Messages.Id = #{&msg_id as Messages::Id}
+          AND Messages.RoomId = #{&room_id as Messages::RoomId}
+    }).await? else {
+        return Err(Error::NotFound);
+    };
+
+    let author_id: UserId = row.user_id()?;
+
+    if author_id != auth.user_id() && !perms.contains(Permissions::MANAGE_MESSAGES) {
+        return Err(Error::Unauthorized);
+    }
+
+    #[rustfmt::skip]
+    let stream = db.query_stream2(schema::sql! {
+        const_assert!(!Columns::IS_DYNAMIC);
+
+        SELECT
+            MessageRevisions.Id         AS @_,
+            MessageRevisions.EditorId   AS @_,
+            MessageRevisions.Content    AS @_
+        FROM MessageRevisions
+        WHERE MessageRevisions.MsgId = #{&msg_id as MessageRevisions::MsgId}
+        ORDER BY MessageRevisions.Id ASC
+    }).await?;
+
+    Ok(stream.map(move |row| match row {
+        Err(e) => Err(Error::from(e)),
+        Ok(row) => Ok(MessageRevision {
+            id: row.message_revisions_id()?,
+            msg_id,
+            editor_id: row.message_revisions_editor_id()?,
+            content: row.message_revisions_content()?,
+        }),
+    }))
+}
@@ -1,5 +1,6 @@
-use crate::{prelude::*, util::encrypted_asset::encrypt_snowflake_opt};
+use crate::{gateway::task::event_processors::emit, prelude::*, util::encrypted_asset::encrypt_snowflake_opt};
 use common::emoji::EmoteOrEmojiId;
+use schema::flags::RoomMemberFlags;
 
 use sdk::{
     api::commands::all::PutReaction,
@@ -20,7 +21,7 @@ pub async fn add_reaction(
 
     let perms = state.perm_cache.get(auth.user_id(), room_id).await;
 
-    if matches!(perms, Some(perms) if !perms.contains(Permissions::ADD_REACTIONS)) {
+    if matches!(perms, Some(perms) if !perms.contains(Permissions::ADD_REACTIONS) || perms.flags.contains(RoomMemberFlags::MUTED)) {
         return Err(Error::Unauthorized);
     }
 
@@ -138,6 +139,7 @@ pub async fn add_reaction(
                 let add_reactions = Permissions::ADD_REACTIONS.to_i64();
                 AND (Rooms.Permissions1 & {add_reactions[0]} = {add_reactions[0]})
                 AND (Rooms.Permissions2 & {add_reactions[1]} = {add_reactions[1]})
+                AND NOT Rooms.Muted // timed out members cannot react
             }
         ),
 
@@ -279,7 +281,7 @@ pub async fn add_reaction(
             })),
         });
 
-        state.gateway.events.send(&ServerEvent::party(party_id, Some(room_id), event)).await?;
+        emit(&state, ServerEvent::party(party_id, Some(room_id), event)).await?;
     }
 
     Ok(())
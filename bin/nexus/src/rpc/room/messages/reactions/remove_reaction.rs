@@ -1,4 +1,4 @@
-use crate::prelude::*;
+use crate::{gateway::task::event_processors::emit, prelude::*};
 
 use common::emoji::EmoteOrEmojiId;
 use sdk::{
@@ -89,7 +89,7 @@ pub async fn remove_own_reaction(
     };
 
     #[rustfmt::skip]
-    state.gateway.events.send(&ServerEvent::party(party_id, Some(room_id), ServerMsg::new_message_reaction_remove(UserReactionEvent {
+    emit(&state, ServerEvent::party(party_id, Some(room_id), ServerMsg::new_message_reaction_remove(UserReactionEvent {
         emote,
         msg_id,
         room_id,
@@ -3,8 +3,12 @@ use hashbrown::HashSet;
 use crate::asset::{maybe_add_asset, AssetMode};
 use crate::prelude::*;
 
-use crate::internal::role_overwrites::RawOverwrites;
+use crate::internal::{
+    audit::{AuditChanges, AuditEntry},
+    role_overwrites::RawOverwrites,
+};
 
+use schema::audit::AuditAction;
 use sdk::models::*;
 
 use sdk::api::commands::all::PatchRoom;
@@ -170,7 +174,9 @@ pub async fn modify_room(
     }
 
     #[rustfmt::skip]
-    let res = t.execute2(schema::sql! {
+    let row = t.query_opt2(schema::sql! {
+        type OldRooms = Rooms;
+
         UPDATE Rooms SET
             if name.is_some()             { Rooms./Name     = #{&name       as Rooms::Name}, }
             if position.is_some()         { Rooms./Position = #{&position   as Rooms::Position}, }
@@ -181,14 +187,58 @@ pub async fn modify_room(
                 Some(false) => { Rooms./Flags & ~const {RoomFlags::NSFW.bits()} },
                 None        => { Rooms./Flags }
             }
+        FROM Rooms AS OldRooms
         WHERE Rooms.Id = #{&room_id as Rooms::Id}
+          AND OldRooms.Id = Rooms.Id
+        RETURNING
+            Rooms.PartyId       AS @PartyId,
+            Rooms.Name          AS @Name,
+            Rooms.Position      AS @Position,
+            Rooms.Topic         AS @Topic,
+            Rooms.AvatarId      AS @AvatarId,
+            Rooms.Flags         AS @Flags,
+            OldRooms.Name       AS @OldName,
+            OldRooms.Position   AS @OldPosition,
+            OldRooms.Topic      AS @OldTopic,
+            OldRooms.AvatarId   AS @OldAvatarId,
+            OldRooms.Flags      AS @OldFlags
     }).await?;
 
-    if res != 1 {
+    let Some(row) = row else {
         t.rollback().await?;
 
         return Err(Error::InternalErrorStatic("Unable to update room"));
+    };
+
+    let mut changes = AuditChanges::default();
+    changes
+        .diff("name", row.old_name::<SmolStr>()?, row.name()?)
+        .diff("position", row.old_position::<i16>()?, row.position()?)
+        .diff("topic", row.old_topic::<Option<SmolStr>>()?, row.topic()?)
+        .diff("avatar", row.old_avatar_id::<Option<FileId>>()?, row.avatar_id()?)
+        .diff("flags", row.old_flags::<RoomFlags>()?, row.flags()?);
+
+    // overwrites are only listed by id, as their values can be large
+    if !raw.id.is_empty() {
+        changes.set("overwrites", None, &raw.id);
+    }
+
+    if !form.remove_overwrites.is_empty() {
+        let removed: Vec<Snowflake> = form.remove_overwrites.as_slice().iter().copied().map(From::from).collect();
+
+        changes.set("remove_overwrites", None, removed);
+    }
+
+    AuditEntry {
+        party_id: row.party_id()?,
+        user_id: auth.user_id(),
+        target_id: Some(room_id),
+        action: AuditAction::RoomUpdate,
+        reason: None,
+        changes,
     }
+    .record(&state, &t)
+    .await?;
 
     t.commit().await?;
 
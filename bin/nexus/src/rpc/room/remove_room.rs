@@ -1,4 +1,6 @@
+use crate::internal::audit::AuditEntry;
 use crate::prelude::*;
+use schema::audit::AuditAction;
 use sdk::api::commands::all::DeleteRoom;
 use sdk::models::*;
 
@@ -13,7 +15,7 @@ pub async fn remove_room(
     let t = db.transaction().await?;
 
     #[rustfmt::skip]
-    let res = t.execute2(schema::sql! {
+    let row = t.query_opt2(schema::sql! {
         const_assert!(!Columns::IS_DYNAMIC);
 
         struct PendingRoom { Id: Rooms::Id }
@@ -31,13 +33,25 @@ pub async fn remove_room(
         )
         UPDATE Rooms SET (DeletedAt) = now()
         FROM PendingRoom WHERE Rooms.Id = PendingRoom.Id
+        RETURNING Rooms.PartyId AS @PartyId
     }).await?;
 
-    if res != 1 {
+    let Some(row) = row else {
         t.rollback().await?;
 
         return Err(Error::Unauthorized);
+    };
+
+    AuditEntry {
+        party_id: row.party_id()?,
+        user_id: auth.user_id(),
+        target_id: Some(room_id),
+        action: AuditAction::RoomDelete,
+        reason: None,
+        changes: Default::default(),
     }
+    .record(&state, &t)
+    .await?;
 
     t.commit().await?;
 
@@ -0,0 +1,149 @@
+use std::time::{Duration, SystemTime};
+
+use schema::{flags::InteractionFlags, SnowflakeExt};
+use sdk::{
+    api::commands::all::RespondToInteraction,
+    models::{gateway::message::ServerMsg, *},
+};
+
+use crate::{gateway::task::event_processors::emit, prelude::*};
+
+/// How long a bot has to respond to, or defer, an interaction
+pub const RESPONSE_WINDOW: Duration = Duration::from_secs(60);
+
+/// How long a bot has to respond to an interaction after deferring it, counting from the invocation
+pub const DEFERRED_WINDOW: Duration = Duration::from_secs(60 * 15);
+
+/// Responds to a slash command invocation as the bot that owns the command.
+///
+/// A deferred response tells the invoking user the bot is working on it, and extends the time allowed for the
+/// final response. Ephemeral responses are only shown to the invoking user and not stored, otherwise the
+/// response is posted to the room as a regular message by the bot.
+pub async fn respond_interaction(
+    state: ServerState,
+    auth: Authorization,
+    cmd: &Archived<RespondToInteraction>,
+) -> Result<Option<Message>, Error> {
+    if !auth.is_bot() {
+        return Err(Error::Unauthorized);
+    }
+
+    let room_id: RoomId = cmd.room_id.into();
+    let interaction_id: Snowflake = cmd.interaction_id.into();
+    let form = &cmd.body;
+
+    let content = form.message.content.as_str().trim();
+
+    if form.deferred {
+        // deferring only acknowledges the interaction, the response itself comes later
+        if !content.is_empty() || !form.message.attachments.is_empty() || !form.message.embeds.is_empty() {
+            return Err(Error::BadRequest);
+        }
+    }
+
+    // ephemeral responses are never stored, so they are only checked here
+    let ephemeral_content = match form.ephemeral && !form.deferred {
+        false => None,
+        true if !form.message.attachments.is_empty() => return Err(Error::BadRequest),
+        true => {
+            let config = state.config();
+
+            let trimmed = md_utils::trim_message(
+                content,
+                Some(md_utils::TrimLimits {
+                    len: config.shared.message_length.clone(),
+                    max_newlines: config.shared.max_newlines as usize,
+                }),
+            );
+
+            match trimmed {
+                Some(content) if !content.is_empty() => Some(SmolStr::from(&*content)),
+                _ => return Err(Error::BadRequest),
+            }
+        }
+    };
+
+    let now = SystemTime::now();
+
+    // interaction ids double as the time of invocation
+    if interaction_id < Snowflake::at(now - DEFERRED_WINDOW) {
+        return Err(Error::NotFound);
+    }
+
+    let initial_cutoff = Snowflake::at(now - RESPONSE_WINDOW);
+
+    let (claim, conflicts) = match form.deferred {
+        true => (InteractionFlags::DEFERRED, InteractionFlags::DEFERRED | InteractionFlags::RESPONDED),
+        false => (InteractionFlags::RESPONDED, InteractionFlags::RESPONDED),
+    };
+
+    let (claim, conflicts) = (claim.bits(), conflicts.bits());
+
+    const DEFERRED: i16 = InteractionFlags::DEFERRED.bits();
+
+    // claims the interaction first, so concurrent responses can't both succeed
+    #[rustfmt::skip]
+    let Some(row) = state.db.write.get().await?.query_opt2(schema::sql! {
+        UPDATE Interactions SET (Flags) = (Interactions.Flags | #{&claim as Interactions::Flags})
+        WHERE Interactions.Id = #{&interaction_id as Interactions::Id}
+          AND Interactions.RoomId = #{&room_id as Interactions::RoomId}
+          AND Interactions.Flags & #{&conflicts as Interactions::Flags} = 0
+          AND (
+            Interactions.Flags & const {DEFERRED} = const {DEFERRED}
+            OR Interactions.Id >= #{&initial_cutoff as Interactions::Id}
+          )
+          AND Interactions.CommandId IN (
+            SELECT AppCommands.Id
+            FROM AppCommands INNER JOIN Apps ON Apps.Id = AppCommands.AppId
+            WHERE Apps.BotId = #{auth.user_id_ref() as Apps::BotId}
+          )
+        RETURNING Interactions.UserId AS @UserId
+    }).await? else {
+        return Err(Error::NotFound);
+    };
+
+    let user_id: UserId = row.user_id()?;
+
+    if form.deferred || form.ephemeral {
+        let event = ServerMsg::new_interaction_response(InteractionResponseEvent {
+            interaction_id,
+            room_id,
+            bot_id: auth.user_id(),
+            deferred: form.deferred,
+            content: ephemeral_content,
+        });
+
+        // only the invoking user sees these
+        emit(&state, ServerEvent::user(user_id, Some(room_id), event)).await?;
+
+        return Ok(None);
+    }
+
+    // the bot posts in its own right, so its own permissions in the room apply
+    let res = crate::rpc::room::messages::create_message::create_message_body(
+        state.clone(),
+        auth,
+        room_id,
+        &form.message,
+    )
+    .await;
+
+    // let the bot try again if the message was rejected
+    if res.is_err() {
+        unclaim(&state, interaction_id, claim).await?;
+    }
+
+    res
+}
+
+async fn unclaim(state: &ServerState, interaction_id: Snowflake, claim: i16) -> Result<(), Error> {
+    let keep = !claim;
+
+    #[rustfmt::skip]
+    state.db.write.get().await?.execute2(schema::sql! {
+        UPDATE Interactions SET (Flags) = (Interactions.Flags & #{&keep as Interactions::Flags})
+        WHERE Interactions.Id = #{&interaction_id as Interactions::Id}
+    }).await?;
+
+    Ok(())
+}
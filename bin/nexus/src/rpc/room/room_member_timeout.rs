@@ -0,0 +1,87 @@
+use std::time::{Duration, SystemTime};
+
+use schema::{audit::AuditAction, roles::UserAction};
+use sdk::{api::commands::room::TimeoutRoomMember, models::*};
+
+use crate::{
+    internal::audit::{AuditChanges, AuditEntry},
+    prelude::*,
+    rpc::party::party_member_timeout::MAX_TIMEOUT,
+};
+
+/// Applies, changes or lifts (if no duration is given) a timeout for a single room
+pub async fn timeout_room_member(
+    state: ServerState,
+    auth: Authorization,
+    cmd: &Archived<TimeoutRoomMember>,
+) -> Result<(), Error> {
+    let room_id: RoomId = cmd.room_id.into();
+    let member_id: UserId = cmd.member_id.into();
+
+    let mute_expires = match cmd.body.duration.as_ref() {
+        Some(secs) if secs.to_native() == 0 => return Err(Error::BadRequest),
+        Some(secs) => {
+            Some(SystemTime::now() + Duration::from_secs(secs.to_native() as u64).min(MAX_TIMEOUT))
+        }
+        None => None,
+    };
+
+    let mut db = state.db.write.get().await?;
+    let t = db.transaction().await?;
+
+    #[rustfmt::skip]
+    let Some(row) = t.query_opt2(schema::sql! {
+        SELECT LiveRooms.PartyId AS @PartyId
+        FROM LiveRooms WHERE LiveRooms.Id = #{&room_id as Rooms::Id}
+    }).await? else {
+        t.rollback().await?;
+
+        return Err(Error::NotFound);
+    };
+
+    let party_id: PartyId = row.party_id()?;
+
+    crate::internal::moderation::check_member_action(&t, party_id, auth.user_id(), member_id, UserAction::Mute)
+        .await?;
+
+    // NOTE: The room member mute triggers will emit the member_updated event
+    #[rustfmt::skip]
+    let res = t.execute2(schema::sql! {
+        INSERT INTO RoomMembers (UserId, RoomId, MuteExpires) (
+            SELECT PartyMembers.UserId, #{&room_id as Rooms::Id}, #{&mute_expires as RoomMembers::MuteExpires}
+            FROM PartyMembers
+            WHERE PartyMembers.PartyId = #{&party_id as Party::Id}
+              AND PartyMembers.UserId = #{&member_id as Users::Id}
+        )
+        ON CONFLICT (RoomMembers./RoomId, RoomMembers./UserId) DO UPDATE RoomMembers SET (MuteExpires) = (
+            #{&mute_expires as RoomMembers::MuteExpires}
+        )
+    }).await?;
+
+    if res == 0 {
+        t.rollback().await?;
+
+        return Err(Error::NotFound);
+    }
+
+    let mut changes = AuditChanges::default();
+    changes.set("room_id", None, room_id);
+    changes.set("duration", None, cmd.body.duration.as_ref().map(|secs| secs.to_native()));
+
+    AuditEntry {
+        party_id,
+        user_id: auth.user_id(),
+        target_id: Some(member_id),
+        action: AuditAction::RoomMemberTimeout,
+        reason: None,
+        changes,
+    }
+    .record(&state, &t)
+    .await?;
+
+    t.commit().await?;
+
+    state.perm_cache.remove(member_id, room_id).await;
+
+    Ok(())
+}
@@ -1,7 +1,8 @@
+use schema::flags::RoomMemberFlags;
 use sdk::models::*;
 
-use crate::prelude::*;
 use crate::util::encrypted_asset::encrypt_snowflake_opt;
+use crate::{gateway::task::event_processors::emit, prelude::*};
 
 use sdk::api::commands::all::StartTyping;
 use sdk::models::gateway::message::ServerMsg;
@@ -19,6 +20,11 @@ pub async fn trigger_typing(
                 return Err(Error::NotFound);
             }
 
+            // timed out members shouldn't appear to be typing
+            if perms.flags.contains(RoomMemberFlags::MUTED) {
+                return Err(Error::Unauthorized);
+            }
+
             true
         }
         _ => false,
@@ -40,6 +46,7 @@ pub async fn trigger_typing(
 
                 let perms = Permissions::SEND_MESSAGES.to_i64();
                 AND AggRoomPerms.Permissions1 & {perms[0]} = {perms[0]}
+                AND NOT AggRoomPerms.Muted
             }
         )
 
@@ -121,7 +128,7 @@ pub async fn trigger_typing(
         parent: cmd.body.parent.deserialize_simple().expect("Unable to deserialize parent"),
     });
 
-    state.gateway.events.send(&ServerEvent::party(party_id, Some(room_id), event)).await?;
+    emit(&state, ServerEvent::party(party_id, Some(room_id), event)).await?;
 
     Ok(())
 }
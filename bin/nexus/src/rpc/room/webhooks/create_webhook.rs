@@ -0,0 +1,178 @@
+use schema::audit::AuditAction;
+use sdk::{api::commands::all::CreateWebhook, models::*};
+
+use crate::{
+    asset::{maybe_add_asset, AssetMode},
+    internal::audit::{AuditChanges, AuditEntry},
+    prelude::*,
+    util::encrypted_asset::encrypt_snowflake,
+};
+
+/// Maximum number of webhooks a single room may have
+pub const MAX_WEBHOOKS: i64 = 10;
+
+/// Messages per minute allowed when no rate limit is given
+pub const DEFAULT_RATE_LIMIT: u16 = 30;
+
+/// Highest rate limit that may be set, in messages per minute
+pub const MAX_RATE_LIMIT: u16 = 120;
+
+/// Encodes a stored webhook token as it appears in the webhook's URL
+pub fn encode_token(token: &[u8]) -> Result<FixedStr<22>, Error> {
+    match <[u8; 16]>::try_from(token) {
+        Ok(token) => Ok(util::base64::encode_u128(u128::from_le_bytes(token))),
+        Err(_) => Err(Error::InternalErrorStatic("Invalid Webhook Token")),
+    }
+}
+
+/// Creates an incoming webhook for a room, along with the user its messages are posted as.
+pub async fn create_webhook(
+    state: ServerState,
+    auth: Authorization,
+    cmd: &Archived<CreateWebhook>,
+) -> Result<Webhook, Error> {
+    let room_id: RoomId = cmd.room_id.into();
+    let form = &cmd.body;
+
+    let name = form.name.as_deref().unwrap_or("Webhook");
+
+    if !schema::validation::validate_username(name, state.config().shared.username_length.clone()) {
+        return Err(Error::InvalidUsername);
+    }
+
+    let rate_limit = form.rate_limit.as_ref().map(|r| *r as u16).unwrap_or(DEFAULT_RATE_LIMIT);
+
+    if rate_limit == 0 || rate_limit > MAX_RATE_LIMIT {
+        return Err(Error::BadRequest);
+    }
+
+    #[rustfmt::skip]
+    let Some(row) = state.db.read.get().await?.query_opt2(schema::sql! {
+        const_assert!(!Columns::IS_DYNAMIC);
+
+        SELECT
+            Rooms.PartyId AS @PartyId,
+            (SELECT COUNT(Webhooks.Id) FROM Webhooks WHERE Webhooks.RoomId = Rooms.Id) AS @TotalWebhooks
+        FROM LiveRooms AS Rooms INNER JOIN PartyMembers ON PartyMembers.PartyId = Rooms.PartyId
+        WHERE Rooms.Id = #{&room_id as Rooms::Id}
+          AND PartyMembers.UserId = #{auth.user_id_ref() as Users::Id}
+
+        const PERMS: [i64; 2] = Permissions::MANAGE_WEBHOOKS.to_i64();
+        const_assert!(PERMS[1] == 0);
+
+        AND PartyMembers.Permissions1 & const {PERMS[0]} = const {PERMS[0]}
+    }).await? else {
+        return Err(Error::Unauthorized);
+    };
+
+    if row.total_webhooks::<i64>()? >= MAX_WEBHOOKS {
+        return Err(Error::BadRequest);
+    }
+
+    let party_id: PartyId = row.party_id()?;
+
+    let avatar_id = match form.avatar.as_ref() {
+        Some(&file_id) => {
+            maybe_add_asset(&state, AssetMode::Avatar, auth.user_id(), Nullable::Some(file_id.into())).await?
+        }
+        None => Nullable::Null,
+    };
+
+    let webhook_id = state.sf.gen();
+    let user_id = state.sf.gen();
+
+    let token: [u8; 16] = util::rng::crypto_thread_rng().gen_bytes();
+    let token_bytes = &token[..];
+
+    // webhook users cannot log in, but emails must still be unique
+    let email = format!("{user_id}@webhooks.invalid");
+    let dob = Timestamp::UNIX_EPOCH.date();
+    let user_flags = UserFlags::empty().with_elevation(ElevationLevel::Bot).bits();
+    let db_rate_limit = rate_limit as i16;
+
+    let mut db = state.db.write.get().await?;
+    let t = db.transaction().await?;
+
+    #[rustfmt::skip]
+    t.execute2(schema::sql! {
+        CALL .register_user(
+            #{&user_id  as Users::Id},
+            #{&name     as Users::Username},
+            #{&email    as Users::Email},
+            #{&""       as Users::Passhash},
+            #{&dob      as Users::Dob}
+        )
+    }).await?;
+
+    #[rustfmt::skip]
+    t.execute2(schema::sql! {
+        UPDATE Users SET (Flags) = (#{&user_flags as Users::Flags})
+        WHERE Users.Id = #{&user_id as Users::Id}
+    }).await?;
+
+    // the avatar is shown through the webhook user's base profile
+    if let Nullable::Some(ref avatar_id) = avatar_id {
+        #[rustfmt::skip]
+        t.execute2(schema::sql! {
+            INSERT INTO Profiles (UserId, AvatarId) VALUES (
+                #{&user_id  as Profiles::UserId},
+                #{avatar_id as Profiles::AvatarId}
+            )
+        }).await?;
+    }
+
+    #[rustfmt::skip]
+    let res = t.execute2(schema::sql! {
+        INSERT INTO Webhooks (Id, RoomId, UserId, CreatorId, RateLimit, Token) (
+            SELECT
+                #{&webhook_id           as Webhooks::Id},
+                #{&room_id              as Webhooks::RoomId},
+                #{&user_id              as Webhooks::UserId},
+                #{auth.user_id_ref()    as Webhooks::CreatorId},
+                #{&db_rate_limit        as Webhooks::RateLimit},
+                #{&token_bytes          as Webhooks::Token}
+            // re-check the limit within the transaction to avoid racing other inserts
+            WHERE (SELECT COUNT(Webhooks.Id) FROM Webhooks
+                WHERE Webhooks.RoomId = #{&room_id as Rooms::Id}) < #{&MAX_WEBHOOKS as Type::INT8}
+        )
+    }).await?;
+
+    if res != 1 {
+        t.rollback().await?;
+
+        return Err(Error::BadRequest);
+    }
+
+    let mut changes = AuditChanges::default();
+    changes.set("name", None, name);
+    changes.set("rate_limit", None, rate_limit);
+
+    AuditEntry {
+        party_id,
+        user_id: auth.user_id(),
+        target_id: Some(webhook_id),
+        action: AuditAction::WebhookCreate,
+        reason: None,
+        changes,
+    }
+    .record(&state, &t)
+    .await?;
+
+    t.commit().await?;
+
+    log::debug!("Created webhook {webhook_id} in room {room_id} for {}", auth.user_id());
+
+    Ok(Webhook {
+        id: webhook_id,
+        room_id,
+        user_id,
+        creator_id: Some(auth.user_id()),
+        name: SmolStr::from(name),
+        avatar: match avatar_id {
+            Nullable::Some(id) => Some(encrypt_snowflake(&state, id)),
+            _ => None,
+        },
+        rate_limit,
+        token: encode_token(token_bytes)?,
+    })
+}
@@ -0,0 +1,81 @@
+use std::time::{Duration, SystemTime};
+
+use sdk::{api::commands::room::CreateMessageBody, models::*};
+
+use crate::{prelude::*, rpc::room::messages::create_message::create_webhook_message};
+
+/// Posts a message to a webhook's room as the webhook's user, if the token matches.
+///
+/// Webhooks are not room members, so they may only send content and embeds,
+/// and never mention `@everyone`, `@here` or roles.
+pub async fn execute_webhook(
+    state: ServerState,
+    webhook_id: Snowflake,
+    token: u128,
+    body: &Archived<CreateMessageBody>,
+) -> Result<(), Error> {
+    // webhooks have no uploads of their own to attach
+    if !body.attachments.is_empty() {
+        return Err(Error::BadRequest);
+    }
+
+    let token = token.to_le_bytes();
+    let token_bytes = &token[..];
+
+    let now = SystemTime::now();
+    let cutoff = now - Duration::from_secs(60);
+
+    let db = state.db.write.get().await?;
+
+    // claims a slot in the rate-limit window with a single conditional update, so concurrent
+    // requests cannot all pass the check before any of their messages are counted
+    #[rustfmt::skip]
+    let row = db.query_opt2(schema::sql! {
+        UPDATE Webhooks SET (WindowStart, WindowCount) = (
+            CASE WHEN Webhooks.WindowStart < #{&cutoff as Webhooks::WindowStart}
+                THEN #{&now as Webhooks::WindowStart} ELSE Webhooks.WindowStart END,
+            CASE WHEN Webhooks.WindowStart < #{&cutoff as Webhooks::WindowStart}
+                THEN 1 ELSE Webhooks.WindowCount + 1 END
+        )
+        FROM LiveRooms AS Rooms
+        WHERE Webhooks.Id = #{&webhook_id as Webhooks::Id}
+          AND Webhooks.Token = #{&token_bytes as Webhooks::Token}
+          AND Rooms.Id = Webhooks.RoomId
+          AND (Webhooks.WindowStart < #{&cutoff as Webhooks::WindowStart}
+            OR Webhooks.WindowCount < Webhooks.RateLimit)
+        RETURNING
+            Webhooks.RoomId AS @RoomId,
+            Webhooks.UserId AS @UserId
+    }).await?;
+
+    let Some(row) = row else {
+        // tell apart a spent window from a bad token or deleted room
+        #[rustfmt::skip]
+        let exists = db.query_opt2(schema::sql! {
+            SELECT FROM Webhooks INNER JOIN LiveRooms AS Rooms ON Rooms.Id = Webhooks.RoomId
+            WHERE Webhooks.Id = #{&webhook_id as Webhooks::Id}
+              AND Webhooks.Token = #{&token_bytes as Webhooks::Token}
+        }).await?;
+
+        return Err(match exists {
+            Some(_) => Error::TemporarilyDisabled,
+            None => Error::Unauthorized,
+        });
+    };
+
+    drop(db);
+
+    let room_id: RoomId = row.room_id()?;
+
+    // webhook users have no sessions, so this only identifies them as the author
+    let auth = Authorization::Bot {
+        bot_id: row.user_id()?,
+        issued: Timestamp::now_utc(),
+    };
+
+    let perms = Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS;
+
+    create_webhook_message(state, auth, room_id, body, perms).await?;
+
+    Ok(())
+}
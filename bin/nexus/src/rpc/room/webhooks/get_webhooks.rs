@@ -0,0 +1,56 @@
+use sdk::{api::commands::all::GetWebhooks, models::*};
+
+use crate::{prelude::*, util::encrypted_asset::encrypt_snowflake_opt};
+
+use super::create_webhook::encode_token;
+
+/// Lists the webhooks of a room, including their tokens, for members allowed to manage them
+pub async fn get_webhooks(
+    state: ServerState,
+    auth: Authorization,
+    cmd: &Archived<GetWebhooks>,
+) -> Result<impl Stream<Item = Result<Webhook, Error>>, Error> {
+    let room_id: RoomId = cmd.room_id.into();
+
+    #[rustfmt::skip]
+    let stream = state.db.read.get().await?.query_stream2(schema::sql! {
+        const_assert!(!Columns::IS_DYNAMIC);
+
+        SELECT
+            Webhooks.Id         AS @_,
+            Webhooks.UserId     AS @_,
+            Webhooks.CreatorId  AS @_,
+            Webhooks.RateLimit  AS @_,
+            Webhooks.Token      AS @_,
+            Users.Username      AS @_,
+            Profiles.AvatarId   AS @_
+        FROM Webhooks
+            INNER JOIN LiveRooms AS Rooms ON Rooms.Id = Webhooks.RoomId
+            INNER JOIN PartyMembers ON PartyMembers.PartyId = Rooms.PartyId
+            INNER JOIN Users ON Users.Id = Webhooks.UserId
+            LEFT JOIN Profiles ON Profiles.UserId = Webhooks.UserId AND Profiles.PartyId IS NULL
+        WHERE Webhooks.RoomId = #{&room_id as Rooms::Id}
+          AND PartyMembers.UserId = #{auth.user_id_ref() as Users::Id}
+
+        const PERMS: [i64; 2] = Permissions::MANAGE_WEBHOOKS.to_i64();
+        const_assert!(PERMS[1] == 0);
+
+        AND PartyMembers.Permissions1 & const {PERMS[0]} = const {PERMS[0]}
+
+        ORDER BY Webhooks.Id ASC
+    }).await?;
+
+    Ok(stream.map(move |row| match row {
+        Err(e) => Err(Error::from(e)),
+        Ok(row) => Ok(Webhook {
+            id: row.webhooks_id()?,
+            room_id,
+            user_id: row.webhooks_user_id()?,
+            creator_id: row.webhooks_creator_id()?,
+            name: row.users_username()?,
+            avatar: encrypt_snowflake_opt(&state, row.profiles_avatar_id()?),
+            rate_limit: row.webhooks_rate_limit::<i16>()? as u16,
+            token: encode_token(row.webhooks_token()?)?,
+        }),
+    }))
+}
@@ -0,0 +1,162 @@
+use schema::audit::AuditAction;
+use sdk::{
+    api::commands::all::{PatchWebhook, PatchWebhookForm},
+    models::*,
+};
+
+use crate::{
+    asset::{maybe_add_asset, AssetMode},
+    internal::audit::{AuditChanges, AuditEntry},
+    prelude::*,
+    util::encrypted_asset::encrypt_snowflake_opt,
+};
+
+use super::create_webhook::{encode_token, MAX_RATE_LIMIT};
+
+pub async fn modify_webhook(
+    state: ServerState,
+    auth: Authorization,
+    cmd: &Archived<PatchWebhook>,
+) -> Result<Webhook, Error> {
+    let room_id: RoomId = cmd.room_id.into();
+    let webhook_id: Snowflake = cmd.webhook_id.into();
+    let form = &cmd.body;
+
+    if *form == PatchWebhookForm::default() {
+        return Err(Error::BadRequest);
+    }
+
+    if matches!(form.name.as_deref(), Some(name) if !schema::validation::validate_username(name, state.config().shared.username_length.clone()))
+    {
+        return Err(Error::InvalidUsername);
+    }
+
+    let rate_limit = form.rate_limit.as_ref().map(|r| *r as u16);
+
+    if matches!(rate_limit, Some(r) if r == 0 || r > MAX_RATE_LIMIT) {
+        return Err(Error::BadRequest);
+    }
+
+    // leave the avatar alone unless given
+    let avatar_id = match form.avatar.convert() {
+        Nullable::Undefined => Nullable::Undefined,
+        avatar => maybe_add_asset(&state, AssetMode::Avatar, auth.user_id(), avatar).await?,
+    };
+
+    let mut db = state.db.write.get().await?;
+    let t = db.transaction().await?;
+
+    #[rustfmt::skip]
+    let Some(hook) = t.query_opt2(schema::sql! {
+        const_assert!(!Columns::IS_DYNAMIC);
+
+        SELECT
+            Rooms.PartyId       AS @PartyId,
+            Webhooks.UserId     AS @_,
+            Webhooks.CreatorId  AS @_,
+            Webhooks.RateLimit  AS @_,
+            Webhooks.Token      AS @_,
+            Users.Username      AS @_,
+            Profiles.AvatarId   AS @_
+        FROM Webhooks
+            INNER JOIN LiveRooms AS Rooms ON Rooms.Id = Webhooks.RoomId
+            INNER JOIN PartyMembers ON PartyMembers.PartyId = Rooms.PartyId
+            INNER JOIN Users ON Users.Id = Webhooks.UserId
+            LEFT JOIN Profiles ON Profiles.UserId = Webhooks.UserId AND Profiles.PartyId IS NULL
+        WHERE Webhooks.Id = #{&webhook_id as Webhooks::Id}
+          AND Webhooks.RoomId = #{&room_id as Rooms::Id}
+          AND PartyMembers.UserId = #{auth.user_id_ref() as Users::Id}
+
+        const PERMS: [i64; 2] = Permissions::MANAGE_WEBHOOKS.to_i64();
+        const_assert!(PERMS[1] == 0);
+
+        AND PartyMembers.Permissions1 & const {PERMS[0]} = const {PERMS[0]}
+    }).await? else {
+        t.rollback().await?;
+
+        return Err(Error::NotFound);
+    };
+
+    let user_id: UserId = hook.webhooks_user_id()?;
+    let old_name: SmolStr = hook.users_username()?;
+    let old_rate_limit = hook.webhooks_rate_limit::<i16>()? as u16;
+    let mut new_avatar_id: Option<Snowflake> = hook.profiles_avatar_id()?;
+
+    if let Some(rate_limit) = rate_limit {
+        let rate_limit = rate_limit as i16;
+
+        #[rustfmt::skip]
+        t.execute2(schema::sql! {
+            UPDATE Webhooks SET (RateLimit) = (#{&rate_limit as Webhooks::RateLimit})
+            WHERE Webhooks.Id = #{&webhook_id as Webhooks::Id}
+        }).await?;
+    }
+
+    // renaming the webhook user may assign a new discriminator
+    if let Some(name) = form.name.as_deref() {
+        #[rustfmt::skip]
+        t.execute2(schema::sql! {
+            CALL .update_user(
+                #{&user_id as Users::Id},
+                #{&name    as Users::Username},
+                NULL,
+                NULL
+            )
+        }).await?;
+    }
+
+    // the avatar is shown through the webhook user's base profile
+    if !avatar_id.is_undefined() {
+        #[rustfmt::skip]
+        t.execute2(schema::sql! {
+            INSERT INTO Profiles (UserId, AvatarId) VALUES (
+                #{&user_id   as Profiles::UserId},
+                #{&avatar_id as Profiles::AvatarId}
+            )
+            ON CONFLICT (Profiles./UserId, Profiles./PartyId) DO UPDATE
+                SET Profiles./AvatarId = #{&avatar_id as Profiles::AvatarId}
+        }).await?;
+
+        new_avatar_id = match avatar_id {
+            Nullable::Some(id) => Some(id),
+            _ => None,
+        };
+    }
+
+    let mut changes = AuditChanges::default();
+
+    if let Some(name) = form.name.as_deref() {
+        changes.diff("name", &*old_name, name);
+    }
+
+    if let Some(rate_limit) = rate_limit {
+        changes.diff("rate_limit", old_rate_limit, rate_limit);
+    }
+
+    AuditEntry {
+        party_id: hook.party_id()?,
+        user_id: auth.user_id(),
+        target_id: Some(webhook_id),
+        action: AuditAction::WebhookUpdate,
+        reason: None,
+        changes,
+    }
+    .record(&state, &t)
+    .await?;
+
+    t.commit().await?;
+
+    Ok(Webhook {
+        id: webhook_id,
+        room_id,
+        user_id,
+        creator_id: hook.webhooks_creator_id()?,
+        name: match form.name.as_deref() {
+            Some(name) => SmolStr::from(name),
+            None => old_name,
+        },
+        avatar: encrypt_snowflake_opt(&state, new_avatar_id),
+        rate_limit: rate_limit.unwrap_or(old_rate_limit),
+        token: encode_token(hook.webhooks_token()?)?,
+    })
+}
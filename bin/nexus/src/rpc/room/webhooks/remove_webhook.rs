@@ -0,0 +1,63 @@
+use schema::audit::AuditAction;
+use sdk::{api::commands::all::DeleteWebhook, models::*};
+
+use crate::{internal::audit::AuditEntry, prelude::*};
+
+/// Deletes a webhook, invalidating its token.
+///
+/// The webhook's user is kept, so messages it already posted still show its name and avatar.
+pub async fn remove_webhook(
+    state: ServerState,
+    auth: Authorization,
+    cmd: &Archived<DeleteWebhook>,
+) -> Result<(), Error> {
+    let room_id: RoomId = cmd.room_id.into();
+    let webhook_id: Snowflake = cmd.webhook_id.into();
+
+    let mut db = state.db.write.get().await?;
+    let t = db.transaction().await?;
+
+    #[rustfmt::skip]
+    let Some(row) = t.query_opt2(schema::sql! {
+        const_assert!(!Columns::IS_DYNAMIC);
+
+        SELECT Rooms.PartyId AS @PartyId
+        FROM Webhooks
+            INNER JOIN LiveRooms AS Rooms ON Rooms.Id = Webhooks.RoomId
+            INNER JOIN PartyMembers ON PartyMembers.PartyId = Rooms.PartyId
+        WHERE Webhooks.Id = #{&webhook_id as Webhooks::Id}
+          AND Webhooks.RoomId = #{&room_id as Rooms::Id}
+          AND PartyMembers.UserId = #{auth.user_id_ref() as Users::Id}
+
+        const PERMS: [i64; 2] = Permissions::MANAGE_WEBHOOKS.to_i64();
+        const_assert!(PERMS[1] == 0);
+
+        AND PartyMembers.Permissions1 & const {PERMS[0]} = const {PERMS[0]}
+    }).await? else {
+        t.rollback().await?;
+
+        return Err(Error::NotFound);
+    };
+
+    #[rustfmt::skip]
+    t.execute2(schema::sql! {
+        DELETE FROM Webhooks WHERE Webhooks.Id = #{&webhook_id as Webhooks::Id}
+    }).await?;
+
+    AuditEntry {
+        party_id: row.party_id()?,
+        user_id: auth.user_id(),
+        target_id: Some(webhook_id),
+        action: AuditAction::WebhookDelete,
+        reason: None,
+        changes: Default::default(),
+    }
+    .record(&state, &t)
+    .await?;
+
+    t.commit().await?;
+
+    log::debug!("Webhook {webhook_id} deleted by {}", auth.user_id());
+
+    Ok(())
+}
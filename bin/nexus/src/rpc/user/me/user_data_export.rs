@@ -0,0 +1,44 @@
+use std::time::SystemTime;
+
+use crate::{internal::data_export::run_data_export, prelude::*};
+
+/// Queues an export of all the user's personal data. The user is emailed a download link once finished.
+///
+/// Only one export may be requested per `data_export_cooldown`.
+pub async fn request_data_export(state: ServerState, auth: Authorization) -> Result<(), Error> {
+    let user_id = auth.user_id();
+    let cooldown = SystemTime::now() - state.config().shared.data_export_cooldown;
+
+    let db = state.db.write.get().await?;
+
+    #[rustfmt::skip]
+    let recent = db.query_opt2(schema::sql! {
+        SELECT UserDataExports.Id AS @Id FROM UserDataExports
+        WHERE UserDataExports.UserId = #{&user_id as Users::Id}
+          AND UserDataExports.RequestedAt > #{&cooldown as UserDataExports::RequestedAt}
+        LIMIT 1
+    }).await?;
+
+    if recent.is_some() {
+        return Err(Error::Conflict);
+    }
+
+    let export_id = state.sf.gen();
+
+    #[rustfmt::skip]
+    db.execute2(schema::sql! {
+        INSERT INTO UserDataExports (Id, UserId) VALUES (
+            #{&export_id as UserDataExports::Id},
+            #{&user_id   as UserDataExports::UserId}
+        )
+    }).await?;
+
+    drop(db);
+
+    log::info!("Data export {export_id} requested by {user_id}");
+
+    // detached, the job reports its own errors
+    _ = state.queues.data_export.push(run_data_export(state.clone(), export_id, user_id));
+
+    Ok(())
+}
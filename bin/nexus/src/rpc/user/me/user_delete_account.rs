@@ -0,0 +1,108 @@
+use std::time::SystemTime;
+
+use email::{scenarios::AccountDeletionScheduled, Email};
+use sdk::api::commands::user::DeleteAccount;
+use sdk::models::{ElevationLevel, UserFlags};
+
+use crate::prelude::*;
+
+use crate::internal::{
+    mfa::{process_2fa, validate_2fa_token, ProvidedMfa},
+    password::verify_password,
+};
+use crate::services::email::send_email;
+
+/// Schedules the user's account for deletion after the `user_deletion_delay` cooling-off period,
+/// during which it can still be cancelled.
+pub async fn delete_account(
+    state: ServerState,
+    auth: Authorization,
+    cmd: &Archived<DeleteAccount>,
+) -> Result<(), Error> {
+    let user_id = auth.user_id();
+    let form = &cmd.body;
+
+    if !state.config().shared.password_length.contains(&form.password.len()) {
+        return Err(Error::InvalidCredentials);
+    }
+
+    #[rustfmt::skip]
+    let user = state.db.read.get().await?.query_one2(schema::sql! {
+        SELECT
+            Users.Username  AS @Username,
+            Users.Email     AS @Email,
+            Users.Flags     AS @Flags,
+            Users.Passhash  AS @Passhash,
+            Users.Mfa       AS @Mfa
+        FROM Users WHERE Users.Id = #{&user_id as Users::Id}
+    }).await?;
+
+    let flags = UserFlags::from_bits_truncate(user.flags()?);
+
+    // staff accounts must be removed by another admin
+    if let ElevationLevel::System | ElevationLevel::Staff = flags.elevation() {
+        return Err(Error::Unauthorized);
+    }
+
+    if !verify_password(&state, user.passhash()?, &form.password).await? {
+        return Err(Error::InvalidCredentials);
+    }
+
+    if let Some(mfa) = user.mfa()? {
+        let Some(ref totp) = form.totp else {
+            return Err(Error::TOTPRequired);
+        };
+
+        validate_2fa_token(totp)?;
+
+        if !process_2fa(&state, user_id, ProvidedMfa::Encrypted(mfa), &form.password, totp).await? {
+            return Err(Error::InvalidCredentials);
+        }
+    }
+
+    let delete_at = SystemTime::now() + state.config().shared.user_deletion_delay;
+
+    #[rustfmt::skip]
+    let inserted = state.db.write.get().await?.execute2(schema::sql! {
+        INSERT INTO UserDeletions (UserId, DeleteAt) VALUES (
+            #{&user_id   as UserDeletions::UserId},
+            #{&delete_at as UserDeletions::DeleteAt}
+        )
+        ON CONFLICT DO NOTHING
+    }).await?;
+
+    // already scheduled
+    if inserted == 0 {
+        return Ok(());
+    }
+
+    log::info!("User {user_id} scheduled for deletion");
+
+    let username: String = user.username()?;
+
+    send_email(
+        &state,
+        Email::new(
+            user.email::<String>()?,
+            "Your Lantern account is scheduled for deletion",
+            AccountDeletionScheduled::new(username, Timestamp::from(delete_at).to_string()),
+        ),
+    );
+
+    Ok(())
+}
+
+pub async fn cancel_account_deletion(state: ServerState, auth: Authorization) -> Result<(), Error> {
+    #[rustfmt::skip]
+    let removed = state.db.write.get().await?.execute2(schema::sql! {
+        DELETE FROM UserDeletions WHERE UserDeletions.UserId = #{auth.user_id_ref() as UserDeletions::UserId}
+    }).await?;
+
+    if removed == 0 {
+        return Err(Error::NotFound);
+    }
+
+    log::info!("User {} cancelled account deletion", auth.user_id());
+
+    Ok(())
+}
@@ -0,0 +1,20 @@
+use ::email::{mailer::Mailer, tpl_manager::TemplateManager, Email};
+
+use crate::{config::sections, prelude::*};
+
+pub fn create_mailer(config: &sections::Email) -> Result<Mailer, Error> {
+    let templates = TemplateManager::unloaded(&config.templates, "mustache");
+
+    Ok(Mailer::new(&config.smtp_url, &config.from, templates)?)
+}
+
+/// Sends an email in the background, logging any errors.
+pub fn send_email(state: &ServerState, email: Email) {
+    let state = state.clone();
+
+    tokio::spawn(async move {
+        if let Err(e) = state.services.mailer.send(&email).await {
+            log::error!("Error sending {} email: {e}", email.scenario.path());
+        }
+    });
+}
@@ -0,0 +1,234 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::{Duration, SystemTime},
+};
+
+use hmac::{Hmac, Mac};
+use reqwest::{
+    dns::{Addrs, Name, Resolve, Resolving},
+    header::CONTENT_TYPE,
+    redirect::Policy,
+    StatusCode, Url,
+};
+use sha2::Sha256;
+use tokio::sync::Semaphore;
+
+use crate::prelude::*;
+
+/// Posts gateway events to the HTTPS endpoints of app subscriptions.
+///
+/// Each request is signed with `HMAC-SHA256(secret, "{timestamp}.{body}")`, given as hex in the
+/// `X-Lantern-Signature` header along with the timestamp in `X-Lantern-Timestamp`, so receivers
+/// can verify the payload and reject replays.
+pub struct EventDeliveryClient {
+    client: reqwest::Client,
+
+    /// Maximum number of concurrent deliveries
+    limit: Semaphore,
+}
+
+/// Why a single delivery attempt failed
+#[derive(Debug, thiserror::Error)]
+pub enum DeliveryError {
+    #[error("Unexpected Response: {0}")]
+    Status(StatusCode),
+
+    #[error("Request Error: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("Invalid URL")]
+    InvalidUrl,
+
+    #[error("DNS Error: {0}")]
+    Dns(#[from] std::io::Error),
+
+    #[error("Forbidden Address")]
+    ForbiddenAddress,
+}
+
+impl DeliveryError {
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            DeliveryError::Status(status) => Some(*status),
+            DeliveryError::Request(e) => e.status(),
+            _ => None,
+        }
+    }
+}
+
+pub const SIGNATURE_HEADER: &str = "X-Lantern-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Lantern-Timestamp";
+
+impl EventDeliveryClient {
+    pub fn new() -> Result<EventDeliveryClient, Error> {
+        Ok(EventDeliveryClient {
+            // redirects and DNS changes could otherwise lead requests past `check_url`
+            client: super::service_client_builder()
+                .redirect(Policy::none())
+                .dns_resolver(std::sync::Arc::new(GlobalResolver))
+                .build()?,
+            limit: Semaphore::new(num_cpus::get() * 8),
+        })
+    }
+
+    /// Signs and posts a JSON payload to `url`, succeeding on any 2xx response.
+    pub async fn post(&self, url: &str, secret: &[u8], body: &[u8]) -> Result<(), DeliveryError> {
+        let url = check_url(url).await?;
+
+        let _guard = self.limit.acquire().await.expect("delivery semaphore is never closed");
+
+        let timestamp = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let timestamp = timestamp.to_string();
+
+        let signature = sign(secret, &timestamp, body);
+
+        let res = self
+            .client
+            .post(url)
+            .timeout(Duration::from_secs(10))
+            .header(CONTENT_TYPE, "application/json")
+            .header(TIMESTAMP_HEADER, timestamp)
+            .header(SIGNATURE_HEADER, signature)
+            .body(body.to_vec())
+            .send()
+            .await?;
+
+        match res.status() {
+            status if status.is_success() => Ok(()),
+            status => Err(DeliveryError::Status(status)),
+        }
+    }
+}
+
+/// Hex-encoded `HMAC-SHA256(secret, "{timestamp}.{body}")`
+fn sign(secret: &[u8], timestamp: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body);
+
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Checks that `url` is HTTPS and that its host only resolves to globally routable addresses,
+/// so subscriptions cannot be used to reach the server's own network.
+pub async fn check_url(url: &str) -> Result<Url, DeliveryError> {
+    let url = Url::parse(url).map_err(|_| DeliveryError::InvalidUrl)?;
+
+    if url.scheme() != "https" {
+        return Err(DeliveryError::InvalidUrl);
+    }
+
+    let Some(host) = url.host_str() else {
+        return Err(DeliveryError::InvalidUrl);
+    };
+
+    // IPv6 literals are bracketed in URLs, but not when resolved
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let port = url.port_or_known_default().unwrap_or(443);
+
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port)).await?.collect();
+
+    if addrs.is_empty() || !addrs.iter().all(|addr| is_global(addr.ip())) {
+        return Err(DeliveryError::ForbiddenAddress);
+    }
+
+    Ok(url)
+}
+
+/// Resolves hostnames at connection time, refusing any with non-global addresses
+struct GlobalResolver;
+
+impl Resolve for GlobalResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(resolve_global(name))
+    }
+}
+
+async fn resolve_global(name: Name) -> Result<Addrs, Box<dyn std::error::Error + Send + Sync>> {
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0)).await?.collect();
+
+    if addrs.is_empty() || !addrs.iter().all(|addr| is_global(addr.ip())) {
+        return Err(DeliveryError::ForbiddenAddress.into());
+    }
+
+    Ok(Box::new(addrs.into_iter()))
+}
+
+/// Stand-in for the unstable `IpAddr::is_global`
+fn is_global(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || a == 0
+                || a >= 240 // reserved
+                || (a == 100 && (b & 0b1100_0000) == 64) // shared, 100.64.0.0/10
+                || (a == 192 && b == 0 && ip.octets()[2] == 0) // protocol assignments, 192.0.0.0/24
+                || (a == 198 && (b & 0xFE) == 18)) // benchmarking, 198.18.0.0/15
+        }
+        IpAddr::V6(ip) => {
+            if let Some(v4) = ip.to_ipv4_mapped() {
+                return is_global(IpAddr::V4(v4));
+            }
+
+            let [a, b, ..] = ip.segments();
+
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_multicast()
+                || (a & 0xFE00) == 0xFC00 // unique local, fc00::/7
+                || (a & 0xFFC0) == 0xFE80 // link local, fe80::/10
+                || (a == 0x2001 && b == 0x0DB8) // documentation, 2001:db8::/32
+                || (a == 0x0064 && b == 0xFF9B)) // NAT64, 64:ff9b::/96
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature() {
+        let secret: Vec<u8> = (0..16).collect();
+
+        assert_eq!(
+            sign(&secret, "1700000000", br#"{"o":0,"p":null}"#),
+            "ad71ae56ccea3352867f3f3bf85875815627f3f5294323aaf3fc96fd1fb14bc7"
+        );
+    }
+
+    #[test]
+    fn test_is_global() {
+        for ip in ["1.1.1.1", "8.8.8.8", "2606:4700:4700::1111"] {
+            assert!(is_global(ip.parse().unwrap()), "{ip}");
+        }
+
+        for ip in [
+            "127.0.0.1",
+            "10.0.0.1",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "255.255.255.255",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "::ffff:169.254.169.254",
+        ] {
+            assert!(!is_global(ip.parse().unwrap()), "{ip}");
+        }
+    }
+}
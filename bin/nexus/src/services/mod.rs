@@ -1,10 +1,14 @@
 use crate::prelude::*;
 
-use reqwest::{Client, Error as ReqwestError};
+use reqwest::{Client, ClientBuilder, Error as ReqwestError};
 
 pub fn create_service_client() -> Result<Client, ReqwestError> {
-    #[allow(unused_mut)]
-    let mut builder = reqwest::ClientBuilder::new()
+    service_client_builder().build()
+}
+
+/// Common client settings, for services that need to further configure their client
+pub fn service_client_builder() -> ClientBuilder {
+    reqwest::ClientBuilder::new()
         // TODO: Use server name and base URL from config for this?
         .user_agent("Lantern/1.0 (bot; +https://github.com/Lantern-chat)")
         .gzip(true)
@@ -14,24 +18,31 @@ pub fn create_service_client() -> Result<Client, ReqwestError> {
         .redirect(reqwest::redirect::Policy::limited(1))
         .connect_timeout(std::time::Duration::from_secs(10))
         .danger_accept_invalid_certs(false)
-        .http2_adaptive_window(true);
-
-    builder.build()
+        .http2_adaptive_window(true)
 }
 
+pub mod email;
 pub mod embed;
+pub mod event_delivery;
 pub mod hcaptcha;
+pub mod object_store;
 
 pub struct Services {
     pub hcaptcha: hcaptcha::HCaptchaClient,
     pub embed: embed::EmbedClient,
+    pub event_delivery: event_delivery::EventDeliveryClient,
+    pub mailer: ::email::mailer::Mailer,
+    pub object_store: object_store::ObjectStoreClient,
 }
 
 impl Services {
-    pub fn start() -> Result<Services, Error> {
+    pub fn start(config: &crate::config::LocalConfig) -> Result<Services, Error> {
         Ok(Services {
             hcaptcha: hcaptcha::HCaptchaClient::new()?,
             embed: embed::EmbedClient::new()?,
+            event_delivery: event_delivery::EventDeliveryClient::new()?,
+            mailer: email::create_mailer(&config.email)?,
+            object_store: object_store::ObjectStoreClient::new()?,
         })
     }
 }
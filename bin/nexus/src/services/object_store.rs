@@ -0,0 +1,292 @@
+use std::path::Path;
+
+use hmac::{Hmac, Mac};
+use reqwest::{
+    header::{AUTHORIZATION, CONTENT_LENGTH},
+    Method, RequestBuilder, StatusCode, Url,
+};
+use sha2::{Digest, Sha256};
+use tokio::{io::AsyncWriteExt, sync::Semaphore};
+
+use crate::prelude::*;
+
+/// Minimal S3-compatible client for off-site copies of files in the file store.
+///
+/// Uses path-style addressing and SigV4 signing, so it works with Backblaze B2, MinIO and most others.
+/// Objects are uploaded exactly as stored, so they remain encrypted with the local file key.
+pub struct ObjectStoreClient {
+    client: reqwest::Client,
+
+    /// Maximum number of concurrent transfers
+    limit: Semaphore,
+}
+
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+pub enum ObjectStoreError {
+    #[error("Unexpected Response: {0}")]
+    Status(StatusCode),
+}
+
+/// Bucket location and credentials, resolved from the current configuration
+pub struct Bucket {
+    url: Url,
+    region: String,
+    access_key: SmolStr,
+    secret_key: SmolStr,
+}
+
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+impl Bucket {
+    /// Returns `None` if object storage is disabled or missing credentials.
+    pub fn from_config(config: &crate::config::Config) -> Option<Bucket> {
+        let storage = &config.local.storage;
+
+        if !storage.is_enabled() || config.shared.b2_app.is_empty() || config.shared.b2_key.is_empty() {
+            return None;
+        }
+
+        let mut url = Url::parse(&storage.endpoint).ok()?;
+        url.path_segments_mut().ok()?.pop_if_empty().push(&storage.bucket);
+
+        Some(Bucket {
+            url,
+            region: storage.region.clone(),
+            access_key: config.shared.b2_app.clone(),
+            secret_key: config.shared.b2_key.clone(),
+        })
+    }
+
+    fn object_url(&self, id: Snowflake) -> Url {
+        let mut url = self.url.clone();
+        url.path_segments_mut().expect("endpoint is a base URL").push(&id.to_string());
+        url
+    }
+
+    /// Signs the request with AWS Signature Version 4, leaving the payload unsigned
+    /// so uploads can be streamed from disk.
+    fn sign(&self, req: RequestBuilder, method: &Method, url: &Url) -> RequestBuilder {
+        let now = time::OffsetDateTime::now_utc();
+
+        let date = format!("{:04}{:02}{:02}", now.year(), now.month() as u8, now.day());
+        let amz_date = format!("{date}T{:02}{:02}{:02}Z", now.hour(), now.minute(), now.second());
+
+        let host = url.host_str().unwrap_or_default();
+        let host = match url.port() {
+            Some(port) => format!("{host}:{port}"),
+            None => host.to_owned(),
+        };
+
+        let headers = [
+            ("host", host.as_str()),
+            ("x-amz-content-sha256", UNSIGNED_PAYLOAD),
+            ("x-amz-date", amz_date.as_str()),
+        ];
+
+        let scope = format!("{date}/{}/s3/aws4_request", self.region);
+
+        let canonical_request = canonical_request(method.as_str(), url.path(), &headers, UNSIGNED_PAYLOAD);
+        let string_to_sign = string_to_sign(&amz_date, &scope, &canonical_request);
+        let signature = signature(&self.secret_key, &date, &self.region, &string_to_sign);
+
+        req.header("x-amz-date", &amz_date).header("x-amz-content-sha256", UNSIGNED_PAYLOAD).header(
+            AUTHORIZATION,
+            format!(
+                "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={}, Signature={signature}",
+                self.access_key,
+                signed_headers(&headers),
+            ),
+        )
+    }
+}
+
+/// Names of the signed headers, which must already be lowercase and sorted.
+fn signed_headers(headers: &[(&str, &str)]) -> String {
+    headers.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";")
+}
+
+/// SigV4 canonical request without a query string, with headers already lowercase and sorted by name.
+fn canonical_request(method: &str, path: &str, headers: &[(&str, &str)], payload_hash: &str) -> String {
+    let mut canonical_headers = String::new();
+
+    for (name, value) in headers {
+        canonical_headers.push_str(&format!("{name}:{}\n", value.trim()));
+    }
+
+    format!(
+        "{method}\n{path}\n\n{canonical_headers}\n{}\n{payload_hash}",
+        signed_headers(headers)
+    )
+}
+
+fn string_to_sign(amz_date: &str, scope: &str, canonical_request: &str) -> String {
+    format!(
+        "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+        hex::encode(Sha256::digest(canonical_request))
+    )
+}
+
+/// Signs with the key derived for the given date and region, for the `s3` service.
+fn signature(secret_key: &str, date: &str, region: &str, string_to_sign: &str) -> String {
+    let mut key = hmac_sha256(format!("AWS4{secret_key}").as_bytes(), date.as_bytes());
+
+    for part in [region.as_bytes(), b"s3".as_slice(), b"aws4_request".as_slice()] {
+        key = hmac_sha256(&key, part);
+    }
+
+    hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()))
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().into()
+}
+
+impl ObjectStoreClient {
+    pub fn new() -> Result<ObjectStoreClient, Error> {
+        Ok(ObjectStoreClient {
+            client: super::create_service_client()?,
+            limit: Semaphore::new(num_cpus::get() * 4),
+        })
+    }
+
+    fn request(&self, bucket: &Bucket, method: Method, id: Snowflake) -> RequestBuilder {
+        let url = bucket.object_url(id);
+        bucket.sign(self.client.request(method.clone(), url.clone()), &method, &url)
+    }
+
+    /// Uploads the file at `path` as the object for `id`, replacing any existing object.
+    pub async fn put(&self, bucket: &Bucket, id: Snowflake, path: &Path) -> Result<(), Error> {
+        let _guard = self.limit.acquire().await?;
+
+        let file = tokio::fs::File::open(path).await?;
+        let len = file.metadata().await?.len();
+
+        log::debug!("Uploading {id} to object storage ({len} bytes)");
+
+        let res = self
+            .request(bucket, Method::PUT, id)
+            .header(CONTENT_LENGTH, len)
+            .body(reqwest::Body::wrap_stream(tokio_util::io::ReaderStream::new(file)))
+            .send()
+            .await?;
+
+        match res.status() {
+            status if status.is_success() => Ok(()),
+            status => Err(ObjectStoreError::Status(status).into()),
+        }
+    }
+
+    /// Downloads the object for `id` to `path`, returning `false` if there is no such object.
+    ///
+    /// The object is written to a temporary file first, so `path` never holds a partial copy.
+    pub async fn get(&self, bucket: &Bucket, id: Snowflake, path: &Path) -> Result<bool, Error> {
+        let _guard = self.limit.acquire().await?;
+
+        log::debug!("Downloading {id} from object storage");
+
+        let mut res = self.request(bucket, Method::GET, id).send().await?;
+
+        match res.status() {
+            StatusCode::NOT_FOUND => return Ok(false),
+            status if !status.is_success() => return Err(ObjectStoreError::Status(status).into()),
+            _ => {}
+        }
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let partial = path.with_extension("part");
+
+        let download = async {
+            let mut out = tokio::fs::File::create(&partial).await?;
+
+            while let Some(chunk) = res.chunk().await? {
+                out.write_all(&chunk).await?;
+            }
+
+            out.sync_all().await?;
+
+            Ok::<_, Error>(())
+        };
+
+        if let Err(e) = download.await {
+            _ = tokio::fs::remove_file(&partial).await;
+
+            return Err(e);
+        }
+
+        tokio::fs::rename(&partial, path).await?;
+
+        Ok(true)
+    }
+
+    /// Deletes the object for `id`, if it exists.
+    pub async fn delete(&self, bucket: &Bucket, id: Snowflake) -> Result<(), Error> {
+        let _guard = self.limit.acquire().await?;
+
+        let res = self.request(bucket, Method::DELETE, id).send().await?;
+
+        match res.status() {
+            status if status.is_success() || status == StatusCode::NOT_FOUND => Ok(()),
+            status => Err(ObjectStoreError::Status(status).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // "GET Object" example from the AWS documentation on SigV4 header-based authentication for S3
+    const EMPTY_HASH: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+    const SECRET_KEY: &str = "wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY";
+    const AMZ_DATE: &str = "20130524T000000Z";
+    const SCOPE: &str = "20130524/us-east-1/s3/aws4_request";
+
+    const HEADERS: &[(&str, &str)] = &[
+        ("host", "examplebucket.s3.amazonaws.com"),
+        ("range", "bytes=0-9"),
+        ("x-amz-content-sha256", EMPTY_HASH),
+        ("x-amz-date", AMZ_DATE),
+    ];
+
+    #[test]
+    fn test_canonical_request() {
+        let expected = format!(
+            "GET\n/test.txt\n\n\
+             host:examplebucket.s3.amazonaws.com\n\
+             range:bytes=0-9\n\
+             x-amz-content-sha256:{EMPTY_HASH}\n\
+             x-amz-date:20130524T000000Z\n\n\
+             host;range;x-amz-content-sha256;x-amz-date\n\
+             {EMPTY_HASH}"
+        );
+
+        assert_eq!(canonical_request("GET", "/test.txt", HEADERS, EMPTY_HASH), expected);
+    }
+
+    #[test]
+    fn test_string_to_sign() {
+        let canonical_request = canonical_request("GET", "/test.txt", HEADERS, EMPTY_HASH);
+
+        assert_eq!(
+            string_to_sign(AMZ_DATE, SCOPE, &canonical_request),
+            "AWS4-HMAC-SHA256\n20130524T000000Z\n20130524/us-east-1/s3/aws4_request\n\
+             7344ae5b7ee6c3e7e6b0fe0640412a37625d1fbfff95c48bbb2dc43964946972"
+        );
+    }
+
+    #[test]
+    fn test_signature() {
+        let canonical_request = canonical_request("GET", "/test.txt", HEADERS, EMPTY_HASH);
+        let string_to_sign = string_to_sign(AMZ_DATE, SCOPE, &canonical_request);
+
+        assert_eq!(
+            signature(SECRET_KEY, "20130524", "us-east-1", &string_to_sign),
+            "f0e8bdb87c964420e857bd35b5d6ed310bd44f0170aba48dd91039c6036bdb41"
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use filesystem::store::FileStore;
 use schema::sf::SnowflakeGenerator;
 use tokio::sync::Semaphore;
 
@@ -42,6 +43,21 @@ pub struct ServerStateInner {
 
     /// Last timestep used for MFA per-user.
     pub mfa_last: scc::HashIndex<UserId, u64, sdk::FxRandomState2>,
+
+    /// Signalled whenever the `config` table changes
+    pub shared_config_changed: tokio::sync::watch::Sender<()>,
+
+    /// Signalled whenever the `ip_bans` table changes
+    pub ip_bans_changed: tokio::sync::watch::Sender<()>,
+
+    /// Users whose sessions were forcibly revoked, to be dropped from gateway auth caches
+    pub revoked_users: tokio::sync::broadcast::Sender<UserId>,
+
+    /// Parties moving between factions, to be applied by gateway `RpcManager`s
+    pub party_migrations: tokio::sync::broadcast::Sender<rpc::request::PartyMigration>,
+
+    /// Held for reading by party requests in progress, and briefly for writing to drain them for a migration fence
+    pub party_fence: tokio::sync::RwLock<()>,
 }
 
 #[derive(Clone)]
@@ -65,6 +81,13 @@ impl config::HasConfig<Config> for ServerState {
 }
 
 impl ServerState {
+    /// Encrypted file storage rooted at the configured data path
+    pub fn fs(&self) -> FileStore {
+        FileStore {
+            root: self.config().local.paths.data_path.clone(),
+        }
+    }
+
     pub fn new(config: Config, db: db::DatabasePools) -> Self {
         ServerState(triomphe::Arc::new(ServerStateInner {
             db,
@@ -77,11 +100,16 @@ impl ServerState {
             hasher: sdk::FxRandomState2::default(),
 
             mfa_last: Default::default(),
+            shared_config_changed: tokio::sync::watch::Sender::new(()),
+            ip_bans_changed: tokio::sync::watch::Sender::new(()),
+            revoked_users: tokio::sync::broadcast::Sender::new(256),
+            party_migrations: tokio::sync::broadcast::Sender::new(64),
+            party_fence: tokio::sync::RwLock::new(()),
 
             sf: SnowflakeGenerator::new(sdk::models::sf::LANTERN_EPOCH, 0),
 
             gateway: Gateway::default(),
-            services: Services::start().expect("Failed to start services"),
+            services: Services::start(&config.local).expect("Failed to start services"),
             queues: Queues::default(),
 
             config: config::Config::new(config),
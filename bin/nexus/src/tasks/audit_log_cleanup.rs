@@ -0,0 +1,35 @@
+use std::time::SystemTime;
+
+use schema::SnowflakeExt;
+
+use super::*;
+
+pub fn add_audit_log_cleanup_task(state: &ServerState, runner: &TaskRunner) {
+    runner.add(RetryTask::new(IntervalFnTask::new(
+        state.clone(),
+        Duration::from_secs(60 * 60),
+        |state, _| async move {
+            log::trace!("Cleaning up old audit log entries");
+
+            // audit log ids are snowflakes, so they double as the creation timestamp
+            let oldest = Snowflake::timestamp_only(SystemTime::now() - state.config().shared.audit_log_retention);
+
+            let task = async {
+                #[rustfmt::skip]
+                let removed = state.db.write.get().await?.execute2(schema::sql! {
+                    DELETE FROM AuditLog WHERE AuditLog.Id < #{&oldest as AuditLog::Id}
+                }).await?;
+
+                if removed > 0 {
+                    log::debug!("Removed {removed} expired audit log entries");
+                }
+
+                Ok::<(), Error>(())
+            };
+
+            if let Err(e) = task.await {
+                log::error!("Error during audit log cleanup: {e}");
+            }
+        },
+    )))
+}
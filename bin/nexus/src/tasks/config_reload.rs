@@ -0,0 +1,81 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use schema::config::{ConfigIdentifier, SharedConfig};
+
+use crate::config::{Config, LocalConfig};
+
+use super::*;
+
+/// Reloads the shared config whenever the `config` table changes, and additionally
+/// the local config file when a reload is triggered, such as by SIGHUP.
+///
+/// Invalid configs are logged and discarded, leaving the current config in place. An invalid
+/// shared config is also overwritten in the database by the current one, so it cannot fail the next startup.
+pub fn add_config_reload_task(state: &ServerState, runner: &TaskRunner, path: Option<PathBuf>) {
+    let state = state.clone();
+
+    runner.add(AsyncFnTask::new(|mut alive| async move {
+        let mut shared_changed = state.shared_config_changed.subscribe();
+
+        loop {
+            let reload_local = tokio::select! {
+                biased;
+                _ = alive.changed() => break,
+                _ = state.config.config_reload.notified() => true,
+                _ = shared_changed.changed() => false,
+            };
+
+            let path = if reload_local { path.as_deref() } else { None };
+
+            match reload(&state, path).await {
+                Ok(()) => log::info!("Config reloaded"),
+                Err(e) => log::error!("Error reloading config, keeping current config: {e}"),
+            }
+        }
+    }));
+}
+
+async fn reload(state: &ServerState, path: Option<&Path>) -> Result<(), Error> {
+    let current = state.config_full();
+
+    let shared = {
+        let db = state.db.read.get().await?;
+
+        SharedConfig::load(&db, ConfigIdentifier::ById(current.shared.config_id)).await?
+    };
+
+    if let Err(e) = shared.validate() {
+        // the restored row triggers another reload, which then finds the current config again
+        let db = state.db.write.get().await?;
+
+        if let Err(e) = current.shared.save(&db).await {
+            log::error!("Error restoring last valid shared config: {e}");
+        }
+
+        return Err(e.into());
+    }
+
+    let local = match path {
+        Some(path) => {
+            let mut local: LocalConfig = ::config::load_file(Some(path))?;
+
+            local.retain_startup_sections(&current.local);
+            local.validate_reload(&current.local).map_err(Error::InternalErrorStatic)?;
+            local
+        }
+        None => current.local.clone(),
+    };
+
+    state.config.set(Arc::new(Config { shared, local }));
+
+    if path.is_some() {
+        if let Err(e) = state.services.mailer.templates.reload().await {
+            log::error!("Error reloading email templates: {e}");
+        }
+    }
+
+    Ok(())
+}
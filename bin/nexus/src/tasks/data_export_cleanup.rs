@@ -0,0 +1,51 @@
+use std::{io::ErrorKind, time::SystemTime};
+
+use super::*;
+
+/// Exports still incomplete after this long are assumed to have been interrupted
+const STALE_EXPORT: Duration = Duration::from_secs(60 * 60 * 24);
+
+pub fn add_data_export_cleanup_task(state: &ServerState, runner: &TaskRunner) {
+    runner.add(RetryTask::new(IntervalFnTask::new(
+        state.clone(),
+        Duration::from_secs(60 * 30),
+        |state, _| async move {
+            log::trace!("Cleaning up expired data exports");
+
+            let now = SystemTime::now();
+            let stale = now - STALE_EXPORT;
+
+            let task = async {
+                #[rustfmt::skip]
+                let rows = state.db.write.get().await?.query2(schema::sql! {
+                    DELETE FROM UserDataExports
+                    WHERE UserDataExports.Expires < #{&now as UserDataExports::Expires}
+                       OR (UserDataExports.CompletedAt IS NULL
+                      AND UserDataExports.RequestedAt < #{&stale as UserDataExports::RequestedAt})
+                    RETURNING UserDataExports.Id AS @Id
+                }).await?;
+
+                for row in &rows {
+                    let export_id: Snowflake = row.id()?;
+
+                    match state.fs().delete(export_id).await {
+                        Err(e) if e.kind() != ErrorKind::NotFound => {
+                            log::error!("Error deleting data export archive {export_id}: {e}");
+                        }
+                        _ => {}
+                    }
+                }
+
+                if !rows.is_empty() {
+                    log::debug!("Removed {} expired data exports", rows.len());
+                }
+
+                Ok::<(), Error>(())
+            };
+
+            if let Err(e) = task.await {
+                log::error!("Error during data export cleanup: {e}");
+            }
+        },
+    )))
+}
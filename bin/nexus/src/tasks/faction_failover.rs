@@ -0,0 +1,52 @@
+use std::time::SystemTime;
+
+use crate::internal::faction::{place_party, FACTION_EVICTION};
+
+use super::*;
+
+/// Removes factions that have been down for longer than [`FACTION_EVICTION`],
+/// placing their parties on the remaining live factions.
+///
+/// Until then, parties on a down faction are served by the nexus.
+pub fn add_faction_failover_task(state: &ServerState, runner: &TaskRunner) {
+    runner.add(RetryTask::new(IntervalFnTask::new(
+        state.clone(),
+        Duration::from_secs(60),
+        |state, _| async move {
+            log::trace!("Checking for down factions");
+
+            let cutoff = SystemTime::now() - FACTION_EVICTION;
+
+            let task = async {
+                let db = state.db.write.get().await?;
+
+                #[rustfmt::skip]
+                let orphaned = db.query2(schema::sql! {
+                    DELETE FROM FactionParties WHERE FactionParties.FactionId IN (
+                        SELECT Factions.Id FROM Factions WHERE Factions.LastSeen < #{&cutoff as Factions::LastSeen}
+                    )
+                    RETURNING FactionParties.PartyId AS @PartyId
+                }).await?;
+
+                #[rustfmt::skip]
+                let evicted = db.execute2(schema::sql! {
+                    DELETE FROM Factions WHERE Factions.LastSeen < #{&cutoff as Factions::LastSeen}
+                }).await?;
+
+                if evicted > 0 {
+                    log::warn!("Evicted {evicted} down factions, moving {} parties", orphaned.len());
+                }
+
+                for row in orphaned {
+                    place_party(&db, row.party_id()?).await?;
+                }
+
+                Ok::<(), Error>(())
+            };
+
+            if let Err(e) = task.await {
+                log::error!("Error during faction failover: {e}");
+            }
+        },
+    )))
+}
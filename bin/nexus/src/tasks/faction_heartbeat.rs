@@ -0,0 +1,47 @@
+use crate::internal::faction::FACTION_HEARTBEAT;
+
+use super::*;
+
+/// Registers this faction node so new parties can be placed on it, refreshing the registration
+/// periodically so the nexus knows it's still alive.
+pub fn add_faction_heartbeat_task(state: &ServerState, runner: &TaskRunner) {
+    runner.add(RetryTask::new(IntervalFnTask::new(
+        state.clone(),
+        FACTION_HEARTBEAT,
+        |state, _| async move {
+            let config = state.config();
+
+            let Some(faction_id) = config.local.node.faction_id() else {
+                return;
+            };
+
+            let addr = config.local.rpc.advertise.ip();
+            let port = config.local.rpc.advertise.port() as i32;
+
+            drop(config);
+
+            let task = async {
+                #[rustfmt::skip]
+                state.db.write.get().await?.execute2(schema::sql! {
+                    INSERT INTO Factions (Id, Addr, Port, LastSeen) VALUES (
+                        #{&faction_id as Factions::Id},
+                        #{&addr       as Factions::Addr},
+                        #{&port       as Factions::Port},
+                        now()
+                    )
+                    ON CONFLICT (Factions./Id) DO UPDATE Factions SET (Addr, Port, LastSeen) = (
+                        #{&addr as Factions::Addr},
+                        #{&port as Factions::Port},
+                        now()
+                    )
+                }).await?;
+
+                Ok::<(), Error>(())
+            };
+
+            if let Err(e) = task.await {
+                log::error!("Error sending faction heartbeat: {e}");
+            }
+        },
+    )))
+}
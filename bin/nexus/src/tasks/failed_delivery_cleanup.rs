@@ -0,0 +1,38 @@
+use std::time::SystemTime;
+
+use schema::SnowflakeExt;
+
+use super::*;
+
+/// How long undelivered events are kept in the dead-letter log
+const RETENTION: Duration = Duration::from_secs(60 * 60 * 24 * 7);
+
+pub fn add_failed_delivery_cleanup_task(state: &ServerState, runner: &TaskRunner) {
+    runner.add(RetryTask::new(IntervalFnTask::new(
+        state.clone(),
+        Duration::from_secs(60 * 60),
+        |state, _| async move {
+            log::trace!("Cleaning up failed event deliveries");
+
+            // dead-letter ids are snowflakes, so they double as the time of failure
+            let oldest = Snowflake::timestamp_only(SystemTime::now() - RETENTION);
+
+            let task = async {
+                #[rustfmt::skip]
+                let removed = state.db.write.get().await?.execute2(schema::sql! {
+                    DELETE FROM FailedDeliveries WHERE FailedDeliveries.Id < #{&oldest as FailedDeliveries::Id}
+                }).await?;
+
+                if removed > 0 {
+                    log::debug!("Removed {removed} expired failed event deliveries");
+                }
+
+                Ok::<(), Error>(())
+            };
+
+            if let Err(e) = task.await {
+                log::error!("Error during failed delivery cleanup: {e}");
+            }
+        },
+    )))
+}
@@ -0,0 +1,137 @@
+use std::{io::ErrorKind, time::SystemTime};
+
+use futures::StreamExt;
+
+use super::*;
+
+/// Maximum number of blobs replicated per iteration
+const REPLICATION_BATCH: i64 = 100;
+
+pub fn add_file_storage_tasks(state: &ServerState, runner: &TaskRunner) {
+    // Task for copying blobs to object storage that weren't already written-through
+    runner.add(RetryTask::new(IntervalFnTask::new(
+        state.clone(),
+        Duration::from_secs(60),
+        |state, _| async move {
+            if !state.config().local.storage.is_enabled() {
+                return;
+            }
+
+            log::trace!("Replicating files to object storage");
+
+            let task = async {
+                #[rustfmt::skip]
+                let rows = state.db.read.get().await?.query2(schema::sql! {
+                    SELECT FileBlobs.Id AS @Id
+                    FROM FileBlobs WHERE FileBlobs.Replicated = FALSE
+                    ORDER BY FileBlobs.Id ASC
+                    LIMIT #{&REPLICATION_BATCH as Type::INT8}
+                }).await?;
+
+                let mut replicated = 0;
+
+                for row in &rows {
+                    let blob_id: Snowflake = row.id()?;
+
+                    // a single missing or unreadable blob shouldn't hold up the rest
+                    match crate::internal::files::replicate_blob(&state, blob_id).await {
+                        Ok(true) => replicated += 1,
+                        Ok(false) => break, // disabled since starting
+                        Err(e) => log::warn!("Error replicating blob {blob_id}: {e}"),
+                    }
+                }
+
+                if replicated > 0 {
+                    log::info!("Replicated {replicated} files to object storage");
+                }
+
+                Ok::<(), Error>(())
+            };
+
+            if let Err(e) = task.await {
+                log::error!("Error during file replication: {e}");
+            }
+        },
+    )));
+
+    // Task for evicting the least recently accessed local copies of replicated blobs
+    runner.add(RetryTask::new(IntervalFnTask::new(
+        state.clone(),
+        Duration::from_secs(60 * 60),
+        |state, _| async move {
+            let cache_size = state.config().local.storage.cache_size;
+
+            if cache_size == 0 || !state.config().local.storage.is_enabled() {
+                return;
+            }
+
+            log::trace!("Evicting replicated files from local storage");
+
+            let task = async {
+                let db = state.db.read.get().await?;
+
+                #[rustfmt::skip]
+                let stream = db.query_stream2(schema::sql! {
+                    SELECT FileBlobs.Id AS @Id
+                    FROM FileBlobs WHERE FileBlobs.Replicated = TRUE
+                }).await?;
+
+                let mut stream = std::pin::pin!(stream);
+
+                let mut cached = Vec::new();
+                let mut total = 0u64;
+
+                while let Some(row) = stream.next().await {
+                    let blob_id: Snowflake = row?.id()?;
+
+                    // already evicted
+                    let Ok(meta) = state.fs().metadata(blob_id).await else { continue };
+
+                    // not all filesystems track access times, so fall back to when it was written
+                    let last_used = meta.accessed().or_else(|_| meta.modified()).unwrap_or(SystemTime::UNIX_EPOCH);
+
+                    total += meta.len();
+                    cached.push((last_used, meta.len(), blob_id));
+                }
+
+                if total <= cache_size {
+                    return Ok(());
+                }
+
+                cached.sort_unstable();
+
+                let (mut evicted, mut bytes) = (0u64, 0u64);
+
+                for (_, len, blob_id) in cached {
+                    if total <= cache_size {
+                        break;
+                    }
+
+                    let _blob_lock = state.id_lock.lock(blob_id).await;
+
+                    match state.fs().delete(blob_id).await {
+                        Ok(()) => {
+                            evicted += 1;
+                            bytes += len;
+                        }
+                        Err(e) if e.kind() == ErrorKind::NotFound => {}
+                        Err(e) => {
+                            log::error!("Error evicting blob {blob_id} from local storage: {e}");
+                            continue;
+                        }
+                    }
+
+                    total -= len;
+                }
+
+                log::info!("Evicted {evicted} replicated files from local storage, freeing {bytes} bytes");
+
+                Ok::<(), Error>(())
+            };
+
+            if let Err(e) = task.await {
+                log::error!("Error during file cache eviction: {e}");
+            }
+        },
+    )));
+}
@@ -0,0 +1,37 @@
+use std::time::SystemTime;
+
+use schema::SnowflakeExt;
+
+use crate::rpc::room::respond_interaction::DEFERRED_WINDOW;
+
+use super::*;
+
+pub fn add_interaction_cleanup_task(state: &ServerState, runner: &TaskRunner) {
+    runner.add(RetryTask::new(IntervalFnTask::new(
+        state.clone(),
+        Duration::from_secs(60 * 5),
+        |state, _| async move {
+            log::trace!("Cleaning up expired interactions");
+
+            // interaction ids double as the time of invocation, and none can be responded to after this
+            let oldest = Snowflake::timestamp_only(SystemTime::now() - DEFERRED_WINDOW);
+
+            let task = async {
+                #[rustfmt::skip]
+                let removed = state.db.write.get().await?.execute2(schema::sql! {
+                    DELETE FROM Interactions WHERE Interactions.Id < #{&oldest as Interactions::Id}
+                }).await?;
+
+                if removed > 0 {
+                    log::debug!("Removed {removed} expired interactions");
+                }
+
+                Ok::<(), Error>(())
+            };
+
+            if let Err(e) = task.await {
+                log::error!("Error during interaction cleanup: {e}");
+            }
+        },
+    )))
+}
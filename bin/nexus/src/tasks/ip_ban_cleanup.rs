@@ -0,0 +1,35 @@
+use timestamp::Timestamp;
+
+use super::*;
+
+pub fn add_ip_ban_cleanup_task(state: &ServerState, runner: &TaskRunner) {
+    runner.add(RetryTask::new(IntervalFnTask::new(
+        state.clone(),
+        Duration::from_secs(60 * 10),
+        |state, _| async move {
+            log::trace!("Cleaning up expired IP bans");
+
+            let now = Timestamp::now_utc();
+
+            let task = async {
+                let db = state.db.write.get().await?;
+
+                // expired bans are already excluded from the filter, this just removes the rows
+                #[rustfmt::skip]
+                let removed = db.execute2(schema::sql! {
+                    DELETE FROM IpBans WHERE IpBans.Expires < #{&now as IpBans::Expires}
+                }).await?;
+
+                if removed > 0 {
+                    log::debug!("Removed {removed} expired IP bans");
+                }
+
+                Ok::<(), Error>(())
+            };
+
+            if let Err(e) = task.await {
+                log::error!("Error during IP ban cleanup: {e}");
+            }
+        },
+    )))
+}
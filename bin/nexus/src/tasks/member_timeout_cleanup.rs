@@ -0,0 +1,45 @@
+use timestamp::Timestamp;
+
+use super::*;
+
+pub fn add_member_timeout_cleanup_task(state: &ServerState, runner: &TaskRunner) {
+    runner.add(RetryTask::new(IntervalFnTask::new(
+        state.clone(),
+        Duration::from_secs(30),
+        |state, _| async move {
+            log::trace!("Lifting expired member timeouts");
+
+            let now = Timestamp::now_utc();
+
+            let task = async {
+                let db = state.db.write.get().await?;
+
+                // NOTE: Triggers on both tables will emit member_updated events for each lifted timeout
+                #[rustfmt::skip]
+                let (party_rows, room_rows) = tokio::try_join!(
+                    db.query2(schema::sql! {
+                        UPDATE PartyMembers SET (MuteUntil) = (NULL)
+                        WHERE PartyMembers.MuteUntil < #{&now as PartyMembers::MuteUntil}
+                        RETURNING PartyMembers.UserId AS @UserId
+                    }),
+                    db.query2(schema::sql! {
+                        UPDATE RoomMembers SET (MuteExpires) = (NULL)
+                        WHERE RoomMembers.MuteExpires < #{&now as RoomMembers::MuteExpires}
+                        RETURNING RoomMembers.UserId AS @UserId
+                    }),
+                )?;
+
+                // cached permissions may still include the mute
+                for row in party_rows.iter().chain(&room_rows) {
+                    state.perm_cache.clear_user(row.user_id()?).await;
+                }
+
+                Ok::<(), Error>(())
+            };
+
+            if let Err(e) = task.await {
+                log::error!("Error during member timeout cleanup: {e}");
+            }
+        },
+    )))
+}
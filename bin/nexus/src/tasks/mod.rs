@@ -6,24 +6,62 @@ type Alive = tokio::sync::watch::Receiver<bool>;
 
 use crate::prelude::*;
 
+pub use config_reload::add_config_reload_task;
+
 pub fn add_tasks(state: &ServerState, runner: &TaskRunner) {
     let config = state.config();
 
     rpc_server::add_rpc_server_task(state, runner);
     gateway_event_cleanup::add_gateway_event_cleanup_task(state, runner);
     perm_cache_cleanup::add_perm_cache_cleanup(state, runner);
+    party_ban_cleanup::add_party_ban_cleanup_task(state, runner);
+    member_timeout_cleanup::add_member_timeout_cleanup_task(state, runner);
+    audit_log_cleanup::add_audit_log_cleanup_task(state, runner);
+    revision_cleanup::add_revision_cleanup_task(state, runner);
 
     if config.local.node.is_user_nexus() {
+        data_export_cleanup::add_data_export_cleanup_task(state, runner);
+        faction_failover::add_faction_failover_task(state, runner);
+        failed_delivery_cleanup::add_failed_delivery_cleanup_task(state, runner);
+        file_storage::add_file_storage_tasks(state, runner);
         mfa_cleanup::add_mfa_cleanup_tasks(state, runner);
+        orphan_cleanup::add_orphan_cleanup_task(state, runner);
         session_cleanup::add_session_cleanup_task(state, runner);
+        interaction_cleanup::add_interaction_cleanup_task(state, runner);
+        ip_ban_cleanup::add_ip_ban_cleanup_task(state, runner);
+        rate_limit_cleanup::add_rate_limit_cleanup_task(state, runner);
+        user_ban_cleanup::add_user_ban_cleanup_task(state, runner);
+        user_deletion::add_user_deletion_task(state, runner);
+        user_purge::add_user_purge_task(state, runner);
+    }
+
+    if config.local.node.is_faction() {
+        faction_heartbeat::add_faction_heartbeat_task(state, runner);
     }
 
     crate::gateway::task::listen::add_gateway_listener(state.clone(), runner);
     crate::gateway::task::process::add_gateway_processor(state.clone(), runner);
 }
 
+mod audit_log_cleanup;
+mod config_reload;
+mod data_export_cleanup;
+mod faction_failover;
+mod faction_heartbeat;
+mod failed_delivery_cleanup;
+mod file_storage;
 mod gateway_event_cleanup;
+mod interaction_cleanup;
+mod ip_ban_cleanup;
+mod member_timeout_cleanup;
 mod mfa_cleanup;
+mod orphan_cleanup;
+mod party_ban_cleanup;
 mod perm_cache_cleanup;
+mod rate_limit_cleanup;
+mod revision_cleanup;
 mod rpc_server;
 mod session_cleanup;
+mod user_ban_cleanup;
+mod user_deletion;
+mod user_purge;
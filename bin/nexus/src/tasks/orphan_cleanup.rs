@@ -0,0 +1,100 @@
+use std::{sync::atomic::AtomicU64, time::SystemTime};
+
+use schema::SnowflakeExt;
+
+use super::*;
+
+/// Maximum number of files removed per batch
+const CLEANUP_BATCH: i64 = 500;
+
+/// Maximum number of batches per iteration, so a large backlog doesn't hog the database
+const MAX_BATCHES: usize = 20;
+
+/// Running totals since startup, reported with each cleanup
+static RECLAIMED_FILES: AtomicU64 = AtomicU64::new(0);
+static RECLAIMED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Removes uploads that were never used, either because they were never completed
+/// or because nothing ever referenced them, once older than `orphan_cleanup`.
+///
+/// Assets no longer referenced by anything in `AggUsedAssets`, such as replaced avatars
+/// or deleted emotes, are removed first, leaving their files unused.
+///
+/// Files are considered in use if referenced by anything in `AggUsedFiles`,
+/// which includes attachments (even orphaned ones, to preserve links), assets and wallpapers.
+pub fn add_orphan_cleanup_task(state: &ServerState, runner: &TaskRunner) {
+    runner.add(RetryTask::new(IntervalFnTask::new(
+        state.clone(),
+        Duration::from_secs(60 * 60),
+        |state, _| async move {
+            log::trace!("Cleaning up orphaned files");
+
+            // file ids are snowflakes, so they double as the upload timestamp
+            let oldest = Snowflake::timestamp_only(SystemTime::now() - state.config().shared.orphan_cleanup);
+
+            let task = async {
+                let mut db = state.db.write.get().await?;
+
+                let (mut files, mut bytes) = (0u64, 0u64);
+
+                for _ in 0..MAX_BATCHES {
+                    let t = db.transaction().await?;
+
+                    // asset ids are snowflakes too, so new assets aren't removed before they're used
+                    #[rustfmt::skip]
+                    let assets = t.execute2(schema::sql! {
+                        DELETE FROM UserAssets WHERE UserAssets.Id IN (
+                            SELECT UserAssets.Id FROM UserAssets
+                            WHERE UserAssets.Id < #{&oldest as UserAssets::Id}
+                              AND NOT EXISTS (SELECT FROM AggUsedAssets WHERE AggUsedAssets.Id = UserAssets.Id)
+                            LIMIT #{&CLEANUP_BATCH as Type::INT8}
+                        )
+                    }).await?;
+
+                    #[rustfmt::skip]
+                    let rows = t.query2(schema::sql! {
+                        DELETE FROM Files WHERE Files.Id IN (
+                            SELECT Files.Id FROM Files
+                            WHERE Files.Id < #{&oldest as Files::Id}
+                              AND NOT EXISTS (SELECT FROM AggUsedFiles WHERE AggUsedFiles.Id = Files.Id)
+                            LIMIT #{&CLEANUP_BATCH as Type::INT8}
+                        )
+                        RETURNING Files.Id AS @Id, Files.BlobId AS @BlobId
+                    }).await?;
+
+                    let mut removed = Vec::with_capacity(rows.len());
+
+                    for row in &rows {
+                        removed.push((row.id()?, row.blob_id()?));
+                    }
+
+                    // deduplicated contents are only removed once no other file shares them
+                    let stored = crate::internal::files::release_files(&t, &removed).await?;
+
+                    t.commit().await?;
+
+                    files += removed.len() as u64;
+                    bytes += crate::internal::files::remove_stored(&state, &stored).await;
+
+                    if rows.len() < CLEANUP_BATCH as usize && assets < CLEANUP_BATCH as u64 {
+                        break;
+                    }
+                }
+
+                if files > 0 {
+                    let total_files = RECLAIMED_FILES.fetch_add(files, Ordering::Relaxed) + files;
+                    let total_bytes = RECLAIMED_BYTES.fetch_add(bytes, Ordering::Relaxed) + bytes;
+
+                    log::info!("Removed {files} orphaned files, reclaiming {bytes} bytes");
+                    log::debug!("Reclaimed {total_files} orphaned files ({total_bytes} bytes) since startup");
+                }
+
+                Ok::<(), Error>(())
+            };
+
+            if let Err(e) = task.await {
+                log::error!("Error during orphaned file cleanup: {e}");
+            }
+        },
+    )))
+}
@@ -0,0 +1,50 @@
+use schema::flags::MemberFlags;
+use timestamp::Timestamp;
+
+use super::*;
+
+pub fn add_party_ban_cleanup_task(state: &ServerState, runner: &TaskRunner) {
+    runner.add(RetryTask::new(IntervalFnTask::new(
+        state.clone(),
+        Duration::from_secs(60),
+        |state, _| async move {
+            log::trace!("Lifting expired party bans");
+
+            let now = Timestamp::now_utc();
+
+            let task = async {
+                let db = state.db.write.get().await?;
+
+                // Deleting the banned member rows emits member_unban events
+                #[rustfmt::skip]
+                let lifted = db.execute2(schema::sql! {
+                    struct Expired {
+                        PartyId: PartyBans::PartyId,
+                        UserId: PartyBans::UserId,
+                    }
+
+                    WITH Expired AS (
+                        DELETE FROM PartyBans WHERE PartyBans.Expires < #{&now as PartyBans::Expires}
+                        RETURNING
+                            PartyBans.PartyId AS Expired.PartyId,
+                            PartyBans.UserId AS Expired.UserId
+                    )
+                    DELETE FROM PartyMembers USING Expired
+                    WHERE PartyMembers.PartyId = Expired.PartyId
+                      AND PartyMembers.UserId = Expired.UserId
+                      AND PartyMembers.Flags & const {MemberFlags::BANNED.bits()} != 0
+                }).await?;
+
+                if lifted > 0 {
+                    log::debug!("Lifted {lifted} expired party bans");
+                }
+
+                Ok::<(), Error>(())
+            };
+
+            if let Err(e) = task.await {
+                log::error!("Error during party ban cleanup: {e}");
+            }
+        },
+    )))
+}
@@ -0,0 +1,37 @@
+use std::time::SystemTime;
+
+use super::*;
+
+/// How long an address is remembered after its last violation, which also resets ban escalation
+const FORGET_AFTER: Duration = Duration::from_secs(60 * 60 * 24 * 30);
+
+pub fn add_rate_limit_cleanup_task(state: &ServerState, runner: &TaskRunner) {
+    runner.add(RetryTask::new(IntervalFnTask::new(
+        state.clone(),
+        Duration::from_secs(60 * 60),
+        |state, _| async move {
+            log::trace!("Cleaning up stale rate-limit violations");
+
+            let cutoff = SystemTime::now() - FORGET_AFTER;
+
+            let task = async {
+                let db = state.db.write.get().await?;
+
+                #[rustfmt::skip]
+                let removed = db.execute2(schema::sql! {
+                    DELETE FROM RateLimits WHERE RateLimits.LastViolation < #{&cutoff as RateLimits::LastViolation}
+                }).await?;
+
+                if removed > 0 {
+                    log::debug!("Forgot {removed} stale rate-limit records");
+                }
+
+                Ok::<(), Error>(())
+            };
+
+            if let Err(e) = task.await {
+                log::error!("Error during rate-limit cleanup: {e}");
+            }
+        },
+    )))
+}
@@ -0,0 +1,35 @@
+use std::time::SystemTime;
+
+use schema::SnowflakeExt;
+
+use super::*;
+
+pub fn add_revision_cleanup_task(state: &ServerState, runner: &TaskRunner) {
+    runner.add(RetryTask::new(IntervalFnTask::new(
+        state.clone(),
+        Duration::from_secs(60 * 60),
+        |state, _| async move {
+            log::trace!("Cleaning up old message revisions");
+
+            // revision ids are snowflakes of when the edit was made
+            let oldest = Snowflake::timestamp_only(SystemTime::now() - state.config().shared.revision_retention);
+
+            let task = async {
+                #[rustfmt::skip]
+                let removed = state.db.write.get().await?.execute2(schema::sql! {
+                    DELETE FROM MessageRevisions WHERE MessageRevisions.Id < #{&oldest as MessageRevisions::Id}
+                }).await?;
+
+                if removed > 0 {
+                    log::debug!("Removed {removed} expired message revisions");
+                }
+
+                Ok::<(), Error>(())
+            };
+
+            if let Err(e) = task.await {
+                log::error!("Error during message revision cleanup: {e}");
+            }
+        },
+    )))
+}
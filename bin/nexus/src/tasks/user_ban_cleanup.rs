@@ -0,0 +1,78 @@
+use email::{scenarios::UserUnbanned, Email};
+use sdk::models::UserFlags;
+use timestamp::Timestamp;
+
+use crate::services::email::send_email;
+
+use super::*;
+
+pub fn add_user_ban_cleanup_task(state: &ServerState, runner: &TaskRunner) {
+    runner.add(RetryTask::new(IntervalFnTask::new(
+        state.clone(),
+        Duration::from_secs(60 * 5),
+        |state, _| async move {
+            log::trace!("Lifting expired user bans");
+
+            let now = Timestamp::now_utc();
+
+            let task = async {
+                let mut db = state.db.write.get().await?;
+                let t = db.transaction().await?;
+
+                #[rustfmt::skip]
+                let rows = t.query2(schema::sql! {
+                    DELETE FROM UserBans WHERE UserBans.Expires < #{&now as UserBans::Expires}
+                    RETURNING UserBans.UserId AS @UserId
+                }).await?;
+
+                if rows.is_empty() {
+                    return Ok(());
+                }
+
+                let mut ids = Vec::with_capacity(rows.len());
+
+                for row in rows {
+                    ids.push(row.user_id::<UserId>()?);
+                }
+
+                #[rustfmt::skip]
+                let rows = t.query2(schema::sql! {
+                    UPDATE Users SET (Flags) = (Users.Flags & ~const {UserFlags::BANNED.bits()})
+                    WHERE Users.Id = ANY(#{&ids as SNOWFLAKE_ARRAY})
+                      AND Users.Flags & const {UserFlags::BANNED.bits()} != 0
+                    RETURNING
+                        Users.Username AS @Username,
+                        Users.Email AS @Email
+                }).await?;
+
+                t.commit().await?;
+
+                log::info!("Lifted {} expired user bans", rows.len());
+
+                for row in rows {
+                    let email: String = row.email()?;
+
+                    // purged accounts have no email to notify
+                    if crate::internal::user_deletion::is_purged_email(&email) {
+                        continue;
+                    }
+
+                    send_email(
+                        &state,
+                        Email::new(
+                            email,
+                            "Your Lantern account has been unbanned",
+                            UserUnbanned::new(row.username::<String>()?),
+                        ),
+                    );
+                }
+
+                Ok::<(), Error>(())
+            };
+
+            if let Err(e) = task.await {
+                log::error!("Error during user ban cleanup: {e}");
+            }
+        },
+    )))
+}
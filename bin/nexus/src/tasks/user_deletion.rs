@@ -0,0 +1,48 @@
+use timestamp::Timestamp;
+
+use crate::internal::user_deletion::soft_delete_user;
+
+use super::*;
+
+pub fn add_user_deletion_task(state: &ServerState, runner: &TaskRunner) {
+    runner.add(RetryTask::new(IntervalFnTask::new(
+        state.clone(),
+        Duration::from_secs(60 * 10),
+        |state, _| async move {
+            log::trace!("Deleting accounts past their cooling-off period");
+
+            let now = Timestamp::now_utc();
+
+            let task = async {
+                let mut db = state.db.write.get().await?;
+
+                #[rustfmt::skip]
+                let rows = db.query2(schema::sql! {
+                    SELECT UserDeletions.UserId AS @UserId FROM UserDeletions
+                    WHERE UserDeletions.DeleteAt < #{&now as UserDeletions::DeleteAt}
+                }).await?;
+
+                for row in rows {
+                    let user_id: UserId = row.user_id()?;
+
+                    // also removes the pending deletion
+                    let t = db.transaction().await?;
+                    let bot_ids = soft_delete_user(&t, user_id).await?;
+                    t.commit().await?;
+
+                    for id in std::iter::once(user_id).chain(bot_ids) {
+                        _ = state.revoked_users.send(id);
+                    }
+
+                    log::info!("User {user_id} deleted at their request");
+                }
+
+                Ok::<(), Error>(())
+            };
+
+            if let Err(e) = task.await {
+                log::error!("Error during account deletion: {e}");
+            }
+        },
+    )))
+}
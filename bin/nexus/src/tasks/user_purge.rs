@@ -0,0 +1,117 @@
+use std::time::SystemTime;
+
+use sdk::models::MessageFlags;
+
+use super::*;
+
+/// Maximum number of accounts purged per iteration
+const PURGE_BATCH: i64 = 100;
+
+pub fn add_user_purge_task(state: &ServerState, runner: &TaskRunner) {
+    runner.add(RetryTask::new(IntervalFnTask::new(
+        state.clone(),
+        Duration::from_secs(60 * 60),
+        |state, _| async move {
+            log::trace!("Purging deleted users");
+
+            let (cutoff, tombstone) = {
+                let config = state.config();
+
+                (
+                    SystemTime::now() - config.shared.user_purge_delay,
+                    config.shared.purge_message_content,
+                )
+            };
+
+            let task = async {
+                let mut db = state.db.write.get().await?;
+
+                // purged users have their email replaced by a placeholder, so they are not selected again
+                #[rustfmt::skip]
+                let rows = db.query2(schema::sql! {
+                    SELECT Users.Id AS @Id FROM Users
+                    WHERE Users.DeletedAt < #{&cutoff as Users::DeletedAt}
+                      AND Users.Email NOT SIMILAR TO "%@purged.invalid"
+                    LIMIT #{&PURGE_BATCH as Type::INT8}
+                }).await?;
+
+                for row in rows {
+                    let user_id: UserId = row.id()?;
+
+                    // one failed account should not hold up the rest of the batch
+                    match purge_user(&state, &mut db, user_id, tombstone).await {
+                        Ok(num_files) => log::info!("Purged deleted user {user_id} and {num_files} files"),
+                        Err(e) => log::error!("Error purging deleted user {user_id}: {e}"),
+                    }
+                }
+
+                Ok::<(), Error>(())
+            };
+
+            if let Err(e) = task.await {
+                log::error!("Error during user purge: {e}");
+            }
+        },
+    )))
+}
+
+async fn purge_user(
+    state: &ServerState,
+    db: &mut db::Object,
+    user_id: UserId,
+    tombstone: bool,
+) -> Result<usize, Error> {
+    let t = db.transaction().await?;
+
+    // personal uploads such as attachments and profile avatars are removed, but files behind
+    // assets still used by parties and others stay with the (now anonymous) user row
+    #[rustfmt::skip]
+    let rows = t.query2(schema::sql! {
+        DELETE FROM Files
+        WHERE Files.UserId = #{&user_id as Files::UserId}
+          AND Files.Id NOT IN (
+            SELECT AggSharedAssetFiles.Id FROM AggSharedAssetFiles
+            WHERE AggSharedAssetFiles.Id IS NOT NULL
+          )
+        RETURNING Files.Id AS @Id, Files.BlobId AS @BlobId
+    }).await?;
+
+    let mut files = Vec::with_capacity(rows.len());
+
+    for row in &rows {
+        files.push((row.id()?, row.blob_id()?));
+    }
+
+    if tombstone {
+        #[rustfmt::skip]
+        t.execute2(schema::sql! {
+            UPDATE Messages SET (Content, Flags) = (
+                NULL, Messages.Flags | const {MessageFlags::DELETED.bits()}
+            )
+            WHERE Messages.UserId = #{&user_id as Messages::UserId}
+        }).await?;
+
+        // earlier revisions hold the same content
+        #[rustfmt::skip]
+        t.execute2(schema::sql! {
+            DELETE FROM MessageRevisions WHERE MessageRevisions.MsgId IN (
+                SELECT Messages.Id FROM Messages
+                WHERE Messages.UserId = #{&user_id as Messages::UserId}
+            )
+        }).await?;
+    }
+
+    t.execute2(schema::sql! {
+        CALL .purge_user(#{&user_id as Users::Id})
+    })
+    .await?;
+
+    // contents shared with other users' files are kept
+    let stored = crate::internal::files::release_files(&t, &files).await?;
+
+    t.commit().await?;
+
+    crate::internal::files::remove_stored(state, &stored).await;
+
+    Ok(files.len())
+}
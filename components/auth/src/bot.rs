@@ -57,10 +57,17 @@ impl SplitBotToken {
     }
 
     pub fn new(key: &BotTokenKey, id: Snowflake) -> Self {
+        Self::new_issued(key, id, SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs())
+    }
+
+    /// Creates a token with an explicit issue time, in seconds since the UNIX epoch.
+    ///
+    /// Useful to ensure a regenerated token differs from the last even within the same second.
+    pub fn new_issued(key: &BotTokenKey, id: Snowflake, issued: u64) -> Self {
         let mut t = SplitBotToken {
             id,
             hmac: [0; 20],
-            issued: SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs(),
+            issued,
         };
 
         t.hmac = t.token_mac(key).finalize_fixed().into();
@@ -27,7 +27,7 @@ macro_rules! section {
 
         $(impl Extra { $($extra:tt)+ })?
     ) => { $crate::paste::paste! {
-        #[derive(Debug, $crate::serde::Deserialize)]
+        #[derive(Debug, Clone, $crate::serde::Deserialize)]
         $(#[$meta])*
         #[serde(deny_unknown_fields)]
         $vis struct $name {$(
@@ -77,7 +77,7 @@ macro_rules! config {
         ),*$(,)?}
     ) => {
         $(#[$meta])*
-        #[derive(Default, Debug, $crate::serde::Deserialize)]
+        #[derive(Default, Debug, Clone, $crate::serde::Deserialize)]
         #[serde(deny_unknown_fields)]
         #[cfg_attr(not(feature = "strict"), serde(default))]
         pub struct $name {
@@ -101,6 +101,31 @@ pub trait Configuration {
     fn configure(&mut self);
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigLoadError {
+    #[error("Unable to read config file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Unable to parse config file: {0}")]
+    Toml(#[from] toml::de::Error),
+}
+
+/// Reads the TOML config file at `path`, or uses the defaults if no path is given,
+/// then applies any environmental overrides.
+pub fn load_file<C>(path: Option<&std::path::Path>) -> Result<C, ConfigLoadError>
+where
+    C: Default + Configuration + serde::de::DeserializeOwned,
+{
+    let mut config = match path {
+        Some(path) => toml::from_str(&std::fs::read_to_string(path)?)?,
+        None => C::default(),
+    };
+
+    config.configure();
+
+    Ok(config)
+}
+
 use std::sync::Arc;
 use tokio::sync::Notify;
 
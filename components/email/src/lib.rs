@@ -1,9 +1,12 @@
+extern crate tracing as log;
+
 use smol_str::SmolStr;
 
 #[macro_use]
 mod macros;
 
 pub mod fmt;
+pub mod mailer;
 pub mod scenarios;
 pub mod tpl_manager;
 
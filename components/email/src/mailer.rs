@@ -0,0 +1,73 @@
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+
+use crate::{tpl_manager::TemplateManager, Email};
+
+#[derive(Debug, thiserror::Error)]
+pub enum MailerError {
+    #[error("SMTP Error: {0}")]
+    Smtp(#[from] lettre::transport::smtp::Error),
+
+    #[error("Invalid Address: {0}")]
+    Address(#[from] lettre::address::AddressError),
+
+    #[error("Invalid Message: {0}")]
+    Message(#[from] lettre::error::Error),
+
+    #[error("Missing email template: {0}")]
+    MissingTemplate(&'static str),
+}
+
+/// Renders and sends emails over SMTP.
+///
+/// If no SMTP URL is configured, emails are rendered but silently dropped.
+pub struct Mailer {
+    transport: Option<AsyncSmtpTransport<Tokio1Executor>>,
+    from: Mailbox,
+    pub templates: TemplateManager,
+}
+
+impl Mailer {
+    pub fn new(smtp_url: &str, from: &str, templates: TemplateManager) -> Result<Self, MailerError> {
+        let transport = match smtp_url {
+            "" => None,
+            url => Some(AsyncSmtpTransport::<Tokio1Executor>::from_url(url)?.build()),
+        };
+
+        Ok(Mailer {
+            transport,
+            from: from.parse()?,
+            templates,
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.transport.is_some()
+    }
+
+    pub async fn send(&self, email: &Email) -> Result<(), MailerError> {
+        let body = self.templates.render(email).await;
+
+        let Some(body) = body else {
+            return Err(MailerError::MissingTemplate(email.scenario.path()));
+        };
+
+        let Some(ref transport) = self.transport else {
+            log::debug!("Email disabled, dropping {} to {}", email.scenario.path(), email.to);
+            return Ok(());
+        };
+
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(email.to.parse()?)
+            .subject(email.subject.as_str())
+            .header(ContentType::TEXT_HTML)
+            .body(body)?;
+
+        transport.send(message).await?;
+
+        Ok(())
+    }
+}
@@ -43,6 +43,19 @@ decl_scenarios! {
         username: String,
     },
 
+    /// Sent to a user when they request deletion of their account.
+    AccountDeletionScheduled use "account_deletion_scheduled.mustache" {
+        username: String,
+        date: String,
+    },
+
+    /// Sent to a user when their requested data export is ready to download.
+    DataExportReady use "data_export_ready.mustache" {
+        username: String,
+        link: String,
+        expires: String,
+    },
+
     /// Sent to a user when they receive a friend request,
     /// but only after a certain period of account inactivity.
     DelayedFriendRequest use "friend_request.mustache" {
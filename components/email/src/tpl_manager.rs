@@ -2,7 +2,7 @@ use ramhorns::{Error, Ramhorns};
 use std::path::PathBuf;
 
 pub struct TemplateManager {
-    inner: tokio::sync::RwLock<Ramhorns<foldhash::fast::RandomState>>,
+    inner: tokio::sync::RwLock<Option<Ramhorns<foldhash::fast::RandomState>>>,
     dir: PathBuf,
     ext: &'static str,
 }
@@ -20,13 +20,22 @@ impl TemplateManager {
                     .await
                     .expect("Unable to spawn task")?;
 
-                tpls.into()
+                Some(tpls).into()
             },
             dir,
             ext,
         })
     }
 
+    /// Create a new `TemplateManager` without loading any templates until [`reload`](Self::reload) is called.
+    pub fn unloaded(dir: impl Into<PathBuf>, ext: &'static str) -> Self {
+        Self {
+            inner: None.into(),
+            dir: dir.into(),
+            ext,
+        }
+    }
+
     /// Asynchronously reload all templates from the directory.
     pub async fn reload(&self) -> Result<(), Error> {
         let (dir, ext) = (self.dir.clone(), self.ext);
@@ -35,14 +44,14 @@ impl TemplateManager {
             .await
             .expect("Unable to spawn task");
 
-        *self.inner.write().await = new_tpls?;
+        *self.inner.write().await = Some(new_tpls?);
 
         Ok(())
     }
 
     pub async fn render(&self, email: &crate::Email) -> Option<String> {
         let tpls = self.inner.read().await;
-        let out = tpls.get(email.scenario.path())?.render(email);
+        let out = tpls.as_ref()?.get(email.scenario.path())?.render(email);
 
         Some(out)
     }
@@ -4,10 +4,10 @@ use std::sync::{
     atomic::{AtomicU64, Ordering},
     Arc,
 };
-use std::{borrow::Cow, net::SocketAddr};
+use std::{borrow::Cow, net::SocketAddr, time::Duration};
 
 use auth::RawAuthToken;
-use futures_util::{stream::FuturesUnordered, FutureExt, StreamExt};
+use futures_util::{stream::FuturesUnordered, FutureExt, Stream, StreamExt};
 use indexmap::IndexMap;
 use parking_lot::RwLock;
 
@@ -139,68 +139,99 @@ pub struct RpcManager {
 
     /// Room to party association.
     rooms: scc::HashIndex<Snowflake, Snowflake, sdk::FxRandomState2>,
+
+    /// Parties being migrated between factions, requests for which are held until they've moved.
+    frozen: scc::HashIndex<Snowflake, Arc<tokio::sync::Notify>, sdk::FxRandomState2>,
 }
 
+/// Longest time a request is held while its party is migrating
+const MAX_FREEZE: Duration = Duration::from_secs(10);
+
 use crate::{
     auth::Authorization,
-    request::{PartyInfo, RpcRequest},
+    request::{FactionInfo, PartyInfo, RpcRequest},
 };
 
 impl RpcManager {
+    /// Asks the nexus which node hosts the party, registering the faction client if needed.
     async fn find_faction(&self, endpoint: Resolve) -> Result<Option<RpcClient>, RpcClientError> {
-        let mut clients = Vec::new();
-        self.clients.scan_async(|c| clients.push(c.clone())).await;
-
         let ref msg = match endpoint {
             Resolve::Nexus => unreachable!(),
             Resolve::Party(party_id) => RpcRequest::GetPartyInfoFromPartyId(party_id),
             Resolve::Room(room_id) => RpcRequest::GetPartyInfoFromRoomId(room_id),
         };
 
-        let mut futs = FuturesUnordered::from_iter(clients.into_iter().map(|client| async move {
-            let mut recv = crate::stream::RpcRecvReader::new(client.send(msg).await?);
-
-            let res = match recv.recv::<Result<PartyInfo, ApiError>>().await? {
-                None => return Ok(None),
-                Some(ArchivedResult::Ok(res)) => res,
-                Some(ArchivedResult::Err(e)) => {
-                    log::error!("Remote RPC Error during party lookup: {:?}", e);
-                    return Ok(None);
-                }
-            };
+        let mut recv = crate::stream::RpcRecvReader::new(self.nexus.send(msg).await?);
 
-            self.set_party(client.faction_id, res.party_id, res.room_ids.as_ref()).await.map(Some)
-        }));
+        let info = match recv.recv::<Result<PartyInfo, ApiError>>().await? {
+            None => return Ok(None),
+            Some(ArchivedResult::Ok(info)) => info,
+            Some(ArchivedResult::Err(e)) => {
+                log::error!("Remote RPC Error during party lookup: {:?}", e);
+                return Ok(None);
+            }
+        };
 
-        while let Some(res) = futs.next().await {
-            if matches!(res, Ok(Some(_))) {
-                return res;
+        let client = match info.faction.as_ref() {
+            // not hosted on a live faction, so the nexus serves it directly
+            None => self.nexus.clone(),
+            Some(faction) => {
+                let Ok(faction) = rkyv::deserialize::<FactionInfo, RancorError>(faction) else {
+                    return Err(RpcClientError::EncodingError);
+                };
+
+                // factions share the nexus TLS configuration
+                self.add_faction(RpcClient::new(
+                    self.nexus.endpoint.clone(),
+                    faction.key(),
+                    faction.addr,
+                    self.nexus.nominal_conns,
+                    self.nexus.name.clone(),
+                ))
+                .await
             }
-        }
+        };
 
-        Ok(None)
+        self.insert_party(&client, info.party_id, info.room_ids.as_ref()).await;
+
+        Ok(Some(client))
     }
 
-    pub async fn send(&self, cmd: &RpcRequest) -> Result<quinn::RecvStream, RpcClientError> {
-        let client = match cmd {
-            RpcRequest::ApiProcedure { proc, .. } => {
-                let endpoint = proc.endpoint();
-
-                match self.get_client(endpoint) {
-                    Ok(client) => client,
-                    Err(RpcClientError::MissingParty(_) | RpcClientError::MissingRoom(_)) => {
-                        match self.find_faction(endpoint).boxed().await? {
-                            Some(client) => client,
-                            None => return Err(RpcClientError::DoesNotExist),
-                        }
-                    }
-                    Err(e) => return Err(e),
+    async fn resolve(&self, endpoint: Resolve) -> Result<RpcClient, RpcClientError> {
+        match self.get_client(endpoint) {
+            Ok(client) => Ok(client),
+            Err(RpcClientError::MissingParty(_) | RpcClientError::MissingRoom(_)) => {
+                match self.find_faction(endpoint).boxed().await? {
+                    Some(client) => Ok(client),
+                    None => Err(RpcClientError::DoesNotExist),
                 }
             }
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn send(&self, cmd: &RpcRequest) -> Result<quinn::RecvStream, RpcClientError> {
+        let endpoint = match cmd {
+            RpcRequest::ApiProcedure { proc, .. } => proc.endpoint(),
+            RpcRequest::ForwardedClientCommand { cmd, .. } => cmd.endpoint(),
             _ => unimplemented!("Non-procedure requests"),
         };
 
-        client.send(cmd).await
+        self.wait_if_frozen(endpoint).await;
+
+        let client = self.resolve(endpoint).await?;
+
+        match client.send(cmd).await {
+            // if a faction goes down, forget it and try again with wherever the nexus says the party is now
+            Err(e @ (RpcClientError::Connect(_) | RpcClientError::Connection(_))) if client != self.nexus => {
+                log::warn!("Faction {} unreachable, resolving party again: {e}", client.faction_id);
+
+                self.remove_faction(client.faction_id).await;
+
+                self.resolve(endpoint).await?.send(cmd).await
+            }
+            res => res,
+        }
     }
 
     pub async fn authorize(&self, token: RawAuthToken) -> Result<Result<Authorization, ApiError>, RpcClientError> {
@@ -216,6 +247,36 @@ impl RpcManager {
             Some(res) => res.deserialize_simple().unwrap(),
         })
     }
+
+    /// Posts a message through an incoming webhook, which is always handled by the nexus.
+    pub async fn execute_webhook(
+        &self,
+        webhook_id: Snowflake,
+        token: u128,
+        body: sdk::api::commands::room::CreateMessageBody,
+    ) -> Result<Result<(), ApiError>, RpcClientError> {
+        let stream = self.nexus.send(&RpcRequest::ExecuteWebhook { webhook_id, token, body }).await?;
+
+        let mut recv = crate::stream::RpcRecvReader::new(stream);
+
+        match recv.recv::<Result<(), ApiError>>().await? {
+            None => Err(RpcClientError::EncodingError),
+            Some(res) => res.deserialize_simple().map_err(|_| RpcClientError::EncodingError),
+        }
+    }
+
+    /// Streams the archive of a completed data export in chunks, which is always handled by the nexus.
+    ///
+    /// Only the first item may be an API error, such as for an invalid or expired token.
+    pub async fn download_data_export(
+        &self,
+        export_id: Snowflake,
+        token: u128,
+    ) -> Result<impl Stream<Item = std::io::Result<Result<Vec<u8>, ApiError>>>, RpcClientError> {
+        let stream = self.nexus.send(&RpcRequest::DownloadDataExport { export_id, token }).await?;
+
+        Ok(crate::stream::RpcRecvReader::new(stream).recv_stream_deserialized::<Result<Vec<u8>, ApiError>>())
+    }
 }
 
 impl RpcManager {
@@ -225,9 +286,16 @@ impl RpcManager {
             clients: scc::HashSet::default(),
             factions: scc::HashIndex::default(),
             rooms: scc::HashIndex::default(),
+            frozen: scc::HashIndex::default(),
         }
     }
 
+    /// The user nexus client.
+    #[inline]
+    pub fn nexus(&self) -> &RpcClient {
+        &self.nexus
+    }
+
     /// Add a faction to the manager, returning the client to use for the faction,
     /// or the existing client if it already exists.
     pub async fn add_faction(&self, mut client: RpcClient) -> RpcClient {
@@ -253,15 +321,26 @@ impl RpcManager {
     where
         'a: 'b, // don't let the room_ids reference outlive the party_id
     {
-        use scc::hash_index::Entry;
-
-        let party_id = party_id.into();
-
         let client = match self.factions.get_async(&faction_id).await {
             Some(faction_client) => faction_client.get().clone(),
             _ => return Err(RpcClientError::MissingFaction(faction_id)),
         };
 
+        self.insert_party(&client, party_id, room_ids).await;
+
+        Ok(client)
+    }
+
+    async fn insert_party(
+        &self,
+        client: &RpcClient,
+        party_id: Archived<Snowflake>,
+        room_ids: &[Archived<Snowflake>],
+    ) {
+        use scc::hash_index::Entry;
+
+        let party_id = party_id.into();
+
         // NOTE: This is kind of weird because of async lifetimes and `Send` weirdness.
         _ = tokio::join!(
             async {
@@ -278,8 +357,34 @@ impl RpcManager {
                     .await;
             },
         );
+    }
 
-        Ok(client)
+    /// Removes a faction and every party it hosted, so they'll be resolved again on next use.
+    pub async fn remove_faction(&self, faction_id: Snowflake) {
+        let Some(client) = self.factions.get_async(&faction_id).await.map(|e| e.get().clone()) else {
+            return;
+        };
+
+        let mut party_ids = Vec::new();
+
+        self.factions
+            .retain_async(|&id, c| {
+                if *c != client {
+                    return true;
+                }
+
+                if id != faction_id {
+                    party_ids.push(id);
+                }
+
+                false
+            })
+            .await;
+
+        tokio::join!(
+            self.clients.remove_async(&client),
+            self.rooms.retain_async(|_, pid| !party_ids.contains(pid)),
+        );
     }
 
     pub async fn remove_party(&self, party_id: Snowflake) {
@@ -289,6 +394,61 @@ impl RpcManager {
         );
     }
 
+    /// Holds requests for the party until [`Self::thaw_party`] is called, or [`MAX_FREEZE`] elapses.
+    pub async fn freeze_party(&self, party_id: Snowflake) {
+        _ = self.frozen.insert_async(party_id, Arc::new(tokio::sync::Notify::new())).await;
+    }
+
+    /// Releases requests held for the party.
+    pub async fn thaw_party(&self, party_id: Snowflake) {
+        if let Some(notify) = self.frozen.peek_with(&party_id, |_, n| n.clone()) {
+            self.frozen.remove_async(&party_id).await;
+            notify.notify_waiters();
+        }
+    }
+
+    /// Forgets all party locations and releases any held requests,
+    /// such as when migration updates may have been missed.
+    pub async fn reset_parties(&self) {
+        tokio::join!(
+            self.factions.clear_async(),
+            self.clients.clear_async(),
+            self.rooms.clear_async(),
+            self.frozen.retain_async(|_, notify| {
+                notify.notify_waiters();
+                false
+            }),
+        );
+    }
+
+    async fn wait_if_frozen(&self, endpoint: Resolve) {
+        let party_id = match endpoint {
+            Resolve::Nexus => return,
+            Resolve::Party(party_id) => party_id,
+            Resolve::Room(room_id) => match self.rooms.peek_with(&room_id, |_, &party_id| party_id) {
+                Some(party_id) => party_id,
+                None => return,
+            },
+        };
+
+        let Some(notify) = self.frozen.peek_with(&party_id, |_, n| n.clone()) else {
+            return;
+        };
+
+        let notified = notify.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+
+        // may have been thawed before we started waiting
+        if !self.frozen.contains(&party_id) {
+            return;
+        }
+
+        if tokio::time::timeout(MAX_FREEZE, notified).await.is_err() {
+            log::warn!("Party {party_id} is still migrating, sending request anyway");
+        }
+    }
+
     pub async fn get_connection(&self, kind: Resolve) -> Result<RpcClientConnection, RpcClientError> {
         self.get_client(kind)?.get_connection().await
     }
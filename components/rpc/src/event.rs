@@ -5,12 +5,37 @@ use sdk::{
 
 use smallvec::{smallvec, SmallVec};
 
+use crate::client::Resolve;
+
 pub type SmallSnowflakeVec = SmallVec<[Snowflake; 1]>;
 
 #[derive(Debug, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
 pub enum ClientCommand {
     /// Regular client message/command
     Regular(ClientMsg),
+
+    /// The connection has closed, so any state held for it should be cleared
+    Disconnected,
+}
+
+impl ClientCommand {
+    /// The node responsible for handling this command when forwarded by a gateway.
+    pub fn endpoint(&self) -> Resolve {
+        match self {
+            ClientCommand::Regular(ClientMsg::Subscribe(payload)) => Resolve::Party(payload.party_id),
+            _ => Resolve::Nexus,
+        }
+    }
+}
+
+/// Reply to a forwarded [`ClientCommand`], routed back to the originating connection
+#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+pub enum ClientCommandReply {
+    /// Message to be sent directly to the client
+    Msg(ServerMsg),
+
+    /// The connection is allowed to receive events from this party
+    Subscribed(Snowflake),
 }
 
 #[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
@@ -60,6 +60,9 @@ decl_procs! {
     112 = GetUser,
     113 = UpdateUserPrefs,
     114 = UserLogout,
+    115 = RequestDataExport,
+    116 = DeleteAccount,
+    117 = CancelAccountDeletion,
 
     // File stuff, will either go to the Nexus or CDN nodes
     201 = CreateFile,
@@ -90,6 +93,17 @@ decl_procs! {
     416 = CreatePinFolder       @ party.party_id,
     417 = CreateRoom            @ party.party_id,
     418 = SearchParty           @ party.party_id,
+    419 = CreateEmote           @ party.party_id,
+    420 = PatchEmote            @ party.party_id,
+    421 = DeleteEmote           @ party.party_id,
+    422 = BanMember             @ party.party_id,
+    423 = UnbanMember           @ party.party_id,
+    424 = GetPartyBans          @ party.party_id,
+    425 = TimeoutMember         @ party.party_id,
+    426 = KickMember            @ party.party_id,
+    427 = GetAuditLog           @ party.party_id,
+    428 = AddBotToParty         @ party.party_id,
+    429 = GetPartyCommands      @ party.party_id,
 
     // Room stuff, also goes to faction servers but needs a party_id lookup first
     501 = CreateMessage         @ room.room_id,
@@ -110,6 +124,37 @@ decl_procs! {
     516 = PatchRoom             @ room.room_id,
     517 = DeleteRoom            @ room.room_id,
     518 = GetRoom               @ room.room_id,
+    519 = TimeoutRoomMember     @ room.room_id,
+    520 = CreateWebhook         @ room.room_id,
+    521 = GetWebhooks           @ room.room_id,
+    522 = PatchWebhook          @ room.room_id,
+    523 = DeleteWebhook         @ room.room_id,
+    524 = RespondToInteraction  @ room.room_id,
+    525 = GetMessageRevisions   @ room.room_id,
+
+    // Admin stuff, all goes to the Nexus
+    601 = CreateIpBan,
+    602 = DeleteIpBan,
+    603 = GetIpBans,
+    604 = BanUser,
+    605 = UnbanUser,
+    606 = DeleteUser,
+    607 = LogoutUser,
+    608 = LookupUser,
+    609 = MigrateParty,
+
+    // App stuff, all goes to the Nexus
+    701 = CreateApp,
+    702 = GetApps,
+    703 = PatchApp,
+    704 = DeleteApp,
+    705 = RegenerateBotToken,
+    706 = SetAppSubscription,
+    707 = GetAppSubscription,
+    708 = DeleteAppSubscription,
+    709 = CreateAppCommand,
+    710 = GetAppCommands,
+    711 = DeleteAppCommand,
 }
 
 use futures_util::{future::BoxFuture, FutureExt, StreamExt};
@@ -18,6 +18,8 @@ pub enum RpcRequest {
 
     /// Fetch the shared config from the Nexus server
     GetSharedConfig,
+    /// Stream the shared config, then again whenever it is reloaded
+    WatchSharedConfig,
 
     Authorize {
         token: RawAuthToken,
@@ -28,11 +30,87 @@ pub enum RpcRequest {
     /// Fetch party info from a room_id
     GetPartyInfoFromRoomId(Snowflake),
 
-    ForwardedClientCommand(ClientCommand),
+    /// Client command forwarded by a gateway on behalf of one of its connections,
+    /// replying with a stream of `ClientCommandReply`
+    ForwardedClientCommand {
+        user_id: Snowflake,
+        conn_id: Snowflake,
+        cmd: ClientCommand,
+    },
+
+    /// Fetch the current IP bans once
+    GetIpBans,
+    /// Stream the current IP bans, then again whenever they change or expire
+    WatchIpBans,
+
+    /// Stream the ids of users whose sessions have been forcibly revoked
+    WatchRevokedUsers,
+
+    /// Stream party migrations between faction nodes
+    WatchPartyMigrations,
+
+    /// Report rate-limit violations accumulated by a gateway since its last report
+    ReportRateLimitViolations(Vec<RateLimitViolation>),
+
+    /// Post a message through an incoming webhook, authorized by its secret token
+    ExecuteWebhook {
+        webhook_id: Snowflake,
+        token: u128,
+        body: sdk::api::commands::room::CreateMessageBody,
+    },
+
+    /// Stream the archive of a completed data export, authorized by the token emailed to its user
+    DownloadDataExport {
+        export_id: Snowflake,
+        token: u128,
+    },
 }
 
 #[derive(Debug, rkyv::Archive, rkyv::Serialize)]
 pub struct PartyInfo {
     pub party_id: Snowflake,
     pub room_ids: Vec<Snowflake>,
+    /// Faction node hosting the party, or `None` if it's served by the nexus
+    pub faction: Option<FactionInfo>,
+}
+
+/// Faction node location, as registered by its heartbeat
+#[derive(Debug, Clone, Copy, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+pub struct FactionInfo {
+    /// Faction UUID as stored in the `factions` table
+    pub id: u128,
+    pub addr: std::net::SocketAddr,
+}
+
+impl FactionInfo {
+    /// Key for this faction within the `RpcManager`, which shares a keyspace with party ids.
+    pub fn key(&self) -> Snowflake {
+        let key = (self.id as u64) ^ ((self.id >> 64) as u64);
+
+        Snowflake(std::num::NonZeroU64::new(key).unwrap_or(std::num::NonZeroU64::MAX))
+    }
+}
+
+/// Stage of a party moving between faction nodes, applied by each gateway's `RpcManager`
+#[derive(Debug, Clone, Copy, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+pub enum PartyMigration {
+    /// Hold new requests for the party until it has moved
+    Freeze(Snowflake),
+    /// The party has moved, so resolve its location again and release held requests
+    Moved(Snowflake),
+}
+
+/// Active IP bans, as applied by the gateway IP filter
+#[derive(Debug, Default, Clone, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+pub struct IpBanList {
+    pub addresses: Vec<std::net::IpAddr>,
+    /// Network address and prefix length of each banned CIDR range
+    pub networks: Vec<(std::net::IpAddr, u8)>,
+}
+
+/// Number of rate-limit violations from a single (privacy-masked) address
+#[derive(Debug, Clone, Copy, rkyv::Archive, rkyv::Serialize)]
+pub struct RateLimitViolation {
+    pub addr: std::net::IpAddr,
+    pub count: u32,
 }
@@ -0,0 +1,63 @@
+/// Actions recorded in the party audit log
+///
+/// THIS MUST MATCH THE VALUES STORED IN `lantern.audit_log.action`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i16)]
+pub enum AuditAction {
+    MemberKick = 1,
+    MemberBan = 2,
+    MemberUnban = 3,
+    MemberTimeout = 4,
+    RoomMemberTimeout = 5,
+    BotAdd = 6,
+
+    PartyUpdate = 10,
+
+    RoleCreate = 20,
+    RoleUpdate = 21,
+    RoleDelete = 22,
+
+    RoomCreate = 30,
+    RoomUpdate = 31,
+    RoomDelete = 32,
+
+    EmoteCreate = 40,
+    EmoteUpdate = 41,
+    EmoteDelete = 42,
+
+    WebhookCreate = 50,
+    WebhookUpdate = 51,
+    WebhookDelete = 52,
+}
+
+impl AuditAction {
+    #[inline]
+    pub const fn to_i16(self) -> i16 {
+        self as i16
+    }
+
+    pub const fn from_i16(value: i16) -> Option<AuditAction> {
+        Some(match value {
+            1 => AuditAction::MemberKick,
+            2 => AuditAction::MemberBan,
+            3 => AuditAction::MemberUnban,
+            4 => AuditAction::MemberTimeout,
+            5 => AuditAction::RoomMemberTimeout,
+            6 => AuditAction::BotAdd,
+            10 => AuditAction::PartyUpdate,
+            20 => AuditAction::RoleCreate,
+            21 => AuditAction::RoleUpdate,
+            22 => AuditAction::RoleDelete,
+            30 => AuditAction::RoomCreate,
+            31 => AuditAction::RoomUpdate,
+            32 => AuditAction::RoomDelete,
+            40 => AuditAction::EmoteCreate,
+            41 => AuditAction::EmoteUpdate,
+            42 => AuditAction::EmoteDelete,
+            50 => AuditAction::WebhookCreate,
+            51 => AuditAction::WebhookUpdate,
+            52 => AuditAction::WebhookDelete,
+            _ => return None,
+        })
+    }
+}
@@ -1,6 +1,6 @@
 use std::{borrow::Cow, ops::RangeInclusive, time::Duration};
 
-use sdk::models::{HCaptchaSiteKey, Timestamp};
+use sdk::models::{HCaptchaSiteKey, ServerConfig, ServerLimits, Timestamp};
 use smol_str::SmolStr;
 use uuid::Uuid;
 
@@ -11,6 +11,9 @@ pub enum ConfigError {
 
     #[error("Invalid HCaptcha Site Key")]
     InvalidHCaptchaSiteKey,
+
+    #[error("Invalid Config: {0}")]
+    Invalid(&'static str),
 }
 
 impl From<db::pg::Error> for ConfigError {
@@ -45,6 +48,16 @@ pub struct SharedConfig {
     pub username_length: RangeInclusive<usize>,
     pub mfa_backup_count: u8,
     pub mfa_pending_time: Duration,
+    pub user_purge_delay: Duration,
+    pub user_deletion_delay: Duration,
+    pub purge_message_content: bool,
+    pub data_export_cooldown: Duration,
+    pub data_export_expiry: Duration,
+
+    // Security settings
+    pub rl_ban_threshold: u32,
+    pub rl_ban_duration: Duration,
+    pub rl_violation_decay: Duration,
 
     // User settings
     pub relative_time_random_factor: f32,
@@ -61,12 +74,17 @@ pub struct SharedConfig {
     pub role_description_length: RangeInclusive<usize>,
     pub max_active_rooms: u16,
     pub max_total_rooms: u16,
+    pub max_emotes: u16,
+    pub emote_name_length: RangeInclusive<usize>,
+    pub audit_log_retention: Duration,
 
     // Message settings
     pub max_newlines: u8,
     pub message_length: RangeInclusive<usize>,
     pub max_embeds: u8,
+    pub max_attachments: u8,
     pub max_regex_search_len: usize,
+    pub revision_retention: Duration,
 
     // Upload settings
     pub max_upload_size: u64,
@@ -109,7 +127,10 @@ pub enum ConfigIdentifier {
 }
 
 impl SharedConfig {
+    /// Writes the config back to its row, refusing any config that would fail validation on the next startup.
     pub async fn save(&self, obj: &db::Object) -> Result<Self, ConfigError> {
+        self.validate()?;
+
         #[inline]
         fn range(range: &RangeInclusive<usize>) -> PgRange<i64> {
             PgRange::new(
@@ -128,7 +149,15 @@ impl SharedConfig {
         let session_duration = dur(self.session_duration);
         let presence_timeout = dur(self.presence_timeout);
         let mfa_pending_time = dur(self.mfa_pending_time);
+        let user_purge_delay = dur(self.user_purge_delay);
+        let user_deletion_delay = dur(self.user_deletion_delay);
+        let data_export_cooldown = dur(self.data_export_cooldown);
+        let data_export_expiry = dur(self.data_export_expiry);
+        let rl_ban_duration = dur(self.rl_ban_duration);
+        let rl_violation_decay = dur(self.rl_violation_decay);
         let orphan_cleanup = dur(self.orphan_cleanup);
+        let audit_log_retention = dur(self.audit_log_retention);
+        let revision_retention = dur(self.revision_retention);
 
         let password_length = range(&self.password_length);
         let username_length = range(&self.username_length);
@@ -138,16 +167,20 @@ impl SharedConfig {
         let room_topic_length = range(&self.room_topic_length);
         let role_name_length = range(&self.role_name_length);
         let role_description_length = range(&self.role_description_length);
+        let emote_name_length = range(&self.emote_name_length);
         let message_length = range(&self.message_length);
 
         let minimum_age = self.minimum_age as i16;
         let mfa_backup_count = self.mfa_backup_count as i16;
+        let rl_ban_threshold = self.rl_ban_threshold as i32;
         let max_status_len = self.max_status_length as i16;
         let max_bio_len = self.max_bio_length as i16;
         let max_active_rooms = self.max_active_rooms as i16;
         let max_total_rooms = self.max_total_rooms as i16;
+        let max_emotes = self.max_emotes as i16;
         let max_newlines = self.max_newlines as i16;
         let max_embeds = self.max_embeds as i16;
+        let max_attachments = self.max_attachments as i16;
         let regex_search_len = self.max_regex_search_len as i16;
 
         let max_upload_size = self.max_upload_size as i64;
@@ -182,6 +215,14 @@ impl SharedConfig {
                 Config./UsernameLength     = #{&username_length as Config::UsernameLength},
                 Config./MfaBackupCount     = #{&mfa_backup_count as Config::MfaBackupCount},
                 Config./MfaPendingTime     = #{&mfa_pending_time as Config::MfaPendingTime},
+                Config./UserPurgeDelay     = #{&user_purge_delay as Config::UserPurgeDelay},
+                Config./UserDeletionDelay  = #{&user_deletion_delay as Config::UserDeletionDelay},
+                Config./PurgeMessageContent = #{&self.purge_message_content as Config::PurgeMessageContent},
+                Config./DataExportCooldown = #{&data_export_cooldown as Config::DataExportCooldown},
+                Config./DataExportExpiry   = #{&data_export_expiry as Config::DataExportExpiry},
+                Config./RlBanThreshold     = #{&rl_ban_threshold as Config::RlBanThreshold},
+                Config./RlBanDuration      = #{&rl_ban_duration as Config::RlBanDuration},
+                Config./RlViolationDecay   = #{&rl_violation_decay as Config::RlViolationDecay},
                 Config./ReltimeRndFactor   = #{&self.relative_time_random_factor as Config::ReltimeRndFactor},
                 Config./MaxStatusLen       = #{&max_status_len as Config::MaxStatusLen},
                 Config./MaxBioLen          = #{&max_bio_len as Config::MaxBioLen},
@@ -194,10 +235,15 @@ impl SharedConfig {
                 Config./RoleDescLen        = #{&role_description_length as Config::RoleDescLen},
                 Config./MaxActiveRooms     = #{&max_active_rooms as Config::MaxActiveRooms},
                 Config./MaxTotalRooms      = #{&max_total_rooms as Config::MaxTotalRooms},
+                Config./MaxEmotes          = #{&max_emotes as Config::MaxEmotes},
+                Config./EmoteNameLen       = #{&emote_name_length as Config::EmoteNameLen},
+                Config./AuditLogRetention  = #{&audit_log_retention as Config::AuditLogRetention},
                 Config./MaxNewlines        = #{&max_newlines as Config::MaxNewlines},
                 Config./MessageLength      = #{&message_length as Config::MessageLength},
                 Config./MaxEmbeds          = #{&max_embeds as Config::MaxEmbeds},
+                Config./MaxAttachments     = #{&max_attachments as Config::MaxAttachments},
                 Config./RegexSearchLen     = #{&regex_search_len as Config::RegexSearchLen},
+                Config./RevisionRetention  = #{&revision_retention as Config::RevisionRetention},
                 Config./MaxUploadSize      = #{&max_upload_size as Config::MaxUploadSize},
                 Config./MaxUploadChunk     = #{&max_upload_chunk as Config::MaxUploadChunk},
                 Config./OrphanCleanup      = #{&orphan_cleanup as Config::OrphanCleanup},
@@ -245,6 +291,14 @@ impl SharedConfig {
                 Config.UsernameLength      AS @_,
                 Config.MfaBackupCount      AS @_,
                 Config.MfaPendingTime      AS @_,
+                Config.UserPurgeDelay      AS @_,
+                Config.UserDeletionDelay   AS @_,
+                Config.PurgeMessageContent AS @_,
+                Config.DataExportCooldown  AS @_,
+                Config.DataExportExpiry    AS @_,
+                Config.RlBanThreshold      AS @_,
+                Config.RlBanDuration       AS @_,
+                Config.RlViolationDecay    AS @_,
                 Config.ReltimeRndFactor    AS @_,
                 Config.MaxStatusLen        AS @_,
                 Config.MaxBioLen           AS @_,
@@ -257,10 +311,15 @@ impl SharedConfig {
                 Config.RoleDescLen         AS @_,
                 Config.MaxActiveRooms      AS @_,
                 Config.MaxTotalRooms       AS @_,
+                Config.MaxEmotes           AS @_,
+                Config.EmoteNameLen        AS @_,
+                Config.AuditLogRetention   AS @_,
                 Config.MaxNewlines         AS @_,
                 Config.MessageLength       AS @_,
                 Config.MaxEmbeds           AS @_,
+                Config.MaxAttachments      AS @_,
                 Config.RegexSearchLen      AS @_,
+                Config.RevisionRetention   AS @_,
                 Config.MaxUploadSize       AS @_,
                 Config.MaxUploadChunk      AS @_,
                 Config.OrphanCleanup       AS @_,
@@ -291,12 +350,12 @@ impl SharedConfig {
             Duration::from_millis(ms.max(0) as u64)
         }
 
-        fn range(range: PgRange<i32>) -> RangeInclusive<usize> {
+        fn range(range: PgRange<i32>) -> Result<RangeInclusive<usize>, ConfigError> {
             let (Some(lower), Some(upper)) = (range.lower(), range.upper()) else {
-                panic!("Invalid Postgres Range: {:?}", range);
+                return Err(ConfigError::Invalid("unbounded length range"));
             };
 
-            (lower.value as usize)..=(upper.value as usize)
+            Ok((lower.value.max(0) as usize)..=(upper.value.max(0) as usize))
         }
 
         Ok(SharedConfig {
@@ -313,26 +372,39 @@ impl SharedConfig {
             fs_cache_max_age: dur(row.config_fs_cache_max_age()?),
             session_duration: dur(row.config_session_duration()?),
             minimum_age: row.config_minimum_age::<i16>()? as u8,
-            password_length: range(row.config_password_length()?),
-            username_length: range(row.config_username_length()?),
+            password_length: range(row.config_password_length()?)?,
+            username_length: range(row.config_username_length()?)?,
             mfa_backup_count: row.config_mfa_backup_count::<i16>()? as u8,
             mfa_pending_time: dur(row.config_mfa_pending_time()?),
+            user_purge_delay: dur(row.config_user_purge_delay()?),
+            user_deletion_delay: dur(row.config_user_deletion_delay()?),
+            purge_message_content: row.config_purge_message_content()?,
+            data_export_cooldown: dur(row.config_data_export_cooldown()?),
+            data_export_expiry: dur(row.config_data_export_expiry()?),
+            rl_ban_threshold: row.config_rl_ban_threshold::<i32>()?.max(1) as u32,
+            rl_ban_duration: dur(row.config_rl_ban_duration()?),
+            rl_violation_decay: dur(row.config_rl_violation_decay()?),
             relative_time_random_factor: row.config_reltime_rnd_factor()?,
             max_status_length: row.config_max_status_len::<i16>()? as usize,
             max_bio_length: row.config_max_bio_len::<i16>()? as usize,
             presence_timeout: dur(row.config_presence_timeout()?),
-            party_name_length: range(row.config_party_name_len()?),
-            party_description_length: range(row.config_party_desc_len()?),
-            room_name_length: range(row.config_room_name_len()?),
-            room_topic_length: range(row.config_room_topic_len()?),
-            role_name_length: range(row.config_role_name_len()?),
-            role_description_length: range(row.config_role_desc_len()?),
+            party_name_length: range(row.config_party_name_len()?)?,
+            party_description_length: range(row.config_party_desc_len()?)?,
+            room_name_length: range(row.config_room_name_len()?)?,
+            room_topic_length: range(row.config_room_topic_len()?)?,
+            role_name_length: range(row.config_role_name_len()?)?,
+            role_description_length: range(row.config_role_desc_len()?)?,
             max_active_rooms: row.config_max_active_rooms::<i16>()? as u16,
             max_total_rooms: row.config_max_total_rooms::<i16>()? as u16,
+            max_emotes: row.config_max_emotes::<i16>()? as u16,
+            emote_name_length: range(row.config_emote_name_len()?)?,
+            audit_log_retention: dur(row.config_audit_log_retention()?),
             max_newlines: row.config_max_newlines::<i16>()? as u8,
-            message_length: range(row.config_message_length()?),
+            message_length: range(row.config_message_length()?)?,
             max_embeds: row.config_max_embeds::<i16>()? as u8,
+            max_attachments: row.config_max_attachments::<i16>()? as u8,
             max_regex_search_len: row.config_regex_search_len::<i64>()? as usize,
+            revision_retention: dur(row.config_revision_retention()?),
             max_upload_size: row.config_max_upload_size::<i64>()? as u64,
             max_upload_chunk: row.config_max_upload_chunk::<i32>()? as u32,
             orphan_cleanup: dur(row.config_orphan_cleanup()?),
@@ -352,3 +424,100 @@ impl SharedConfig {
         })
     }
 }
+
+impl SharedConfig {
+    /// Checks the config for values that would break the server if applied,
+    /// such as empty length ranges or zero intervals.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let ranges = [
+            &self.password_length,
+            &self.username_length,
+            &self.party_name_length,
+            &self.party_description_length,
+            &self.room_name_length,
+            &self.room_topic_length,
+            &self.role_name_length,
+            &self.role_description_length,
+            &self.emote_name_length,
+            &self.message_length,
+        ];
+
+        if ranges.iter().any(|r| r.is_empty()) {
+            return Err(ConfigError::Invalid("empty length range"));
+        }
+
+        let intervals = [
+            self.fs_cache_interval,
+            self.session_duration,
+            self.mfa_pending_time,
+            self.presence_timeout,
+            self.rl_violation_decay,
+            self.orphan_cleanup,
+        ];
+
+        if intervals.iter().any(Duration::is_zero) {
+            return Err(ConfigError::Invalid("zero duration"));
+        }
+
+        if self.base_domain.is_empty() || self.cdn_domain.is_empty() {
+            return Err(ConfigError::Invalid("missing domain"));
+        }
+
+        if self.max_upload_chunk == 0 || self.max_upload_chunk as u64 > self.max_upload_size {
+            return Err(ConfigError::Invalid("upload chunk size out of range"));
+        }
+
+        if !(0.0..=1.0).contains(&self.relative_time_random_factor) {
+            return Err(ConfigError::Invalid("relative time random factor out of range"));
+        }
+
+        Ok(())
+    }
+
+    /// Entity tag identifying this revision of the config, changes whenever the config is saved.
+    pub fn etag(&self) -> SmolStr {
+        smol_str::format_smolstr!(
+            "\"{}-{:x}\"",
+            self.config_id.simple(),
+            self.last_updated.to_unix_timestamp_ms()
+        )
+    }
+
+    /// The subset of the config that is safe to expose to clients,
+    /// such as the limits they should validate input against before sending.
+    pub fn public(&self) -> ServerConfig {
+        ServerConfig {
+            server_name: self.server_name.clone(),
+            hcaptcha_sitekey: self.hcaptcha_sitekey,
+            cdn: self.cdn_domain.clone(),
+            min_age: self.minimum_age,
+            secure: self.secure_web,
+            camo: self.camo_enable,
+            limits: ServerLimits {
+                username_length: self.username_length.clone(),
+                password_length: self.password_length.clone(),
+                max_status_length: self.max_status_length,
+                max_bio_length: self.max_bio_length,
+                party_name_length: self.party_name_length.clone(),
+                party_description_length: self.party_description_length.clone(),
+                room_name_length: self.room_name_length.clone(),
+                room_topic_length: self.room_topic_length.clone(),
+                role_name_length: self.role_name_length.clone(),
+                role_description_length: self.role_description_length.clone(),
+                emote_name_length: self.emote_name_length.clone(),
+                max_emotes: self.max_emotes,
+                message_length: self.message_length.clone(),
+                max_newlines: self.max_newlines,
+                max_upload_size: self.max_upload_size,
+                max_upload_chunk: self.max_upload_chunk,
+                max_avatar_size: self.max_avatar_size,
+                max_banner_size: self.max_banner_size,
+                max_avatar_pixels: self.max_avatar_pixels,
+                max_banner_pixels: self.max_banner_pixels,
+                avatar_width: self.avatar_width,
+                banner_width: self.banner_width,
+                banner_height: self.banner_height,
+            },
+        }
+    }
+}
@@ -25,4 +25,12 @@ bitflags::bitflags! {
     pub struct RoomMemberFlags: i32 {
         const MUTED = 1 << 0;
     }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct InteractionFlags: i16 {
+        /// The bot acknowledged the interaction and will respond later
+        const DEFERRED = 1 << 0;
+        /// The bot has given its final response
+        const RESPONDED = 1 << 1;
+    }
 }
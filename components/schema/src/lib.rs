@@ -26,6 +26,7 @@ pub mod sf;
 pub use sf::{Snowflake, SnowflakeExt};
 
 pub mod asset;
+pub mod audit;
 pub mod config;
 pub mod flags;
 pub mod names;
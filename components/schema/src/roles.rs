@@ -5,7 +5,7 @@
 //! -    This includes moving a role to become higher than their own.
 //! - Can only assign permissions they have
 //! - Cannot remove permissions that would remove it from themselves
-//! - Cannot ban/kick/rename users with roles at or above their own
+//! - Cannot ban/kick/rename/mute users with roles at or above their own
 //! - Admins are exempt from the assign/remove safety restrictions. Can freely give or remove for any role below them.
 
 use sdk::models::{Permissions, Snowflake};
@@ -50,6 +50,7 @@ pub enum UserAction {
     Kick,
     Ban,
     Rename,
+    Mute,
 }
 
 pub struct RoleChange {
@@ -85,8 +86,8 @@ impl RoleChecker {
 
         let mut highest_own = self.roles.get_index_of(&user_roles[0]).unwrap_or(everyone);
         let mut highest_other = match other_roles.is_empty() {
-            false => everyone,
-            true => self.roles.get_index_of(&other_roles[0]).unwrap_or(everyone),
+            true => everyone,
+            false => self.roles.get_index_of(&other_roles[0]).unwrap_or(everyone),
         };
 
         let mut permissions = Permissions::empty();
@@ -108,6 +109,7 @@ impl RoleChecker {
             UserAction::Kick => Permissions::KICK_MEMBERS,
             UserAction::Ban => Permissions::BAN_MEMBERS,
             UserAction::Rename => Permissions::MANAGE_NICKNAMES,
+            UserAction::Mute => Permissions::MUTE_MEMBERS,
         };
 
         if !permissions.contains(required_perm) {
@@ -11,6 +11,7 @@ thorn::functions! {
     pub extern "pg" fn combine_profile_bits(base_bits: Type::INT4, party_bits: Type::INT4, party_avatar: Type::INT8) in Lantern;
     pub extern "pg" fn iif(condition: Type::BOOL, true_result: Type::ANYELEMENT, false_result: Type::ANYELEMENT) in Lantern;
     pub extern "pg" fn redeem_invite(_user_id: Type::INT8, _invite_id: Type::INT8, _invite_code: Type::TEXT) in Lantern;
+    pub extern "pg" fn purge_user(_user_id: Type::INT8) in Lantern;
     pub extern "pg" fn refresh_all_permissions() in Lantern;
     pub extern "pg" fn register_user(_id: Type::INT8, _username: Type::TEXT, _email: Type::TEXT, _passhash: Type::TEXT, _dob: Type::DATE) in Lantern;
     pub extern "pg" fn set_presence(_user_id: Type::INT8, _conn_id: Type::INT8, _flags: Type::INT2, _activity: Type::JSONB) in Lantern;
@@ -50,6 +51,9 @@ thorn::enums! {
         ProfileUpdated,
         RelUpdated,
         TokenRefresh,
+        EmoteCreated,
+        EmoteUpdated,
+        EmoteDeleted,
     }
 }
 
@@ -85,6 +89,17 @@ thorn::tables! {
         PartyId: Nullable(Type::INT8),
     }
 
+    /// Active IP bans only
+    pub struct AggIpBans in Lantern {
+        Id: Nullable(Type::INT8),
+        Expires: Nullable(Type::TIMESTAMPTZ),
+        Address: Nullable(Type::INET),
+        /// Network address of the banned CIDR range
+        Network: Nullable(Type::INET),
+        /// Prefix length of the banned CIDR range
+        Prefix: Nullable(Type::INT2),
+    }
+
     pub struct AggMemberPresence in Lantern {
         UserId: Nullable(Type::INT8),
         Discriminator: Nullable(Type::INT4),
@@ -199,12 +214,23 @@ thorn::tables! {
         JoinedAt: Nullable(Type::TIMESTAMPTZ),
         Permissions1: Nullable(Type::INT8),
         Permissions2: Nullable(Type::INT8),
+        Muted: Nullable(Type::BOOL),
     }
 
     pub struct AggUsedFiles in Lantern {
         Id: Nullable(Type::INT8),
     }
 
+    /// Assets still shown by parties, rooms, roles, emotes or profiles
+    pub struct AggUsedAssets in Lantern {
+        Id: Nullable(Type::INT8),
+    }
+
+    /// Files behind assets still shown by parties, rooms, roles, emotes or other profiles
+    pub struct AggSharedAssetFiles in Lantern {
+        Id: Nullable(Type::INT8),
+    }
+
     pub struct AggUserAssociations in Lantern {
         UserId: Nullable(Type::INT8),
         OtherId: Nullable(Type::INT8),
@@ -234,12 +260,43 @@ thorn::tables! {
         Description: Nullable(Type::TEXT),
     }
 
+    pub struct AppCommands in Lantern {
+        Id: Type::INT8,
+        AppId: Type::INT8,
+        PartyId: Nullable(Type::INT8),
+        Name: Type::TEXT,
+        Description: Type::TEXT,
+        Options: Type::JSONB,
+    }
+
+    pub struct AppSubscriptions in Lantern {
+        AppId: Type::INT8,
+        Intents: Type::INT4,
+        Url: Type::TEXT,
+        Secret: Type::BYTEA,
+    }
+
     pub struct Attachments in Lantern {
         MsgId: Type::INT8,
         FileId: Type::INT8,
         Flags: Nullable(Type::INT2),
     }
 
+    /// Persistent record of administrative actions within a party
+    pub struct AuditLog in Lantern {
+        Id: Type::INT8,
+        PartyId: Type::INT8,
+        /// User that performed the action
+        UserId: Type::INT8,
+        /// User, role, room, etc. being acted upon
+        TargetId: Nullable(Type::INT8),
+        /// See [`AuditAction`](crate::audit::AuditAction)
+        Action: Type::INT2,
+        Reason: Nullable(Type::TEXT),
+        /// Map of changed fields to their before and after values
+        Changes: Nullable(Type::JSONB),
+    }
+
     pub struct Config in Lantern {
         ConfigId: Type::UUID,
         ConfigName: Type::TEXT,
@@ -259,6 +316,14 @@ thorn::tables! {
         MfaBackupCount: Type::INT2,
         MfaPendingTime: Type::INT8,
         RegistrationToken: Nullable(Type::TEXT),
+        UserPurgeDelay: Type::INT8,
+        UserDeletionDelay: Type::INT8,
+        PurgeMessageContent: Type::BOOL,
+        DataExportCooldown: Type::INT8,
+        DataExportExpiry: Type::INT8,
+        RlBanThreshold: Type::INT4,
+        RlBanDuration: Type::INT8,
+        RlViolationDecay: Type::INT8,
         ReltimeRndFactor: Type::FLOAT4,
         MaxStatusLen: Type::INT2,
         MaxBioLen: Type::INT2,
@@ -271,10 +336,15 @@ thorn::tables! {
         RoleDescLen: Type::INT4_RANGE,
         MaxActiveRooms: Type::INT2,
         MaxTotalRooms: Type::INT2,
+        MaxEmotes: Type::INT2,
+        EmoteNameLen: Type::INT4_RANGE,
+        AuditLogRetention: Type::INT8,
         MaxNewlines: Type::INT2,
         MessageLength: Type::INT4_RANGE,
         MaxEmbeds: Type::INT2,
+        MaxAttachments: Type::INT2,
         RegexSearchLen: Type::INT2,
+        RevisionRetention: Type::INT8,
         MaxUploadSize: Type::INT8,
         MaxUploadChunk: Type::INT4,
         OrphanCleanup: Type::INT8,
@@ -346,12 +416,31 @@ thorn::tables! {
         PartyId: Type::INT8,
     }
 
+    /// Pending party migrations, acknowledged by the faction serving the party
+    pub struct PartyFences in Lantern {
+        PartyId: Type::INT8,
+        FactionId: Type::UUID,
+        Acked: Type::BOOL,
+    }
+
     pub struct Factions in Lantern {
         Id: Type::UUID,
         Addr: Type::INET,
+        Port: Type::INT4,
+        LastSeen: Type::TIMESTAMPTZ,
         Nickname: Nullable(Type::TEXT),
     }
 
+    /// Event deliveries to app subscriptions that failed after all retries
+    pub struct FailedDeliveries in Lantern {
+        Id: Type::INT8,
+        AppId: Type::INT8,
+        Attempts: Type::INT2,
+        Status: Nullable(Type::INT2),
+        Error: Nullable(Type::TEXT),
+        Payload: Type::JSONB,
+    }
+
     /// Backing file table for all attachments, avatars and so forth
     pub struct Files in Lantern {
         Id: Type::INT8,
@@ -374,6 +463,20 @@ thorn::tables! {
         /// blurhash preview (first frame of video if video). this shouldn't
         /// be too large, less than 128 bytes.
         Preview: Nullable(Type::BYTEA),
+        /// Deduplicated contents, if NULL the contents are stored under the file's own id
+        BlobId: Nullable(Type::INT8),
+    }
+
+    /// Deduplicated file contents, shared by every file with identical contents
+    pub struct FileBlobs in Lantern {
+        /// Also the location of the encrypted contents in the file store
+        Id: Type::INT8,
+        Nonce: Type::INT8,
+        Size: Type::INT8,
+        /// BLAKE3 hash of unencrypted contents, keyed by the file encryption key
+        Blake3: Type::BYTEA,
+        /// Whether the contents have been copied to off-site object storage
+        Replicated: Type::BOOL,
     }
 
     pub struct GroupMembers in Lantern {
@@ -391,6 +494,14 @@ thorn::tables! {
         Migrated: Type::TIMESTAMPTZ,
     }
 
+    pub struct Interactions in Lantern {
+        Id: Type::INT8,
+        CommandId: Type::INT8,
+        UserId: Type::INT8,
+        RoomId: Type::INT8,
+        Flags: Type::INT2,
+    }
+
     pub struct Invite in Lantern {
         Id: Type::INT8,
         PartyId: Type::INT8,
@@ -403,9 +514,12 @@ thorn::tables! {
     }
 
     pub struct IpBans in Lantern {
+        Id: Type::INT8,
+        /// If NULL, the ban is permanent
         Expires: Nullable(Type::TIMESTAMPTZ),
         Address: Nullable(Type::INET),
         Network: Nullable(Type::CIDR),
+        Reason: Nullable(Type::TEXT),
     }
 
     pub struct LiveMessages in Lantern {
@@ -478,6 +592,15 @@ thorn::tables! {
         PinId: Type::INT8,
     }
 
+    pub struct MessageRevisions in Lantern {
+        /// Snowflake of when the edit was made
+        Id: Type::INT8,
+        MsgId: Type::INT8,
+        EditorId: Nullable(Type::INT8),
+        /// Content of the message before the edit
+        Content: Nullable(Type::TEXT),
+    }
+
     pub struct MessageStars in Lantern {
         MsgId: Type::INT8,
         UserId: Type::INT8,
@@ -549,6 +672,8 @@ thorn::tables! {
         PartyId: Type::INT8,
         UserId: Type::INT8,
         BannedAt: Type::TIMESTAMPTZ,
+        /// If NULL, the ban is permanent
+        Expires: Nullable(Type::TIMESTAMPTZ),
         Reason: Nullable(Type::TEXT),
     }
 
@@ -591,6 +716,9 @@ thorn::tables! {
     pub struct RateLimits in Lantern {
         Violations: Type::INT4,
         Addr: Type::INET,
+        /// Number of automatic IP bans issued for this address
+        Bans: Type::INT2,
+        LastViolation: Type::TIMESTAMPTZ,
     }
 
     pub struct ReactionUsers in Lantern {
@@ -686,6 +814,35 @@ thorn::tables! {
         Preview: Nullable(Type::BYTEA),
     }
 
+    pub struct UserBans in Lantern {
+        UserId: Type::INT8,
+        BannedBy: Nullable(Type::INT8),
+        BannedAt: Type::TIMESTAMPTZ,
+        /// If NULL, the ban is permanent
+        Expires: Nullable(Type::TIMESTAMPTZ),
+        Reason: Nullable(Type::TEXT),
+    }
+
+    pub struct UserDeletions in Lantern {
+        UserId: Type::INT8,
+        RequestedAt: Type::TIMESTAMPTZ,
+        DeleteAt: Type::TIMESTAMPTZ,
+    }
+
+    pub struct UserDataExports in Lantern {
+        Id: Type::INT8,
+        UserId: Type::INT8,
+        RequestedAt: Type::TIMESTAMPTZ,
+        /// NULL until the archive has been written
+        CompletedAt: Nullable(Type::TIMESTAMPTZ),
+        /// Encryption Nonce
+        Nonce: Nullable(Type::INT8),
+        Size: Nullable(Type::INT8),
+        /// Download link token
+        Token: Nullable(Type::BYTEA),
+        Expires: Nullable(Type::TIMESTAMPTZ),
+    }
+
     pub struct UserFreelist in Lantern {
         Username: Type::TEXT,
         Discriminator: Type::INT4,
@@ -726,4 +883,20 @@ thorn::tables! {
         Preferences: Nullable(Type::JSONB),
     }
 
+    pub struct Webhooks in Lantern {
+        Id: Type::INT8,
+        RoomId: Type::INT8,
+        /// User that messages are posted as
+        UserId: Type::INT8,
+        CreatorId: Nullable(Type::INT8),
+        /// Maximum number of messages per minute
+        RateLimit: Type::INT2,
+        Flags: Type::INT2,
+        Token: Type::BYTEA,
+        /// Start of the current rate-limit window
+        WindowStart: Type::TIMESTAMPTZ,
+        /// Messages sent within the current rate-limit window
+        WindowCount: Type::INT2,
+    }
+
 }
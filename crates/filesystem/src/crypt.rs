@@ -131,29 +131,29 @@ where
     }
 }
 
-// impl<F: io::Write> io::Write for EncryptedFile<F> {
-//     fn flush(&mut self) -> io::Result<()> {
-//         self.inner.flush()
-//     }
+impl<F: io::Write> io::Write for EncryptedFile<F> {
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
 
-//     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-//         let pos: u64 = self.cipher.current_pos();
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let pos: u64 = self.cipher.current_pos();
 
-//         self.write_buf.clear();
-//         self.write_buf.extend_from_slice(&buf);
+        self.write_buf.clear();
+        self.write_buf.extend_from_slice(buf);
 
-//         self.cipher.apply_keystream(self.write_buf.as_mut_slice());
+        self.cipher.apply_keystream(self.write_buf.as_mut_slice());
 
-//         let bytes = self.inner.write(self.write_buf.as_slice())?;
+        let bytes = self.inner.write(self.write_buf.as_slice())?;
 
-//         if bytes < self.write_buf.len() {
-//             // partial rewind
-//             self.cipher.seek(pos + bytes as u64);
-//         }
+        if bytes < self.write_buf.len() {
+            // partial rewind
+            self.cipher.seek(pos + bytes as u64);
+        }
 
-//         Ok(bytes)
-//     }
-// }
+        Ok(bytes)
+    }
+}
 
 // impl<F: AsyncWrite + Unpin> AsyncWrite for EncryptedFile<F> {
 //     fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
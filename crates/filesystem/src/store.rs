@@ -75,6 +75,14 @@ pub struct FileStore {
 }
 
 impl FileStore {
+    /// Location of the file within the store, which may not exist
+    pub fn path(self, id: Snowflake) -> PathBuf {
+        let mut path = self.root;
+        id_to_path(id, &mut path);
+        id_to_name(id, &mut path);
+        path
+    }
+
     pub async fn delete(self, id: Snowflake) -> io::Result<()> {
         let mut path = self.root;
         id_to_path(id, &mut path);
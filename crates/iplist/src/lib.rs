@@ -159,12 +159,19 @@ impl IpList {
 
 use hashbrown::raw::RawTable;
 
+pub use ipnet::{IpNet, Ipv4Net, Ipv6Net};
+
 #[derive(Default, Clone)]
 pub struct IpSet {
     ipv4: Vec<Ipv4Addr>,
     ipv6: Vec<Ipv6Addr>,
     set: RawTable<u32>,
     hash_builder: foldhash::fast::RandomState,
+
+    /// Sorted, non-overlapping networks
+    ipv4_nets: Vec<Ipv4Net>,
+    /// Sorted, non-overlapping networks
+    ipv6_nets: Vec<Ipv6Net>,
 }
 
 const MAX_LEN: usize = 1 << 31;
@@ -177,6 +184,8 @@ impl IpSet {
             ipv6: Vec::new(),
             set: RawTable::new(),
             hash_builder: foldhash::fast::RandomState::default(),
+            ipv4_nets: Vec::new(),
+            ipv6_nets: Vec::new(),
         };
 
         this.refresh(ips);
@@ -184,6 +193,43 @@ impl IpSet {
         this
     }
 
+    /// Create a new set from individual addresses and CIDR networks
+    pub fn with_networks(ips: &[IpAddr], nets: &[IpNet]) -> Self {
+        let mut this = IpSet::new(ips);
+        this.refresh_networks(nets);
+        this
+    }
+
+    /// Replace all networks in the set, leaving individual addresses untouched
+    pub fn refresh_networks(&mut self, nets: &[IpNet]) {
+        self.ipv4_nets.clear();
+        self.ipv6_nets.clear();
+
+        for net in nets {
+            match net {
+                IpNet::V4(net) => self.ipv4_nets.push(net.trunc()),
+                IpNet::V6(net) => self.ipv6_nets.push(net.trunc()),
+            }
+        }
+
+        // merges overlapping and adjacent networks, and sorts them
+        self.ipv4_nets = Ipv4Net::aggregate(&self.ipv4_nets);
+        self.ipv6_nets = Ipv6Net::aggregate(&self.ipv6_nets);
+    }
+
+    pub fn add_network(&mut self, net: IpNet) {
+        match net {
+            IpNet::V4(net) => {
+                self.ipv4_nets.push(net.trunc());
+                self.ipv4_nets = Ipv4Net::aggregate(&self.ipv4_nets);
+            }
+            IpNet::V6(net) => {
+                self.ipv6_nets.push(net.trunc());
+                self.ipv6_nets = Ipv6Net::aggregate(&self.ipv6_nets);
+            }
+        }
+    }
+
     // Recreate IpSet without freeing memory
     pub fn refresh(&mut self, ips: &[IpAddr]) {
         use std::hash::{BuildHasher, Hash, Hasher};
@@ -250,7 +296,22 @@ impl IpSet {
     //}
 
     pub fn contains(&self, ip: &IpAddr) -> bool {
-        self.set.find(self.hash(ip), |idx| unsafe { self.cmp_eq(*idx, ip) }).is_some()
+        self.set.find(self.hash(ip), |idx| unsafe { self.cmp_eq(*idx, ip) }).is_some() || self.contains_network(ip)
+    }
+
+    // Find the last network starting at or before `ip`, and check if it contains `ip`.
+    // This relies on the networks being sorted and non-overlapping, as produced by `aggregate`.
+    fn contains_network(&self, ip: &IpAddr) -> bool {
+        match *ip {
+            IpAddr::V4(ip) => match self.ipv4_nets.partition_point(|net| net.network() <= ip) {
+                0 => false,
+                idx => self.ipv4_nets[idx - 1].contains(&ip),
+            },
+            IpAddr::V6(ip) => match self.ipv6_nets.partition_point(|net| net.network() <= ip) {
+                0 => false,
+                idx => self.ipv6_nets[idx - 1].contains(&ip),
+            },
+        }
     }
 
     pub fn add(&mut self, ip: IpAddr) {
@@ -276,6 +337,7 @@ impl IpSet {
             ref mut set,
             ref ipv4,
             ref ipv6,
+            ..
         } = self;
 
         set.insert(hash, idx, |&idx| unsafe {
@@ -311,4 +373,24 @@ mod tests {
             assert!(set.contains(banned));
         }
     }
+
+    #[test]
+    fn test_ipset_networks() {
+        let nets: Vec<IpNet> = vec![
+            "10.0.0.0/8".parse().unwrap(),
+            "192.168.1.0/24".parse().unwrap(),
+            "192.168.2.0/24".parse().unwrap(),
+            "2001:db8::/32".parse().unwrap(),
+        ];
+
+        let set = IpSet::with_networks(&["65.105.159.243".parse().unwrap()], &nets);
+
+        for ip in ["10.1.2.3", "192.168.1.255", "192.168.2.0", "2001:db8::1", "65.105.159.243"] {
+            assert!(set.contains(&ip.parse().unwrap()), "{ip}");
+        }
+
+        for ip in ["11.0.0.1", "192.168.3.1", "2001:db9::1", "9.255.255.255"] {
+            assert!(!set.contains(&ip.parse().unwrap()), "{ip}");
+        }
+    }
 }
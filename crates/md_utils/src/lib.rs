@@ -21,6 +21,7 @@ pub enum SpanType {
     Url,
     CustomEmote,
     UserMention,
+    RoleMention,
     RoomMention,
     Spoiler,
 }
@@ -144,6 +145,9 @@ fn scan_markdown_recursive<const S: bool>(input: &str, offset: usize, spans: &mu
 
             // mention
             [b'<', rest @ ..] => match rest {
+                [b'@', b'&', rest @ ..] => scan_substr(3, rest, Some(">"), char::is_ascii_digit, |len, _| {
+                    new_span!(3, i, len, SpanType::RoleMention);
+                }),
                 [n @ (b'@' | b'#'), rest @ ..] => {
                     scan_substr(2, rest, Some(">"), char::is_ascii_digit, |len, _| {
                         let kind = match n {
@@ -370,3 +374,25 @@ mod url_test {
         println!("{} + {}", regexes::NEWLINES.forward().memory_usage(), regexes::NEWLINES.reverse().memory_usage());
     }
 }
+
+#[cfg(test)]
+mod mention_test {
+    use super::*;
+
+    #[test]
+    fn test_mention_spans() {
+        let input = "<@123> <@&456> <#789> `<@1>`";
+        let spans = scan_markdown(input);
+
+        let mentions: Vec<_> = spans
+            .iter()
+            .filter(|s| matches!(s.kind(), SpanType::UserMention | SpanType::RoleMention | SpanType::RoomMention))
+            .map(|s| (s.kind(), &input[s.range()]))
+            .collect();
+
+        assert_eq!(
+            mentions,
+            [(SpanType::UserMention, "123"), (SpanType::RoleMention, "456"), (SpanType::RoomMention, "789")]
+        );
+    }
+}
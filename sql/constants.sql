@@ -191,4 +191,7 @@
 #define MESSAGE_UNREACT_EVENT   'message_unreact'
 #define PROFILE_UPDATED_EVENT   'profile_updated'
 #define REL_UPDATED_EVENT       'rel_updated'
-#define TOKEN_REFRESH_EVENT     'token_refresh'
\ No newline at end of file
+#define TOKEN_REFRESH_EVENT     'token_refresh'
+#define EMOTE_CREATED_EVENT     'emote_created'
+#define EMOTE_UPDATED_EVENT     'emote_updated'
+#define EMOTE_DELETED_EVENT     'emote_deleted'
\ No newline at end of file
@@ -126,7 +126,10 @@ CREATE TYPE lantern.event_code AS ENUM (
     MESSAGE_UNREACT_EVENT,
     PROFILE_UPDATED_EVENT,
     REL_UPDATED_EVENT,
-    TOKEN_REFRESH_EVENT
+    TOKEN_REFRESH_EVENT,
+    EMOTE_CREATED_EVENT,
+    EMOTE_UPDATED_EVENT,
+    EMOTE_DELETED_EVENT
 );
 
 CREATE SEQUENCE lantern.event_id AS bigint;
@@ -161,6 +164,16 @@ CREATE TABLE lantern.config (
     mfa_backup_count    int2        NOT NULL DEFAULT 8,
     mfa_pending_time    int8        NOT NULL DEFAULT (30 * MS_MINUTE),
     registration_token  text, -- used for closed registration
+    user_purge_delay    int8        NOT NULL DEFAULT (30 * MS_DAY), -- deleted users are purged after this long
+    user_deletion_delay int8        NOT NULL DEFAULT (14 * MS_DAY), -- cooling-off period for self-service account deletion
+    purge_message_content bool      NOT NULL DEFAULT false, -- if true, purged users have their messages tombstoned
+    data_export_cooldown int8       NOT NULL DEFAULT (7 * MS_DAY), -- minimum time between data export requests
+    data_export_expiry  int8        NOT NULL DEFAULT (3 * MS_DAY), -- how long a finished export can be downloaded
+
+    -- Security settings
+    rl_ban_threshold    int4        NOT NULL DEFAULT 100, -- rate-limit violations before an automatic IP ban
+    rl_ban_duration     int8        NOT NULL DEFAULT (15 * MS_MINUTE), -- doubled for each repeated ban
+    rl_violation_decay  int8        NOT NULL DEFAULT MS_HOUR, -- violations reset after this long without any
 
     -- User settings
     reltime_rnd_factor  float4      NOT NULL DEFAULT 0.1, -- 10% random factor for relative time
@@ -177,12 +190,17 @@ CREATE TABLE lantern.config (
     role_desc_len       int4range   NOT NULL DEFAULT int4range(1, 256),
     max_active_rooms    int2        NOT NULL DEFAULT 128,
     max_total_rooms     int2        NOT NULL DEFAULT 1024, -- including not-pruned deleted rooms
+    max_emotes          int2        NOT NULL DEFAULT 256, -- custom emotes per party
+    emote_name_len      int4range   NOT NULL DEFAULT int4range(2, 64),
+    audit_log_retention int8        NOT NULL DEFAULT (90 * MS_DAY), -- 90 days
 
     -- Message settings
     max_newlines        int2        NOT NULL DEFAULT 80,
     message_length      int4range   NOT NULL DEFAULT int4range(1, 2500),
     max_embeds          int2        NOT NULL DEFAULT 8, -- max embeds per message
+    max_attachments     int2        NOT NULL DEFAULT 10, -- max attachments per message
     regex_search_len    int2        NOT NULL DEFAULT 128,
+    revision_retention  int8        NOT NULL DEFAULT (30 * MS_DAY), -- how long previous versions of edited messages are kept
 
     -- Upload settings
     max_upload_size     int8        NOT NULL DEFAULT MAX_INT4, -- 2 GiB
@@ -246,6 +264,8 @@ ALTER SEQUENCE lantern.emoji_id OWNED BY lantern.emojis.id;
 CREATE TABLE lantern.factions (
     id          uuid        NOT NULL DEFAULT gen_random_uuid(), -- v4
     addr        inet        NOT NULL,
+    port        integer     NOT NULL,
+    last_seen   timestamptz NOT NULL DEFAULT now(),
     nickname    text,
 
     CONSTRAINT factions_pk PRIMARY KEY(id)
@@ -282,14 +302,25 @@ CREATE TABLE lantern.event_log_last_notification (
 COMMENT ON TABLE lantern.event_log_last_notification IS 'Notification rate-limiting table';
 
 CREATE TABLE lantern.rate_limits (
-    violations  integer     NOT NULL DEFAULT 0,
-    addr        inet        NOT NULL
+    violations      integer     NOT NULL DEFAULT 0,
+    addr            inet        NOT NULL,
+    -- number of automatic IP bans issued, used to escalate ban durations
+    bans            int2        NOT NULL DEFAULT 0,
+    last_violation  timestamptz NOT NULL DEFAULT now(),
+
+    CONSTRAINT rate_limits_pk PRIMARY KEY (addr)
 );
 
 CREATE TABLE lantern.ip_bans (
+    -- snowflake, also serves as the timestamp
+    id          bigint      NOT NULL,
+    -- if NULL, the ban is permanent
     expires     timestamptz,
     address     inet,
-    network     cidr
+    network     cidr,
+    reason      text,
+
+    CONSTRAINT ip_bans_pk PRIMARY KEY (id)
 );
 
 CREATE TABLE IF NOT EXISTS lantern.metrics (
@@ -338,6 +369,58 @@ CREATE TABLE lantern.apps (
     CONSTRAINT apps_pk PRIMARY KEY (id)
 );
 
+-- HTTP event delivery for an app's bot, as an alternative to holding a gateway connection
+CREATE TABLE lantern.app_subscriptions (
+    app_id          bigint      NOT NULL,
+    -- gateway intents to deliver events for
+    intents         int         NOT NULL,
+    url             text        NOT NULL,
+    -- key used to sign each delivery with HMAC-SHA256
+    secret          bytea       NOT NULL,
+
+    CONSTRAINT app_subscription_pk PRIMARY KEY (app_id)
+);
+
+-- Dead-letter log of event deliveries that failed after all retries
+CREATE TABLE lantern.failed_deliveries (
+    -- snowflake, doubles as the time of failure
+    id              bigint      NOT NULL,
+    app_id          bigint      NOT NULL,
+    attempts        smallint    NOT NULL,
+    -- last HTTP status received, if any
+    status          smallint,
+    error           text,
+    payload         jsonb       NOT NULL,
+
+    CONSTRAINT failed_delivery_pk PRIMARY KEY (id)
+);
+
+-- Slash commands registered by an app's bot, either globally or for a single party
+CREATE TABLE lantern.app_commands (
+    id              bigint      NOT NULL,
+    app_id          bigint      NOT NULL,
+    -- NULL for commands available in every party the bot is a member of
+    party_id        bigint,
+    name            text        NOT NULL,
+    description     text        NOT NULL,
+    -- argument schema, as an array of options in positional order
+    options         jsonb       NOT NULL,
+
+    CONSTRAINT app_command_pk PRIMARY KEY (id)
+);
+
+-- Slash command invocations, awaiting a response from the bot
+CREATE TABLE lantern.interactions (
+    -- snowflake, doubles as the time of invocation
+    id              bigint      NOT NULL,
+    command_id      bigint      NOT NULL,
+    user_id         bigint      NOT NULL,
+    room_id         bigint      NOT NULL,
+    flags           smallint    NOT NULL    DEFAULT 0,
+
+    CONSTRAINT interaction_pk PRIMARY KEY (id)
+);
+
 CREATE TABLE lantern.users (
     --- Snowflake id
     id              bigint              NOT NULL,
@@ -365,6 +448,46 @@ COMMENT ON COLUMN lantern.users.discriminator IS '2-byte integer that can be dis
 
 CREATE VIEW lantern.live_users AS SELECT * FROM lantern.users WHERE deleted_at IS NULL;
 
+-- Site-wide user bans, alongside the BANNED user flag
+CREATE TABLE lantern.user_bans (
+    user_id     bigint      NOT NULL,
+    -- site admin that issued the ban
+    banned_by   bigint,
+    banned_at   timestamptz NOT NULL DEFAULT now(),
+    -- if NULL, the ban is permanent
+    expires     timestamptz,
+    reason      text,
+
+    CONSTRAINT user_bans_pk PRIMARY KEY (user_id)
+);
+
+-- Pending self-service account deletions, cancellable until `delete_at`
+CREATE TABLE lantern.user_deletions (
+    user_id         bigint      NOT NULL,
+    requested_at    timestamptz NOT NULL DEFAULT now(),
+    delete_at       timestamptz NOT NULL,
+
+    CONSTRAINT user_deletions_pk PRIMARY KEY (user_id)
+);
+
+-- Archives of all personal data for a user, see `data_export` in the nexus
+CREATE TABLE lantern.user_data_exports (
+    -- also the file store id of the archive
+    id              bigint      NOT NULL,
+    user_id         bigint      NOT NULL,
+    requested_at    timestamptz NOT NULL DEFAULT now(),
+    -- NULL until the archive has been written
+    completed_at    timestamptz,
+    -- encryption nonce of the archive
+    nonce           bigint,
+    size            bigint,
+    -- download link token and its expiration
+    token           bytea,
+    expires         timestamptz,
+
+    CONSTRAINT user_data_exports_pk PRIMARY KEY (id)
+);
+
 CREATE TABLE lantern.user_freelist (
     username        text            NOT NULL,
     discriminator   lantern.uint2   NOT NULL
@@ -412,6 +535,16 @@ CREATE TABLE lantern.faction_parties (
     CONSTRAINT faction_parties_pk PRIMARY KEY(faction_id, party_id)
 );
 
+-- Pending party migrations, acknowledged by the faction serving the party
+-- once it has finished any requests for it already in progress
+CREATE TABLE lantern.party_fences (
+    party_id        bigint      NOT NULL,
+    faction_id      uuid        NOT NULL,
+    acked           boolean     NOT NULL DEFAULT false,
+
+    CONSTRAINT party_fences_pk PRIMARY KEY(party_id)
+);
+
 -- Association map between parties and users
 CREATE TABLE lantern.party_members (
     party_id        bigint          NOT NULL,
@@ -446,6 +579,24 @@ CREATE TABLE lantern.rooms (
 
 CREATE VIEW lantern.live_rooms AS SELECT * FROM lantern.rooms WHERE deleted_at IS NULL;
 
+-- Incoming webhooks, which post messages to a room as their own user
+CREATE TABLE lantern.webhooks (
+    id              bigint      NOT NULL,
+    room_id         bigint      NOT NULL,
+    -- references the user messages are posted as
+    user_id         bigint      NOT NULL,
+    creator_id      bigint,
+    -- maximum number of messages per minute
+    rate_limit      smallint    NOT NULL,
+    flags           smallint    NOT NULL    DEFAULT 0,
+    token           bytea       NOT NULL,
+    -- start of the current rate-limit window, and messages sent within it
+    window_start    timestamptz NOT NULL    DEFAULT now(),
+    window_count    smallint    NOT NULL    DEFAULT 0,
+
+    CONSTRAINT webhook_pk PRIMARY KEY (id)
+);
+
 -- Table for holding active per-room per-user settings
 CREATE TABLE lantern.room_members (
     user_id         bigint      NOT NULL,
@@ -510,9 +661,32 @@ CREATE TABLE lantern.files (
     -- this shouldn't be too large, less than 128 bytes
     preview     bytea,
 
+    -- Deduplicated contents, if NULL the contents are stored under the file's own id
+    blob_id     bigint,
+
     CONSTRAINT file_pk PRIMARY KEY (id)
 );
 COMMENT ON TABLE lantern.files IS 'Backing file table for all attachments, avatars and so forth';
+
+-- Deduplicated file contents, shared by every file with identical contents
+CREATE TABLE lantern.file_blobs (
+    -- Snowflake ID, also the location of the encrypted contents in the file store
+    id          bigint      NOT NULL,
+
+    -- Encryption Nonce
+    nonce       bigint      NOT NULL,
+
+    -- Size of contents in bytes
+    size        bigint      NOT NULL,
+
+    -- BLAKE3 hash of unencrypted contents, keyed by the file encryption key
+    blake3      bytea       NOT NULL,
+
+    -- Whether the contents have been copied to off-site object storage
+    replicated  boolean     NOT NULL DEFAULT false,
+
+    CONSTRAINT file_blobs_pk PRIMARY KEY (id)
+);
 COMMENT ON COLUMN lantern.files.nonce IS 'Encryption Nonce';
 COMMENT ON COLUMN lantern.files.size IS 'Size of file in bytes';
 COMMENT ON COLUMN lantern.files.name IS 'Filename given at upload';
@@ -614,6 +788,18 @@ CREATE TABLE lantern.message_stars (
     CONSTRAINT message_stars_pk PRIMARY KEY (msg_id, user_id)
 );
 
+-- Previous content of edited messages, kept for moderation until the revision retention expires
+CREATE TABLE lantern.message_revisions (
+    -- snowflake of when the edit was made
+    id          bigint      NOT NULL,
+    msg_id      bigint      NOT NULL,
+    editor_id   bigint,
+    content     text,
+
+    CONSTRAINT message_revisions_pk PRIMARY KEY (id)
+);
+ALTER TABLE lantern.message_revisions SET (toast_tuple_target = 256);
+
 CREATE TABLE lantern.embeds (
     id          bigint          NOT NULL,
     expires     timestamptz     NOT NULL DEFAULT now(),
@@ -793,11 +979,30 @@ CREATE TABLE IF NOT EXISTS lantern.party_bans (
     user_id     bigint      NOT NULL,
 
     banned_at   timestamptz NOT NULL DEFAULT now(),
+    -- if NULL, the ban is permanent
+    expires     timestamptz,
     reason      text,
 
     CONSTRAINT party_bans_pk PRIMARY KEY (party_id, user_id)
 );
 
+-- Persistent record of administrative actions within a party
+CREATE TABLE lantern.audit_log (
+    -- snowflake, also serves as the timestamp
+    id          bigint      NOT NULL,
+    party_id    bigint      NOT NULL,
+    -- who performed the action
+    user_id     bigint      NOT NULL,
+    -- the user, role, room, etc. being acted upon, if any
+    target_id   bigint,
+    action      int2        NOT NULL,
+    reason      text,
+    -- map of changed fields to their before and after values, e.g. {"name": {"old": "a", "new": "b"}}
+    changes     jsonb,
+
+    CONSTRAINT audit_log_pk PRIMARY KEY (id)
+);
+
 
 CREATE TABLE lantern.pin_tags (
     id          bigint      NOT NULL,
@@ -837,6 +1042,34 @@ ALTER TABLE lantern.apps ADD CONSTRAINT bot_user_fk FOREIGN KEY (bot_id)
     REFERENCES lantern.users (id) MATCH FULL
     ON DELETE SET NULL ON UPDATE CASCADE;
 
+ALTER TABLE lantern.app_subscriptions ADD CONSTRAINT app_fk FOREIGN KEY (app_id)
+    REFERENCES lantern.apps (id) MATCH FULL
+    ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE lantern.failed_deliveries ADD CONSTRAINT app_fk FOREIGN KEY (app_id)
+    REFERENCES lantern.apps (id) MATCH FULL
+    ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE lantern.app_commands ADD CONSTRAINT app_fk FOREIGN KEY (app_id)
+    REFERENCES lantern.apps (id) MATCH FULL
+    ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE lantern.app_commands ADD CONSTRAINT party_fk FOREIGN KEY (party_id)
+    REFERENCES lantern.party (id) MATCH FULL
+    ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE lantern.interactions ADD CONSTRAINT command_fk FOREIGN KEY (command_id)
+    REFERENCES lantern.app_commands (id) MATCH FULL
+    ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE lantern.interactions ADD CONSTRAINT user_fk FOREIGN KEY (user_id)
+    REFERENCES lantern.users (id) MATCH FULL
+    ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE lantern.interactions ADD CONSTRAINT room_fk FOREIGN KEY (room_id)
+    REFERENCES lantern.rooms (id) MATCH FULL
+    ON DELETE CASCADE ON UPDATE CASCADE;
+
 ALTER TABLE lantern.user_tokens ADD CONSTRAINT user_fk FOREIGN KEY (user_id)
     REFERENCES lantern.users (id) MATCH FULL
     ON DELETE CASCADE ON UPDATE CASCADE;
@@ -861,6 +1094,18 @@ ALTER TABLE lantern.rooms ADD CONSTRAINT parent_fk FOREIGN KEY (parent_id)
     REFERENCES lantern.rooms (id) MATCH FULL
     ON DELETE SET NULL ON UPDATE CASCADE;
 
+ALTER TABLE lantern.webhooks ADD CONSTRAINT room_fk FOREIGN KEY (room_id)
+    REFERENCES lantern.rooms (id) MATCH FULL
+    ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE lantern.webhooks ADD CONSTRAINT user_fk FOREIGN KEY (user_id)
+    REFERENCES lantern.users (id) MATCH FULL
+    ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE lantern.webhooks ADD CONSTRAINT creator_fk FOREIGN KEY (creator_id)
+    REFERENCES lantern.users (id) MATCH FULL
+    ON DELETE SET NULL ON UPDATE CASCADE;
+
 ALTER TABLE lantern.party ADD CONSTRAINT default_room_fk FOREIGN KEY (default_room)
     REFERENCES lantern.rooms (id) MATCH FULL
     ON DELETE RESTRICT ON UPDATE CASCADE -- don't allow deleting default room
@@ -882,6 +1127,10 @@ ALTER TABLE lantern.files ADD CONSTRAINT user_fk FOREIGN KEY (user_id)
     REFERENCES lantern.users (id) MATCH FULL
     ON DELETE RESTRICT ON UPDATE CASCADE;
 
+ALTER TABLE lantern.files ADD CONSTRAINT blob_fk FOREIGN KEY (blob_id)
+    REFERENCES lantern.file_blobs (id) MATCH FULL
+    ON DELETE RESTRICT ON UPDATE CASCADE;
+
 ALTER TABLE lantern.user_assets ADD CONSTRAINT file_id_fk FOREIGN KEY (file_id)
     REFERENCES lantern.files (id) MATCH FULL
     ON DELETE CASCADE ON UPDATE CASCADE;
@@ -948,6 +1197,14 @@ ALTER TABLE lantern.message_pins ADD CONSTRAINT pin_fk FOREIGN KEY (pin_id)
     REFERENCES lantern.pin_tags (id) MATCH FULL
     ON DELETE CASCADE ON UPDATE CASCADE;
 
+ALTER TABLE lantern.message_revisions ADD CONSTRAINT msg_fk FOREIGN KEY (msg_id)
+    REFERENCES lantern.messages (id) MATCH FULL
+    ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE lantern.message_revisions ADD CONSTRAINT editor_fk FOREIGN KEY (editor_id)
+    REFERENCES lantern.users (id) MATCH SIMPLE
+    ON DELETE SET NULL ON UPDATE CASCADE;
+
 ALTER TABLE lantern.message_stars ADD CONSTRAINT msg_fk FOREIGN KEY (msg_id)
     REFERENCES lantern.messages (id) MATCH FULL
     ON DELETE CASCADE ON UPDATE CASCADE;
@@ -1113,6 +1370,22 @@ ALTER TABLE lantern.user_presence ADD CONSTRAINT user_fk FOREIGN KEY(user_id)
     REFERENCES lantern.users (id) MATCH FULL
     ON DELETE CASCADE ON UPDATE CASCADE;
 
+ALTER TABLE lantern.user_bans ADD CONSTRAINT user_fk FOREIGN KEY (user_id)
+    REFERENCES lantern.users (id) MATCH FULL
+    ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE lantern.user_bans ADD CONSTRAINT banned_by_fk FOREIGN KEY (banned_by)
+    REFERENCES lantern.users (id) MATCH SIMPLE
+    ON DELETE SET NULL ON UPDATE CASCADE;
+
+ALTER TABLE lantern.user_deletions ADD CONSTRAINT user_fk FOREIGN KEY (user_id)
+    REFERENCES lantern.users (id) MATCH FULL
+    ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE lantern.user_data_exports ADD CONSTRAINT user_fk FOREIGN KEY (user_id)
+    REFERENCES lantern.users (id) MATCH FULL
+    ON DELETE CASCADE ON UPDATE CASCADE;
+
 ALTER TABLE lantern.party_bans ADD CONSTRAINT party_fk FOREIGN KEY (party_id)
     REFERENCES lantern.party (id) MATCH FULL
     ON DELETE CASCADE ON UPDATE CASCADE;
@@ -1121,6 +1394,14 @@ ALTER TABLE lantern.party_bans ADD CONSTRAINT user_fk FOREIGN KEY (user_id)
     REFERENCES lantern.users (id) MATCH FULL
     ON DELETE CASCADE ON UPDATE CASCADE;
 
+ALTER TABLE lantern.audit_log ADD CONSTRAINT party_fk FOREIGN KEY (party_id)
+    REFERENCES lantern.party (id) MATCH FULL
+    ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE lantern.audit_log ADD CONSTRAINT user_fk FOREIGN KEY (user_id)
+    REFERENCES lantern.users (id) MATCH FULL
+    ON DELETE CASCADE ON UPDATE CASCADE;
+
 ALTER TABLE lantern.pin_tags ADD CONSTRAINT party_fk FOREIGN KEY (party_id)
     REFERENCES lantern.party (id) MATCH FULL
     ON DELETE CASCADE ON UPDATE CASCADE;
@@ -1210,6 +1491,20 @@ CREATE UNIQUE INDEX reactions_emoji_idx ON lantern.reactions
 CREATE UNIQUE INDEX emoji_idx ON lantern.emojis
     USING btree(emoji);
 
+CREATE UNIQUE INDEX file_blobs_blake3_idx ON lantern.file_blobs
+    USING btree(blake3);
+
+CREATE UNIQUE INDEX webhook_user_idx ON lantern.webhooks
+    USING btree(user_id);
+
+CREATE UNIQUE INDEX app_bot_idx ON lantern.apps
+    USING btree(bot_id) WHERE bot_id IS NOT NULL;
+
+CREATE UNIQUE INDEX app_command_global_name_idx ON lantern.app_commands
+    USING btree(app_id, name) WHERE party_id IS NULL;
+CREATE UNIQUE INDEX app_command_party_name_idx ON lantern.app_commands
+    USING btree(app_id, party_id, name) WHERE party_id IS NOT NULL;
+
 ----------------------------------------
 -------------- INDICES -----------------
 ----------------------------------------
@@ -1226,10 +1521,18 @@ CREATE INDEX user_tokens_token_idx          ON lantern.user_tokens      USING ha
 CREATE INDEX user_tokens_expires_idx        ON lantern.user_tokens      USING btree(expires);
 CREATE INDEX party_name_idx                 ON lantern.party            USING btree(name);
 CREATE INDEX party_member_user_idx          ON lantern.party_members    USING btree(user_id, party_id);
+-- each party is hosted by at most one faction
+CREATE UNIQUE INDEX faction_parties_party_idx ON lantern.faction_parties  USING btree(party_id);
 CREATE INDEX room_name_idx                  ON lantern.rooms            USING btree(name);
 CREATE INDEX room_party_idx                 ON lantern.rooms            USING btree(party_id);
 CREATE INDEX room_avatar_idx                ON lantern.rooms            USING btree(avatar_id) WHERE avatar_id IS NOT NULL;
 CREATE INDEX file_idx                       ON lantern.files            USING btree(user_id)        INCLUDE (size);
+CREATE INDEX file_blob_idx                  ON lantern.files            USING btree(blob_id)        WHERE blob_id IS NOT NULL;
+CREATE INDEX file_blob_pending_idx          ON lantern.file_blobs       USING btree(id)             WHERE NOT replicated;
+CREATE INDEX app_owner_idx                  ON lantern.apps             USING btree(owner_id);
+CREATE INDEX failed_delivery_app_idx        ON lantern.failed_deliveries USING btree(app_id, id);
+CREATE INDEX app_command_name_idx           ON lantern.app_commands     USING btree(name);
+CREATE INDEX webhook_room_idx               ON lantern.webhooks         USING btree(room_id);
 CREATE INDEX user_asset_original_file_idx   ON lantern.user_assets      USING btree(file_id);
 
 -- TODO: Is this even necessary with such a simple table? The index itself has the same information as the actual table
@@ -1242,6 +1545,8 @@ CREATE INDEX msg_parent_idx                 ON lantern.messages USING btree(pare
     WHERE flags & MESSAGE_DELETED_PARENT != MESSAGE_DELETED -- live messages only
       AND parent_id IS NOT NULL; -- only children
 
+CREATE INDEX message_revision_msg_idx       ON lantern.message_revisions USING btree(msg_id, id);
+
 -- Use HASH for this to save space
 CREATE INDEX embed_url_idx                  ON lantern.embeds           USING HASH(url);
 CREATE INDEX embed_ty_idx                   ON lantern.embeds           USING btree((embed->>'ty'));
@@ -1267,11 +1572,21 @@ CREATE INDEX mention_msg_idx                ON lantern.mentions         USING bt
 CREATE INDEX mention_user_idx               ON lantern.mentions         USING btree (user_id) WHERE user_id IS NOT NULL;
 CREATE INDEX mention_role_idx               ON lantern.mentions         USING btree (role_id) WHERE role_id IS NOT NULL;
 
-CREATE INDEX rate_limit_idx                 ON lantern.rate_limits      USING btree(addr);
+CREATE INDEX audit_log_party_idx            ON lantern.audit_log        USING btree(party_id, id);
+CREATE INDEX user_bans_expires_idx          ON lantern.user_bans        USING btree(expires) WHERE expires IS NOT NULL;
+CREATE INDEX user_deletions_delete_at_idx   ON lantern.user_deletions   USING btree(delete_at);
+CREATE INDEX user_data_exports_user_idx     ON lantern.user_data_exports USING btree(user_id, requested_at);
+CREATE INDEX user_data_exports_expires_idx  ON lantern.user_data_exports USING btree(expires) WHERE expires IS NOT NULL;
+CREATE INDEX users_purge_idx                ON lantern.users            USING btree(deleted_at) WHERE deleted_at IS NOT NULL AND email NOT SIMILAR TO '%@purged.invalid';
+CREATE INDEX party_bans_expires_idx         ON lantern.party_bans       USING btree(expires) WHERE expires IS NOT NULL;
+CREATE INDEX rate_limit_last_violation_idx  ON lantern.rate_limits      USING btree(last_violation);
 CREATE INDEX ip_bans_address_idx            ON lantern.ip_bans          USING btree(address) WHERE address IS NOT NULL;
 CREATE INDEX ip_bans_network_idx            ON lantern.ip_bans          USING GIST(network inet_ops) WHERE network IS NOT NULL;
+CREATE INDEX ip_bans_expires_idx            ON lantern.ip_bans          USING btree(expires) WHERE expires IS NOT NULL;
 CREATE INDEX relationships_idx              ON lantern.relationships    USING btree(user_b_id, user_a_id);
 
+CREATE INDEX party_member_mute_idx          ON lantern.party_members    USING btree(mute_until) WHERE mute_until IS NOT NULL;
+CREATE INDEX room_member_mute_idx           ON lantern.room_members     USING btree(mute_expires) WHERE mute_expires IS NOT NULL;
 CREATE INDEX room_member_wallpaper_idx      ON lantern.room_members     USING btree(wallpaper_id) WHERE wallpaper_id IS NOT NULL;
 
 ----------------------------------------
@@ -1319,6 +1634,72 @@ FOR EACH ROW EXECUTE FUNCTION lantern.ev_notify_trigger();
 
 --
 
+-- Lets the nexus know to push the updated ban list to gateways
+CREATE OR REPLACE FUNCTION lantern.ip_bans_notify_trigger()
+RETURNS trigger
+LANGUAGE plpgsql AS
+$$
+BEGIN
+    PERFORM pg_notify('ip_bans', '');
+    RETURN NULL;
+END
+$$;
+
+CREATE TRIGGER ip_bans_notify AFTER INSERT OR UPDATE OR DELETE ON lantern.ip_bans
+FOR EACH STATEMENT EXECUTE FUNCTION lantern.ip_bans_notify_trigger();
+
+--
+
+-- Lets the faction serving a party know to drain its requests for a migration
+CREATE OR REPLACE FUNCTION lantern.party_fences_notify_trigger()
+RETURNS trigger
+LANGUAGE plpgsql AS
+$$
+BEGIN
+    PERFORM pg_notify('party_fences', NEW.faction_id::text);
+    RETURN NULL;
+END
+$$;
+
+CREATE TRIGGER party_fences_notify AFTER INSERT OR UPDATE ON lantern.party_fences
+FOR EACH ROW WHEN (NOT NEW.acked) EXECUTE FUNCTION lantern.party_fences_notify_trigger();
+
+--
+
+-- Lets every node reload the app subscriptions it checks events against
+CREATE OR REPLACE FUNCTION lantern.app_subscriptions_notify_trigger()
+RETURNS trigger
+LANGUAGE plpgsql AS
+$$
+BEGIN
+    PERFORM pg_notify('app_subscriptions', '');
+    RETURN NULL;
+END
+$$;
+
+CREATE TRIGGER app_subscriptions_notify AFTER INSERT OR UPDATE OR DELETE ON lantern.app_subscriptions
+FOR EACH STATEMENT EXECUTE FUNCTION lantern.app_subscriptions_notify_trigger();
+
+--
+
+-- Lets the nexus know to reload the shared config and push it to gateways,
+-- bumping `last_updated` so manual edits also change the config ETag
+CREATE OR REPLACE FUNCTION lantern.config_notify_trigger()
+RETURNS trigger
+LANGUAGE plpgsql AS
+$$
+BEGIN
+    NEW.last_updated := now();
+    PERFORM pg_notify('config', '');
+    RETURN NEW;
+END
+$$;
+
+CREATE TRIGGER config_notify BEFORE INSERT OR UPDATE ON lantern.config
+FOR EACH ROW EXECUTE FUNCTION lantern.config_notify_trigger();
+
+--
+
 CREATE OR REPLACE FUNCTION lantern.on_app_update()
 RETURNS TRIGGER
 LANGUAGE plpgsql AS
@@ -1460,6 +1841,10 @@ BEGIN
             ),
             NEW.user_id,
             NEW.party_id;
+    ELSEIF OLD.mute_until IS DISTINCT FROM NEW.mute_until THEN
+        -- timeout applied, changed or lifted
+        INSERT INTO lantern.event_log (code, id, party_id)
+        VALUES (MEMBER_UPDATED_EVENT::lantern.event_code, NEW.user_id, NEW.party_id);
     END IF;
 
     RETURN NEW;
@@ -1499,6 +1884,33 @@ FOR EACH ROW EXECUTE FUNCTION lantern.role_member_trigger();
 
 --
 
+-- Applying or lifting a per-room timeout should trigger a member_updated event
+CREATE OR REPLACE FUNCTION lantern.room_member_mute_trigger()
+RETURNS trigger
+LANGUAGE plpgsql AS
+$$
+BEGIN
+    INSERT INTO lantern.event_log (code, id, party_id, room_id)
+    SELECT MEMBER_UPDATED_EVENT::lantern.event_code,
+        NEW.user_id,
+        rooms.party_id,
+        rooms.id
+    FROM lantern.rooms WHERE rooms.id = NEW.room_id;
+
+    RETURN NEW;
+END
+$$;
+
+CREATE TRIGGER room_member_mute_insert_event AFTER INSERT ON lantern.room_members
+FOR EACH ROW WHEN (NEW.mute_expires IS NOT NULL)
+EXECUTE FUNCTION lantern.room_member_mute_trigger();
+
+CREATE TRIGGER room_member_mute_update_event AFTER UPDATE OF mute_expires ON lantern.room_members
+FOR EACH ROW WHEN (OLD.mute_expires IS DISTINCT FROM NEW.mute_expires)
+EXECUTE FUNCTION lantern.room_member_mute_trigger();
+
+--
+
 -- emit role_deleted/created/updated events
 CREATE OR REPLACE FUNCTION lantern.role_trigger()
 RETURNS trigger
@@ -1526,6 +1938,36 @@ FOR EACH ROW EXECUTE FUNCTION lantern.role_trigger();
 
 --
 
+-- emit emote_deleted/created/updated events
+--
+-- NOTE: Only party emotes emit events, global emotes have no party to send events to
+CREATE OR REPLACE FUNCTION lantern.emote_trigger()
+RETURNS trigger
+LANGUAGE plpgsql AS
+$$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        IF OLD.party_id IS NOT NULL THEN
+            INSERT INTO lantern.event_log (code, id, party_id)
+            VALUES (EMOTE_DELETED_EVENT::lantern.event_code, OLD.id, OLD.party_id);
+        END IF;
+    ELSIF NEW.party_id IS NOT NULL THEN
+        INSERT INTO lantern.event_log(code, id, party_id)
+        SELECT
+            IIF(TG_OP = 'INSERT', EMOTE_CREATED_EVENT::lantern.event_code, EMOTE_UPDATED_EVENT::lantern.event_code),
+            NEW.id,
+            NEW.party_id;
+    END IF;
+
+    RETURN NEW;
+END
+$$;
+
+CREATE TRIGGER emote_event AFTER UPDATE OR INSERT OR DELETE ON lantern.emotes
+FOR EACH ROW EXECUTE FUNCTION lantern.emote_trigger();
+
+--
+
 -- emit 'self_updated' or 'user_updated' events
 -- NOTE: Should be kept in-sync with user fields
 CREATE OR REPLACE FUNCTION lantern.user_trigger()
@@ -2241,7 +2683,9 @@ SELECT
     rooms.*, party_members.user_id, party_members.joined_at,
     -- if user is admin, return -1, otherwise return the permissions
     IIF(party_members.permissions1 = -1, -1::bigint, COALESCE(allow1, 0) | (party_members.permissions1 & ~COALESCE(deny1, 0))) AS permissions1,
-    IIF(party_members.permissions2 = -1, -1::bigint, COALESCE(allow2, 0) | (party_members.permissions2 & ~COALESCE(deny2, 0))) AS permissions2
+    IIF(party_members.permissions2 = -1, -1::bigint, COALESCE(allow2, 0) | (party_members.permissions2 & ~COALESCE(deny2, 0))) AS permissions2,
+    -- party-wide or per-room timeout, GREATEST ignores NULLs
+    COALESCE(GREATEST(party_members.mute_until, room_members.mute_expires) > now(), FALSE) AS muted
 FROM
     lantern.party_members
         INNER JOIN lantern.live_rooms rooms ON rooms.party_id = party_members.party_id
@@ -2437,6 +2881,20 @@ SELECT my.user_id, o.user_id, my.party_id FROM
 
 --
 
+-- Active IP bans, with networks split into address and prefix length
+CREATE OR REPLACE VIEW lantern.agg_ip_bans(id, expires, address, network, prefix) AS
+SELECT
+    ip_bans.id,
+    ip_bans.expires,
+    ip_bans.address,
+    ip_bans.network::inet,
+    masklen(ip_bans.network)::int2
+FROM lantern.ip_bans
+WHERE ip_bans.expires IS NULL OR ip_bans.expires > now()
+;
+
+--
+
 -- NOTE: Just search for `REFERENCES lantern.files` to find which tables should be here
 CREATE OR REPLACE VIEW lantern.agg_used_files(id) AS
 SELECT file_id FROM lantern.user_assets
@@ -2448,6 +2906,32 @@ UNION ALL
 SELECT wallpaper_id FROM lantern.room_members WHERE wallpaper_id IS NOT NULL
 ;
 
+-- Assets still displayed by parties, rooms, roles, emotes or profiles, including those of webhooks.
+-- Any others have been replaced or removed, and are reclaimed by the orphan cleanup task.
+CREATE OR REPLACE VIEW lantern.agg_used_assets(id) AS
+SELECT avatar_id FROM lantern.party WHERE avatar_id IS NOT NULL
+UNION ALL
+SELECT banner_id FROM lantern.party WHERE banner_id IS NOT NULL
+UNION ALL
+SELECT avatar_id FROM lantern.rooms WHERE avatar_id IS NOT NULL
+UNION ALL
+SELECT avatar_id FROM lantern.roles WHERE avatar_id IS NOT NULL
+UNION ALL
+SELECT asset_id FROM lantern.emotes
+UNION ALL
+SELECT avatar_id FROM lantern.profiles WHERE avatar_id IS NOT NULL
+UNION ALL
+SELECT banner_id FROM lantern.profiles WHERE banner_id IS NOT NULL
+;
+
+-- Files behind assets still displayed by parties, rooms, roles, emotes or other users' profiles,
+-- which outlive the account that uploaded them
+CREATE OR REPLACE VIEW lantern.agg_shared_asset_files(id) AS
+SELECT user_assets.file_id FROM lantern.user_assets INNER JOIN lantern.agg_used_assets ON agg_used_assets.id = user_assets.id
+UNION ALL
+SELECT user_asset_files.file_id FROM lantern.user_asset_files INNER JOIN lantern.agg_used_assets ON agg_used_assets.id = user_asset_files.asset_id
+;
+
 --
 
 -- provided solely for the ORDER BY clause
@@ -2564,6 +3048,8 @@ BEGIN
     FROM
         lantern.live_parties party
             LEFT JOIN lantern.party_bans ON party_bans.party_id = party.id AND party_bans.user_id = _user_id
+                -- expired bans are lifted by a cleanup task, but may linger until then
+                AND (party_bans.expires IS NULL OR party_bans.expires > now())
     WHERE
         invite.uses > 0
         AND invite.expires > now()
@@ -2693,7 +3179,25 @@ LANGUAGE plpgsql AS
 $$
 BEGIN
     UPDATE lantern.users SET deleted_at = now() WHERE id = _user_id;
-    CALL lantern.update_user(_user_id, _new_username);
+    -- releases the old username and discriminator to the freelist
+    CALL lantern.update_user(_user_id, _new_username, NULL, NULL);
+
+    -- hand owned parties to the longest-standing remaining member
+    UPDATE lantern.party SET owner_id = successor.user_id
+    FROM (
+        SELECT DISTINCT ON (party_members.party_id) party_members.party_id, party_members.user_id
+        FROM lantern.party_members INNER JOIN lantern.party ON party.id = party_members.party_id
+        WHERE party.owner_id = _user_id
+          AND party_members.user_id <> _user_id
+          AND party_members.flags & 1 = 0 -- not banned
+        ORDER BY party_members.party_id, party_members.joined_at ASC
+    ) AS successor
+    WHERE party.id = successor.party_id;
+
+    -- and delete any with nobody left to take them
+    UPDATE lantern.party SET deleted_at = now() WHERE owner_id = _user_id AND deleted_at IS NULL;
+
+    DELETE FROM lantern.user_deletions WHERE user_id = _user_id;
     DELETE FROM lantern.sessions WHERE user_id = _user_id;
     DELETE FROM lantern.user_tokens WHERE user_id = _user_id;
     DELETE FROM lantern.user_presence WHERE user_id = _user_id;
@@ -2704,4 +3208,28 @@ BEGIN
     DELETE FROM lantern.role_members WHERE user_id = _user_id;
     DELETE FROM lantern.party_members WHERE user_id = _user_id;
 END
+$$;
+
+-- Scrubs the remaining personal data of a soft-deleted user.
+-- The row itself is kept for referential integrity, with a unique placeholder email marking it as purged.
+-- Uploaded files are removed separately by the purge task, which needs their blobs.
+CREATE OR REPLACE PROCEDURE lantern.purge_user(
+    _user_id bigint
+)
+LANGUAGE plpgsql AS
+$$
+BEGIN
+    UPDATE lantern.users SET
+        email = _user_id || '@purged.invalid',
+        passhash = '',
+        mfa = NULL,
+        preferences = NULL,
+        last_active = NULL,
+        dob = '1970-01-01'
+    WHERE id = _user_id AND deleted_at IS NOT NULL;
+
+    DELETE FROM lantern.relationships WHERE user_a_id = _user_id OR user_b_id = _user_id;
+    DELETE FROM lantern.user_bans WHERE user_id = _user_id;
+    DELETE FROM lantern.user_data_exports WHERE user_id = _user_id AND completed_at IS NULL;
+END
 $$;
\ No newline at end of file